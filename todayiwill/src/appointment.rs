@@ -1,20 +1,95 @@
+use chrono::{DateTime, Local, NaiveDate};
 use colored::Colorize;
-use core::fmt;
+use core::{cmp::Ordering, fmt};
+use serde_json::{Map, Value};
 use std::str;
 
 extern crate dirs;
 
+pub mod agenda;
+pub mod duration;
 pub mod helper;
+pub mod ics;
+pub mod journal;
 pub mod list;
+pub mod natural;
+pub mod next;
+pub mod output;
+pub mod recurrence;
+pub mod reminder;
+pub mod search;
+pub mod settings;
+pub mod status;
+pub mod storage;
 pub mod time;
+pub mod tui;
+pub mod zone;
 
-use time::AppointmentTime;
+use duration::Duration;
+use status::Status;
+use time::{AppointmentDateTime, AppointmentTime};
+use zone::Zone;
+
+/// Describe the storage bookkeeping of an appointment. It is not taken into account when
+/// comparing appointments
+#[derive(Clone, Debug, Default)]
+pub struct Metadata {
+    /// Identifier of the appointment inside its day file, `0` when not assigned yet
+    pub id: u32,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+    /// Fields found in the file that this version does not know about, kept on rewrites
+    pub extra: Map<String, Value>,
+}
+
+impl PartialEq for Metadata {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Metadata {}
+
+impl PartialOrd for Metadata {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Metadata {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+/// Describe the changes to be applied to an appointment. Fields left as `None` are kept
+#[derive(Clone, Debug, Default)]
+pub struct AppointmentChanges {
+    pub time: Option<AppointmentTime>,
+    pub description: Option<String>,
+    /// Time the appointment ends, considering the new start time
+    pub end_time: Option<AppointmentTime>,
+    /// Duration, it takes precedence over the end time
+    pub duration: Option<Duration>,
+    pub status: Option<Status>,
+    /// Tags replacing the current ones
+    pub tags: Option<Vec<String>>,
+}
 
 /// Describe an appointment
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Appointment {
+    /// Date the appointment starts on, set by the lists of a date and written along with the
+    /// time, so the start is a full date and time. `None` for appointments not in a list yet
+    date: Option<NaiveDate>,
     time: AppointmentTime,
     description: String,
+    duration: Option<Duration>,
+    status: Status,
+    tags: Vec<String>,
+    recurrence_id: Option<u32>,
+    zone: Option<Zone>,
+    metadata: Metadata,
 }
 
 impl Appointment {
@@ -29,10 +104,274 @@ impl Appointment {
     /// assert_eq!("09:30 A description", appointment.to_string());
     /// ```
     pub fn new(description: String, time: AppointmentTime) -> Self {
-        Self { description, time }
+        Self {
+            date: None,
+            description,
+            time,
+            duration: None,
+            status: Status::Pending,
+            tags: vec![],
+            recurrence_id: None,
+            zone: None,
+            metadata: Metadata::default(),
+        }
+    }
+
+    /// Sets the duration of the `Appointment`, counted in whole minutes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Duration};
+    ///
+    /// let appointment = Appointment::new(String::from("Workshop"), AppointmentTime::new(14, 0).unwrap()).with_duration(Duration::from_minutes(90));
+    /// assert_eq!("14:00-15:30 Workshop", appointment.to_string());
+    /// ```
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Returns the duration of the `Appointment`, if any
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns the time of the day the `Appointment` ends, which is on the next day when it
+    /// crosses midnight. Appointments without duration end when they start
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Duration};
+    ///
+    /// let appointment = Appointment::new(String::from("Workshop"), AppointmentTime::new(14, 0).unwrap()).with_duration(Duration::from_minutes(120));
+    /// assert_eq!(AppointmentTime::new(16, 0).unwrap(), appointment.end_time());
+    /// let appointment = Appointment::new(String::from("Night shift"), AppointmentTime::new(22, 0).unwrap()).with_duration(Duration::from_minutes(480));
+    /// assert_eq!(AppointmentTime::new(6, 0).unwrap(), appointment.end_time());
+    /// ```
+    pub fn end_time(&self) -> AppointmentTime {
+        self.time.clone() + self.duration.unwrap_or_default()
+    }
+
+    /// Sets the date the `Appointment` starts on
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let appointment = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap()).with_date(date);
+    /// assert_eq!(Some(date), appointment.date());
+    /// ```
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// Returns the date the `Appointment` starts on, if known
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.date
     }
 
-    /// Creates an `Appointment` from a string slice
+    /// Returns when the `Appointment` starts, on its own date or on the given one when it has
+    /// no date
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let appointment = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap());
+    /// assert_eq!("2024-06-10 09:30:00", appointment.starts_at(date).to_string());
+    /// let appointment = appointment.with_date(date.pred_opt().unwrap());
+    /// assert_eq!("2024-06-09 09:30:00", appointment.starts_at(date).to_string());
+    /// ```
+    pub fn starts_at(&self, date: NaiveDate) -> AppointmentDateTime {
+        AppointmentDateTime::new(self.date.unwrap_or(date), &self.time)
+    }
+
+    /// Returns when the `Appointment` ends, on its own date or on the given one like `starts_at`.
+    /// It is on the next day when the appointment crosses midnight
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Duration};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let appointment = Appointment::new(String::from("Night shift"), AppointmentTime::new(22, 0).unwrap()).with_duration(Duration::from_minutes(480));
+    /// assert_eq!("2024-06-11 06:00:00", appointment.ends_at(date).to_string());
+    /// ```
+    pub fn ends_at(&self, date: NaiveDate) -> AppointmentDateTime {
+        self.starts_at(date) + self.duration.unwrap_or_default()
+    }
+
+    /// Checks if the `Appointment` ends on a later day than it starts
+    pub fn crosses_midnight(&self) -> bool {
+        let date = NaiveDate::default();
+        self.ends_at(date).date() > date
+    }
+
+    /// Sets what happened to the `Appointment`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Status};
+    ///
+    /// let appointment = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap()).with_status(Status::Done);
+    /// assert_eq!(Status::Done, appointment.status());
+    /// ```
+    pub fn with_status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Returns what happened to the `Appointment`
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Adds a tag to the `Appointment`. The tag may be written with or without the leading `#`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime};
+    ///
+    /// let appointment = Appointment::new(String::from("Deploy"), AppointmentTime::new(9, 30).unwrap()).with_tag("#OnCall").unwrap();
+    /// assert_eq!(&[String::from("oncall")], appointment.tags());
+    /// assert!(appointment.clone().with_tag("on call").is_err());
+    /// ```
+    pub fn with_tag(mut self, tag: &str) -> Result<Self, String> {
+        let tag = helper::parse_tag(tag)?;
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        Ok(self)
+    }
+
+    /// Returns the tags of the `Appointment`, without the leading `#`
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Checks if the `Appointment` has a tag, written with or without the leading `#`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Appointment;
+    ///
+    /// let appointment = Appointment::from("09:30 Deploy #oncall").unwrap();
+    /// assert!(appointment.has_tag("#oncall"));
+    /// assert!(!appointment.has_tag("work"));
+    /// ```
+    pub fn has_tag(&self, tag: &str) -> bool {
+        helper::parse_tag(tag).is_ok_and(|tag| self.tags.contains(&tag))
+    }
+
+    /// Marks the `Appointment` as an occurrence of a recurrence. Occurrences are not written to
+    /// the appointments file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime};
+    ///
+    /// let appointment = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap()).with_recurrence_id(2);
+    /// assert_eq!(Some(2), appointment.recurrence_id());
+    /// ```
+    pub fn with_recurrence_id(mut self, recurrence_id: u32) -> Self {
+        self.recurrence_id = Some(recurrence_id);
+        self
+    }
+
+    /// Sets the time zone the time of the `Appointment` is written in. Appointments without one
+    /// are in the time zone of their calendar
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Zone};
+    ///
+    /// let tokyo = Zone::from("Asia/Tokyo").unwrap();
+    /// let appointment = Appointment::new(String::from("Sync"), AppointmentTime::new(9, 0).unwrap()).with_zone(tokyo);
+    /// assert_eq!(Some(tokyo), appointment.zone());
+    /// ```
+    pub fn with_zone(mut self, zone: Zone) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// Returns the time zone the time of the `Appointment` is written in, if any
+    pub fn zone(&self) -> Option<Zone> {
+        self.zone
+    }
+
+    /// Returns the `Appointment` of a date as shown in another time zone, with the date it falls
+    /// on there. Appointments without a time zone are taken in the default one. The returned
+    /// appointment has no time zone
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Duration, Zone};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let berlin = Zone::from("Europe/Berlin").unwrap();
+    /// let sao_paulo = Zone::from("America/Sao_Paulo").unwrap();
+    /// let appointment = Appointment::new(String::from("Sync"), AppointmentTime::new(3, 0).unwrap())
+    ///     .with_duration(Duration::from_minutes(30))
+    ///     .with_zone(berlin);
+    /// let (shown_on, shown) = appointment.in_zone(date, None, &sao_paulo);
+    /// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 9).unwrap(), shown_on);
+    /// assert_eq!("22:00-22:30 Sync", shown.to_string());
+    /// ```
+    pub fn in_zone(&self, date: NaiveDate, default: Option<Zone>, to: &Zone) -> (NaiveDate, Self) {
+        let from = self.zone.or(default).unwrap_or(*to);
+        let starts_at = from.convert(self.starts_at(date), to);
+        let mut appointment = self.clone();
+        appointment.date = Some(starts_at.date());
+        appointment.time = starts_at.time();
+        appointment.zone = None;
+        (starts_at.date(), appointment)
+    }
+
+    /// Returns the time of the `Appointment`
+    pub fn time(&self) -> &AppointmentTime {
+        &self.time
+    }
+
+    /// Returns the description of the `Appointment`
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the identifier of the `Appointment` inside its day file, `0` when not stored yet
+    pub fn id(&self) -> u32 {
+        self.metadata.id
+    }
+
+    /// Returns the storage bookkeeping of the `Appointment`
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the identifier of the recurrence that generated the `Appointment`, if any
+    pub fn recurrence_id(&self) -> Option<u32> {
+        self.recurrence_id
+    }
+
+    /// Creates an `Appointment` from a string slice. Words starting with `#` at the end of the
+    /// description are taken as tags
     ///
     /// # Example
     ///
@@ -41,12 +380,168 @@ impl Appointment {
     ///
     /// let appointment = Appointment::from("15:06 Visit parents").unwrap();
     /// assert_eq!(Appointment::new(String::from("Visit parents"), AppointmentTime::new(15, 6).unwrap()), appointment);
+    ///
+    /// let appointment = Appointment::from("09:00 Fix #42 first #work #oncall").unwrap();
+    /// assert_eq!("Fix #42 first", appointment.description());
+    /// assert_eq!(&[String::from("work"), String::from("oncall")], appointment.tags());
     /// ```
     pub fn from(appointment: &str) -> Result<Self, String> {
         let time: String = appointment.chars().take(5).collect();
         let appointment_time = AppointmentTime::from(&time)?;
-        let description = appointment.chars().skip(6).collect();
-        Ok(Appointment::new(description, appointment_time))
+        if appointment.chars().nth(5) == Some('-') {
+            let end_time: String = appointment.chars().skip(6).take(5).collect();
+            let end_time = AppointmentTime::from(&end_time)?;
+            let description: String = appointment.chars().skip(12).collect();
+            let (description, tags) = helper::split_tags(&description);
+            return Appointment::new(description, appointment_time)
+                .with_tags(tags)
+                .until(end_time);
+        }
+        let description: String = appointment.chars().skip(6).collect();
+        let (description, tags) = helper::split_tags(&description);
+        Ok(Appointment::new(description, appointment_time).with_tags(tags))
+    }
+
+    /// Adds tags that are already validated
+    pub(crate) fn with_tags(mut self, tags: Vec<String>) -> Self {
+        for tag in tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self
+    }
+
+    /// Sets the duration of the `Appointment` so it ends at the given time. An end time earlier
+    /// than the start time is on the next day
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Duration};
+    ///
+    /// let appointment = Appointment::new(String::from("Workshop"), AppointmentTime::new(14, 0).unwrap());
+    /// assert_eq!(Some(Duration::from_minutes(45)), appointment.clone().until(AppointmentTime::new(14, 45).unwrap()).unwrap().duration());
+    /// assert_eq!(Some(Duration::from_minutes(1410)), appointment.until(AppointmentTime::new(13, 30).unwrap()).unwrap().duration());
+    /// ```
+    pub fn until(self, end_time: AppointmentTime) -> Result<Self, String> {
+        let duration = (end_time.minutes_since_midnight() - self.time.minutes_since_midnight())
+            .rem_euclid(24 * 60);
+        if duration == 0 {
+            return Err(String::from("End time should differ from the start time"));
+        }
+        Ok(self.with_duration(Duration::from_minutes(duration)))
+    }
+
+    /// Applies the changes to the `Appointment`. Its duration is kept when only the time changes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentChanges, AppointmentTime, Duration};
+    ///
+    /// let appointment = Appointment::new(String::from("Workshop"), AppointmentTime::new(14, 0).unwrap()).with_duration(Duration::from_minutes(60));
+    /// let changes = AppointmentChanges {
+    ///     time: Some(AppointmentTime::new(15, 0).unwrap()),
+    ///     description: Some(String::from("Rust workshop")),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!("15:00-16:00 Rust workshop", appointment.apply(changes).unwrap().to_string());
+    /// ```
+    pub fn apply(mut self, changes: AppointmentChanges) -> Result<Self, String> {
+        if let Some(time) = changes.time {
+            self.time = time;
+        }
+        if let Some(description) = changes.description {
+            self.description = description;
+        }
+        if let Some(status) = changes.status {
+            self.status = status;
+        }
+        if let Some(tags) = changes.tags {
+            self.tags = vec![];
+            for tag in tags {
+                self = self.with_tag(&tag)?;
+            }
+        }
+        match (changes.duration, changes.end_time) {
+            (Some(duration), _) => Ok(self.with_duration(duration)),
+            (None, Some(end_time)) => self.until(end_time),
+            (None, None) => Ok(self),
+        }
+    }
+
+    /// Checks if the `Appointment` has started and not ended yet at the reference time of the day
+    /// it starts on. Appointments crossing midnight are in progress until the end of that day,
+    /// use `is_in_progress_at` for the next day
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Duration};
+    ///
+    /// let appointment = Appointment::new(String::from("Workshop"), AppointmentTime::new(14, 0).unwrap()).with_duration(Duration::from_minutes(120));
+    /// assert!(appointment.is_in_progress(&AppointmentTime::new(15, 10).unwrap()));
+    /// assert!(!appointment.is_in_progress(&AppointmentTime::new(16, 0).unwrap()));
+    /// ```
+    pub fn is_in_progress(&self, reference_time: &AppointmentTime) -> bool {
+        self.is_in_progress_at(&self.on_own_day(reference_time))
+    }
+
+    /// Checks if the `Appointment` has started and not ended yet at a given moment. Appointments
+    /// without a date are taken on the date of the moment
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentDateTime, AppointmentTime, Duration};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let appointment = Appointment::new(String::from("Night shift"), AppointmentTime::new(22, 0).unwrap())
+    ///     .with_duration(Duration::from_minutes(240))
+    ///     .with_date(date);
+    /// let next_day = date.succ_opt().unwrap();
+    /// assert!(appointment.is_in_progress_at(&AppointmentDateTime::new(next_day, &AppointmentTime::new(1, 0).unwrap())));
+    /// assert!(!appointment.is_in_progress_at(&AppointmentDateTime::new(next_day, &AppointmentTime::new(2, 0).unwrap())));
+    /// ```
+    pub fn is_in_progress_at(&self, now: &AppointmentDateTime) -> bool {
+        self.starts_at(now.date()) <= *now && *now < self.ends_at(now.date())
+    }
+
+    /// Checks if the `Appointment` starts at or before a given moment. Appointments without a
+    /// date are taken on the date of the moment
+    pub fn is_passed_at(&self, now: &AppointmentDateTime) -> bool {
+        self.starts_at(now.date()) <= *now
+    }
+
+    /// Returns the reference time on the day the `Appointment` starts
+    fn on_own_day(&self, reference_time: &AppointmentTime) -> AppointmentDateTime {
+        AppointmentDateTime::new(self.date.unwrap_or_default(), reference_time)
+    }
+
+    /// Checks if two appointments take place at the same time. Appointments without duration are
+    /// considered to last a minute
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Duration};
+    ///
+    /// let workshop = Appointment::new(String::from("Workshop"), AppointmentTime::new(14, 0).unwrap()).with_duration(Duration::from_minutes(120));
+    /// let call = Appointment::new(String::from("Call"), AppointmentTime::new(15, 30).unwrap());
+    /// let dinner = Appointment::new(String::from("Dinner"), AppointmentTime::new(16, 0).unwrap());
+    /// assert!(workshop.overlaps(&call));
+    /// assert!(!workshop.overlaps(&dinner));
+    /// ```
+    pub fn overlaps(&self, other: &Appointment) -> bool {
+        let interval = |a: &Appointment| {
+            let start = a.time.minutes_since_midnight();
+            (start, start + a.duration.map_or(1, |d| d.minutes()).max(1))
+        };
+        let (start, end) = interval(self);
+        let (other_start, other_end) = interval(other);
+        start < other_end && other_start < end
     }
 
     /// Checks if the current time is earlier or equal to a reference to another AppointmentTime
@@ -63,9 +558,9 @@ impl Appointment {
         self.time.is_equal_or_earlier_than(appointment_time)
     }
 
-    /// Return a string version of the `Appointment` for displaying to the user. If the time of the
-    /// current appointment is earlier or equal to the reference time informed, the string is
-    /// returned with a `strikethrough`
+    /// Return a string version of the `Appointment` for displaying to the user. Appointments done,
+    /// skipped or cancelled are returned with a `strikethrough` followed by their status. Pending
+    /// appointments earlier or equal to the reference time informed are returned `dimmed`
     ///
     /// # Example
     ///
@@ -76,18 +571,64 @@ impl Appointment {
     /// assert_eq!("[10:29] A complete description", appointment.to_string_display(&AppointmentTime::new(9, 24).unwrap()));
     /// ```
     pub fn to_string_display(&self, ref_time: &AppointmentTime) -> String {
-        let display = format!("[{}] {}", self.time, self.description);
-        if self.is_equal_or_earlier_than(ref_time) {
-            display.strikethrough().to_string()
+        self.to_string_display_at(&self.on_own_day(ref_time))
+    }
+
+    /// Return a string version of the `Appointment` like `to_string_display`, comparing it to a
+    /// moment instead of a time of the day it starts on
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentDateTime, AppointmentTime};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let appointment = Appointment::new(String::from("Gym"), AppointmentTime::new(7, 0).unwrap()).with_date(date.succ_opt().unwrap());
+    /// let now = AppointmentDateTime::new(date, &AppointmentTime::new(9, 0).unwrap());
+    /// assert_eq!("[07:00] Gym", appointment.to_string_display_at(&now));
+    /// ```
+    pub fn to_string_display_at(&self, now: &AppointmentDateTime) -> String {
+        let display = format!(
+            "[{}] {}{}",
+            self.time_range(),
+            self.description,
+            self.tags_suffix()
+        );
+        if !self.status.is_pending() {
+            format!("{} ({})", display.strikethrough(), self.status)
+        } else if self.is_in_progress_at(now) {
+            format!("{display} {}", "(now)".bold())
+        } else if self.is_passed_at(now) {
+            display.dimmed().to_string()
         } else {
             display
         }
     }
+
+    /// Returns the tags with their leading `#`, preceded by a space, or an empty string
+    pub(crate) fn tags_suffix(&self) -> String {
+        self.tags.iter().map(|tag| format!(" #{tag}")).collect()
+    }
+
+    /// Returns the start time followed by the end time, when there is a duration
+    pub(crate) fn time_range(&self) -> String {
+        match self.duration {
+            Some(..) => format!("{}-{}", self.time, self.end_time()),
+            None => self.time.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for Appointment {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {}", self.time, self.description)
+        write!(
+            f,
+            "{} {}{}",
+            self.time_range(),
+            self.description,
+            self.tags_suffix()
+        )
     }
 }
 
@@ -101,7 +642,10 @@ impl str::FromStr for Appointment {
 
 #[cfg(test)]
 mod tests {
-    use super::{Appointment, AppointmentTime};
+    use super::{
+        Appointment, AppointmentChanges, AppointmentDateTime, AppointmentTime, Duration, Status,
+    };
+    use chrono::NaiveDate;
     use colored::Colorize;
 
     #[test]
@@ -110,8 +654,15 @@ mod tests {
         assert_eq!(
             result.unwrap(),
             Appointment {
+                date: None,
                 description: String::from("Take the bus"),
-                time: AppointmentTime::new(5, 6).unwrap()
+                time: AppointmentTime::new(5, 6).unwrap(),
+                duration: None,
+                status: Status::Pending,
+                tags: vec![],
+                recurrence_id: None,
+                zone: None,
+                metadata: Default::default(),
             }
         );
     }
@@ -122,8 +673,15 @@ mod tests {
         assert_eq!(
             result.unwrap(),
             Appointment {
+                date: None,
                 description: String::from("A very late appointment"),
-                time: AppointmentTime::new(23, 59).unwrap()
+                time: AppointmentTime::new(23, 59).unwrap(),
+                duration: None,
+                status: Status::Pending,
+                tags: vec![],
+                recurrence_id: None,
+                zone: None,
+                metadata: Default::default(),
             }
         );
     }
@@ -156,6 +714,134 @@ mod tests {
         assert_eq!("Hour should be between 0 and 23", err.unwrap());
     }
 
+    #[test]
+    fn create_appointment_with_end_time_from_str() {
+        let result = Appointment::from("14:00-16:30 Workshop");
+        assert_eq!(
+            result.unwrap(),
+            Appointment::new(
+                String::from("Workshop"),
+                AppointmentTime::new(14, 0).unwrap()
+            )
+            .with_duration(Duration::from_minutes(150))
+        );
+        let result = Appointment::from("22:00-02:30 Night shift");
+        assert_eq!(
+            result.unwrap().duration(),
+            Some(Duration::from_minutes(270))
+        );
+        let result = Appointment::from("14:00-14:00 Workshop");
+        assert_eq!(
+            "End time should differ from the start time",
+            result.err().unwrap()
+        );
+    }
+
+    #[test]
+    fn apply_changes_to_appointment() {
+        let appointment = Appointment::new(
+            String::from("Workshop"),
+            AppointmentTime::new(14, 0).unwrap(),
+        );
+        let changes = AppointmentChanges {
+            time: Some(AppointmentTime::new(9, 0).unwrap()),
+            end_time: Some(AppointmentTime::new(10, 30).unwrap()),
+            status: Some(Status::Done),
+            ..Default::default()
+        };
+        assert_eq!(
+            appointment.clone().apply(changes).unwrap(),
+            Appointment::new(
+                String::from("Workshop"),
+                AppointmentTime::new(9, 0).unwrap()
+            )
+            .with_duration(Duration::from_minutes(90))
+            .with_status(Status::Done)
+        );
+        let changes = AppointmentChanges {
+            end_time: Some(AppointmentTime::new(14, 0).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            appointment.apply(changes).unwrap_err(),
+            "End time should differ from the start time"
+        );
+    }
+
+    #[test]
+    fn display_appointment_in_progress() {
+        colored::control::set_override(true);
+        let appointment = Appointment::new(
+            String::from("Workshop"),
+            AppointmentTime::new(14, 0).unwrap(),
+        )
+        .with_duration(Duration::from_minutes(120));
+        assert_eq!(
+            format!("[14:00-16:00] Workshop {}", "(now)".bold()),
+            appointment.to_string_display(&AppointmentTime::new(14, 0).unwrap())
+        );
+        assert_eq!(
+            "[14:00-16:00] Workshop".dimmed().to_string(),
+            appointment.to_string_display(&AppointmentTime::new(16, 0).unwrap())
+        );
+        assert_eq!(
+            "[14:00-16:00] Workshop",
+            appointment.to_string_display(&AppointmentTime::new(13, 59).unwrap())
+        );
+    }
+
+    #[test]
+    fn appointment_crossing_midnight() {
+        colored::control::set_override(true);
+        let appointment = Appointment::new(
+            String::from("Night shift"),
+            AppointmentTime::new(22, 0).unwrap(),
+        )
+        .with_duration(Duration::from_minutes(480));
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        assert!(appointment.crosses_midnight());
+        assert_eq!("22:00-06:00 Night shift", appointment.to_string());
+        assert_eq!(date.succ_opt().unwrap(), appointment.ends_at(date).date());
+        assert!(appointment.is_in_progress(&AppointmentTime::max_value()));
+        assert!(!appointment.is_in_progress(&AppointmentTime::new(21, 59).unwrap()));
+        assert!(!appointment
+            .clone()
+            .with_duration(Duration::from_minutes(90))
+            .crosses_midnight());
+
+        let appointment = appointment.with_date(date);
+        let next_day = date.succ_opt().unwrap();
+        let at = |date, hour, minutes| {
+            AppointmentDateTime::new(date, &AppointmentTime::new(hour, minutes).unwrap())
+        };
+        assert!(appointment.is_in_progress_at(&at(next_day, 1, 0)));
+        assert!(!appointment.is_in_progress_at(&at(next_day, 6, 0)));
+        assert!(!appointment.is_in_progress_at(&at(date, 21, 59)));
+        assert_eq!(
+            format!("[22:00-06:00] Night shift {}", "(now)".bold()),
+            appointment.to_string_display_at(&at(next_day, 5, 59))
+        );
+    }
+
+    #[test]
+    fn overlapping_appointments() {
+        let appointment = |hour, minutes, duration: Option<i32>| {
+            let appointment = Appointment::new(
+                String::from("An appointment"),
+                AppointmentTime::new(hour, minutes).unwrap(),
+            );
+            match duration {
+                Some(duration) => appointment.with_duration(Duration::from_minutes(duration)),
+                None => appointment,
+            }
+        };
+        assert!(appointment(10, 0, None).overlaps(&appointment(10, 0, None)));
+        assert!(!appointment(10, 0, None).overlaps(&appointment(10, 1, None)));
+        assert!(appointment(9, 0, Some(60)).overlaps(&appointment(9, 59, None)));
+        assert!(!appointment(9, 0, Some(60)).overlaps(&appointment(10, 0, Some(30))));
+        assert!(appointment(9, 30, Some(60)).overlaps(&appointment(10, 0, Some(30))));
+    }
+
     #[test]
     fn display_appointment() {
         let appointment = Appointment::new(
@@ -169,56 +855,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_appointment_with_status() {
+        colored::control::set_override(true);
+        let appointment = Appointment::new(
+            String::from("Go to the dentist"),
+            AppointmentTime::new(2, 30).unwrap(),
+        );
+        let ref_time = AppointmentTime::new(1, 0).unwrap();
+        assert_eq!(
+            format!("{} (done)", "[02:30] Go to the dentist".strikethrough()),
+            appointment
+                .clone()
+                .with_status(Status::Done)
+                .to_string_display(&ref_time)
+        );
+        assert_eq!(
+            format!(
+                "{} (cancelled)",
+                "[02:30] Go to the dentist".strikethrough()
+            ),
+            appointment
+                .with_status(Status::Cancelled)
+                .to_string_display(&AppointmentTime::new(3, 0).unwrap())
+        );
+    }
+
     #[test]
     fn display_appointment_edge_case() {
+        colored::control::set_override(true);
         let appointment = Appointment::new(
             String::from("Study for test tomorrow"),
             AppointmentTime::new(12, 4).unwrap(),
         );
         let ref_time = AppointmentTime::new(12, 5).unwrap();
         assert_eq!(
-            "[12:04] Study for test tomorrow"
-                .strikethrough()
-                .to_string(),
+            "[12:04] Study for test tomorrow".dimmed().to_string(),
             appointment.to_string_display(&ref_time)
         );
     }
 
     #[test]
     fn display_appointment_edge_case_complement() {
+        colored::control::set_override(true);
         let appointment = Appointment::new(
             String::from("Go to gym"),
             AppointmentTime::new(5, 30).unwrap(),
         );
         let ref_time = AppointmentTime::new(5, 30).unwrap();
         assert_eq!(
-            "[05:30] Go to gym".strikethrough().to_string(),
+            "[05:30] Go to gym".dimmed().to_string(),
             appointment.to_string_display(&ref_time)
         );
     }
 
     #[test]
     fn display_past_appointment() {
+        colored::control::set_override(true);
         let appointment = Appointment::new(
             String::from("Do the laundry"),
             AppointmentTime::new(18, 0).unwrap(),
         );
         let ref_time = AppointmentTime::new(18, 5).unwrap();
         assert_eq!(
-            "[18:00] Do the laundry".strikethrough().to_string(),
+            "[18:00] Do the laundry".dimmed().to_string(),
             appointment.to_string_display(&ref_time)
         );
     }
 
     #[test]
     fn display_past_appointment_edge_case() {
+        colored::control::set_override(true);
         let appointment = Appointment::new(
             String::from("Make dinner"),
             AppointmentTime::new(20, 5).unwrap(),
         );
         let ref_time = AppointmentTime::new(20, 5).unwrap();
         assert_eq!(
-            "[20:05] Make dinner".strikethrough().to_string(),
+            "[20:05] Make dinner".dimmed().to_string(),
             appointment.to_string_display(&ref_time)
         );
     }
@@ -227,17 +941,17 @@ mod tests {
     fn appointment_should_be_passed() {
         let future_appointment = Appointment::new(
             String::from("Some future appointment"),
-            AppointmentTime::now() + 5,
+            AppointmentTime::new(14, 35).unwrap(),
         );
-        assert!(!future_appointment.is_equal_or_earlier_than(&AppointmentTime::now()))
+        assert!(!future_appointment.is_equal_or_earlier_than(&AppointmentTime::new(14, 30).unwrap()))
     }
 
     #[test]
     fn appointment_not_should_be_passed() {
         let future_appointment = Appointment::new(
             String::from("Some past appointment"),
-            AppointmentTime::now() - 5,
+            AppointmentTime::new(14, 25).unwrap(),
         );
-        assert!(future_appointment.is_equal_or_earlier_than(&AppointmentTime::now()))
+        assert!(future_appointment.is_equal_or_earlier_than(&AppointmentTime::new(14, 30).unwrap()))
     }
 }
@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use colored::Colorize;
+
+use super::helper;
+
+/// Describe the dates shown by the agenda
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    /// The week of the first date, from monday to sunday
+    Week,
+    /// The month of the first date
+    Month,
+    /// A number of days starting at the first date
+    Days(u32),
+}
+
+impl Period {
+    /// Returns the dates of the period that contains or starts at a date
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::agenda::Period;
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+    /// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(), Period::Week.dates(date)[0]);
+    /// assert_eq!(30, Period::Month.dates(date).len());
+    /// assert_eq!(3, Period::Days(3).dates(date).len());
+    /// ```
+    pub fn dates(&self, date: NaiveDate) -> Vec<NaiveDate> {
+        let (first, last) = match self {
+            Period::Week => {
+                let first = date.week(Weekday::Mon).first_day();
+                (first, first + Days::new(6))
+            }
+            Period::Month => {
+                let first = date
+                    .with_day(1)
+                    .expect("First day of month should be valid");
+                (first, first + Months::new(1) - Days::new(1))
+            }
+            Period::Days(days) => (date, date + Days::new((*days).max(1) as u64 - 1)),
+        };
+        first.iter_days().take_while(|day| *day <= last).collect()
+    }
+}
+
+/// Renders the appointments grouped by day, each day under a header. Days without appointments
+/// are left out unless `show_empty` is set
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::agenda;
+/// use chrono::NaiveDate;
+///
+/// let monday = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let tuesday = NaiveDate::from_ymd_opt(2024, 6, 11).unwrap();
+/// let days = vec![(monday, vec![String::from("[09:00] Standup")]), (tuesday, vec![])];
+/// assert_eq!("Monday 10/06/2024\n  [09:00] Standup", agenda::render_days(&days, false, tuesday));
+/// ```
+pub fn render_days(
+    days: &[(NaiveDate, Vec<String>)],
+    show_empty: bool,
+    today: NaiveDate,
+) -> String {
+    days.iter()
+        .filter(|(_, lines)| show_empty || !lines.is_empty())
+        .map(|(date, lines)| {
+            let mut header = format!("{} {}", date.format("%A"), helper::format_date(*date));
+            if *date == today {
+                header.push_str(" (today)");
+            }
+            let lines = match lines.is_empty() {
+                true => vec![String::from("  No appointments.")],
+                false => lines.iter().map(|line| format!("  {line}")).collect(),
+            };
+            format!("{}\n{}", header.bold(), lines.join("\n"))
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+/// Renders a calendar grid for each month of the dates, from monday to sunday. Days with
+/// appointments are followed by `*` and days out of the dates are left blank
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::agenda::{self, Period};
+/// use chrono::NaiveDate;
+/// use std::collections::HashSet;
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let busy = HashSet::from([NaiveDate::from_ymd_opt(2024, 6, 12).unwrap()]);
+/// assert_eq!(
+///     "June 2024\nMo  Tu  We  Th  Fr  Sa  Su\n10  11  12* 13  14  15  16",
+///     agenda::render_grid(&Period::Week.dates(date), &busy, date)
+/// );
+/// ```
+pub fn render_grid(dates: &[NaiveDate], busy: &HashSet<NaiveDate>, today: NaiveDate) -> String {
+    let mut months: Vec<(i32, u32)> = dates.iter().map(|d| (d.year(), d.month())).collect();
+    months.dedup();
+    months
+        .into_iter()
+        .map(|(year, month)| {
+            let in_month: Vec<&NaiveDate> = dates
+                .iter()
+                .filter(|d| d.year() == year && d.month() == month)
+                .collect();
+            let first = in_month[0].week(Weekday::Mon).first_day();
+            let last = in_month[in_month.len() - 1].week(Weekday::Mon).last_day();
+            let mut lines = vec![
+                in_month[0].format("%B %Y").to_string(),
+                String::from("Mo  Tu  We  Th  Fr  Sa  Su"),
+            ];
+            let days: Vec<NaiveDate> = first.iter_days().take_while(|d| *d <= last).collect();
+            for week in days.chunks(7) {
+                let cells: Vec<String> = week
+                    .iter()
+                    .map(|day| match in_month.contains(&day) {
+                        true => grid_cell(*day, busy.contains(day), *day == today),
+                        false => String::from("   "),
+                    })
+                    .collect();
+                lines.push(cells.join(" ").trim_end().to_string());
+            }
+            lines.join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+/// Returns the day number followed by `*` when it has appointments, today in reverse colors
+fn grid_cell(day: NaiveDate, busy: bool, today: bool) -> String {
+    let number = format!("{:>2}", day.day());
+    let number = match today {
+        true => number.reversed().to_string(),
+        false => number,
+    };
+    format!("{number}{}", if busy { "*" } else { " " })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chrono::NaiveDate;
+    use colored::Colorize;
+
+    use super::{render_days, render_grid, Period};
+
+    fn date(day: u32, month: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn periods_should_cover_their_dates() {
+        let week = Period::Week.dates(date(16, 6));
+        assert_eq!(
+            (week[0], week[6], week.len()),
+            (date(10, 6), date(16, 6), 7)
+        );
+        let month = Period::Month.dates(date(29, 2));
+        assert_eq!(
+            (month[0], month[28], month.len()),
+            (date(1, 2), date(29, 2), 29)
+        );
+        let days = Period::Days(10).dates(date(28, 6));
+        assert_eq!((days[0], days[9]), (date(28, 6), date(7, 7)));
+    }
+
+    #[test]
+    fn empty_days_can_be_shown() {
+        colored::control::set_override(true);
+        let days = vec![
+            (date(10, 6), vec![]),
+            (date(11, 6), vec![String::from("[09:00] Standup")]),
+        ];
+        assert_eq!(
+            render_days(&days, true, date(11, 6)),
+            format!(
+                "{}\n  No appointments.\n\n{}\n  [09:00] Standup",
+                "Monday 10/06/2024".bold(),
+                "Tuesday 11/06/2024 (today)".bold()
+            )
+        );
+        assert_eq!(render_days(&days[..1], false, date(11, 6)), "");
+    }
+
+    #[test]
+    fn grid_should_split_months() {
+        let busy = HashSet::from([date(1, 7), date(28, 6)]);
+        assert_eq!(
+            render_grid(&Period::Days(5).dates(date(27, 6)), &busy, date(1, 5)),
+            "June 2024\n\
+            Mo  Tu  We  Th  Fr  Sa  Su\n            27  28* 29  30\n\n\
+            July 2024\n\
+            Mo  Tu  We  Th  Fr  Sa  Su\n 1*"
+        );
+    }
+}
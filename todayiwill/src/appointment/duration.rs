@@ -0,0 +1,233 @@
+use std::{fmt, str};
+
+use serde::{de, Deserialize, Deserializer};
+
+/// Describe a length of time, like the duration of an appointment or how long before it a
+/// reminder is shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    seconds: i32,
+}
+
+impl Duration {
+    /// Returns a `Duration` of the given number of seconds
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Duration;
+    ///
+    /// assert_eq!("1m30s", Duration::from_seconds(90).to_string());
+    /// ```
+    pub const fn from_seconds(seconds: i32) -> Self {
+        Self { seconds }
+    }
+
+    /// Returns a `Duration` of the given number of minutes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Duration;
+    ///
+    /// assert_eq!("1h30m", Duration::from_minutes(90).to_string());
+    /// ```
+    pub const fn from_minutes(minutes: i32) -> Self {
+        Self {
+            seconds: minutes.saturating_mul(60),
+        }
+    }
+
+    /// Returns the number of seconds of the `Duration`
+    pub fn seconds(&self) -> i32 {
+        self.seconds
+    }
+
+    /// Returns the number of whole minutes of the `Duration`, the remaining seconds are dropped
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Duration;
+    ///
+    /// assert_eq!(1, Duration::from_seconds(90).minutes());
+    /// ```
+    pub fn minutes(&self) -> i32 {
+        self.seconds / 60
+    }
+
+    /// Checks if the `Duration` is below zero
+    pub fn is_negative(&self) -> bool {
+        self.seconds < 0
+    }
+
+    /// Parses a duration written as numbers followed by `h`, `m` or `s`, from the largest unit to
+    /// the smallest, like `90s`, `15m` or `1h30m`. A number without a unit is counted in the unit
+    /// following the previous one (`1h30` is `1h30m`), or in minutes when it is alone
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Duration;
+    ///
+    /// assert_eq!(Ok(Duration::from_seconds(90)), Duration::from("90s"));
+    /// assert_eq!(Ok(Duration::from_minutes(90)), Duration::from("1h30m"));
+    /// assert_eq!(Ok(Duration::from_minutes(15)), Duration::from("15"));
+    /// assert!(Duration::from("an hour").is_err());
+    /// ```
+    pub fn from(duration: &str) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "Invalid duration \"{}\", use a number with a unit like \"90s\", \"15m\" or \"1h30m\"",
+                duration.trim()
+            )
+        };
+        let compact: String = duration.to_lowercase().split_whitespace().collect();
+        let (sign, mut rest) = match compact.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, compact.as_str()),
+        };
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+        let units = [('h', 3600), ('m', 60), ('s', 1)];
+        let mut next_unit = 0;
+        let mut seconds: i32 = 0;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if digits == 0 {
+                return Err(invalid());
+            }
+            let value: i32 = rest[..digits].parse().map_err(|_| invalid())?;
+            let unit = match rest[digits..].chars().next() {
+                Some(unit) => units[next_unit..]
+                    .iter()
+                    .position(|(name, _)| *name == unit)
+                    .map(|position| next_unit + position)
+                    .ok_or_else(invalid)?,
+                None if next_unit == 0 => 1,
+                None if next_unit < units.len() => next_unit,
+                None => return Err(invalid()),
+            };
+            seconds = value
+                .checked_mul(units[unit].1)
+                .and_then(|value| seconds.checked_add(value))
+                .ok_or_else(invalid)?;
+            next_unit = unit + 1;
+            rest = rest.get(digits + 1..).unwrap_or_default();
+        }
+        Ok(Self {
+            seconds: sign * seconds,
+        })
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.seconds < 0 {
+            write!(f, "-")?;
+        }
+        let seconds = self.seconds.unsigned_abs();
+        let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+        if hours > 0 {
+            write!(f, "{hours}h")?;
+        }
+        if minutes > 0 {
+            write!(f, "{minutes}m")?;
+        }
+        if seconds > 0 || (hours == 0 && minutes == 0) {
+            write!(f, "{seconds}s")?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for Duration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Duration::from(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    /// Reads a duration from a number of minutes or from a string like `1h30m`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DurationVisitor;
+
+        impl de::Visitor<'_> for DurationVisitor {
+            type Value = Duration;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a number of minutes or a duration like \"1h30m\"")
+            }
+
+            fn visit_i64<E: de::Error>(self, minutes: i64) -> Result<Duration, E> {
+                i32::try_from(minutes)
+                    .map(Duration::from_minutes)
+                    .map_err(|_| E::custom(format!("Invalid duration \"{minutes}\"")))
+            }
+
+            fn visit_u64<E: de::Error>(self, minutes: u64) -> Result<Duration, E> {
+                i32::try_from(minutes)
+                    .map(Duration::from_minutes)
+                    .map_err(|_| E::custom(format!("Invalid duration \"{minutes}\"")))
+            }
+
+            fn visit_str<E: de::Error>(self, duration: &str) -> Result<Duration, E> {
+                Duration::from(duration).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DurationVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Duration;
+
+    #[test]
+    fn parse_durations_with_units() {
+        assert_eq!(Duration::from("90s"), Ok(Duration::from_seconds(90)));
+        assert_eq!(Duration::from("15m"), Ok(Duration::from_minutes(15)));
+        assert_eq!(Duration::from("1h30m"), Ok(Duration::from_minutes(90)));
+        assert_eq!(Duration::from("1H 05M"), Ok(Duration::from_minutes(65)));
+        assert_eq!(Duration::from("2h"), Ok(Duration::from_minutes(120)));
+        assert_eq!(Duration::from("1h1m1s"), Ok(Duration::from_seconds(3661)));
+        assert_eq!(Duration::from("-5m"), Ok(Duration::from_minutes(-5)));
+    }
+
+    #[test]
+    fn parse_durations_without_units() {
+        assert_eq!(Duration::from("20"), Ok(Duration::from_minutes(20)));
+        assert_eq!(Duration::from("1h30"), Ok(Duration::from_minutes(90)));
+        assert_eq!(Duration::from("2m30"), Ok(Duration::from_seconds(150)));
+    }
+
+    #[test]
+    fn parse_invalid_durations() {
+        let error = String::from(
+            "Invalid duration \"30m1h\", use a number with a unit like \"90s\", \"15m\" or \"1h30m\"",
+        );
+        assert_eq!(Duration::from("30m1h"), Err(error));
+        for duration in ["", "h", "an hour", "5d", "1s30", "1m1m", "99999999999s"] {
+            assert!(
+                Duration::from(duration).is_err(),
+                "{duration} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn display_durations() {
+        assert_eq!("1h30m", Duration::from_minutes(90).to_string());
+        assert_eq!("45m", Duration::from_minutes(45).to_string());
+        assert_eq!("1m30s", Duration::from_seconds(90).to_string());
+        assert_eq!("2h", Duration::from_minutes(120).to_string());
+        assert_eq!("0s", Duration::default().to_string());
+        assert_eq!("-5m", Duration::from_minutes(-5).to_string());
+    }
+}
@@ -1,45 +1,323 @@
-use chrono::{Local, NaiveDate, ParseError};
-use std::path::PathBuf;
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, Days, NaiveDate, ParseError, Weekday,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
-use super::helper;
+use super::{
+    duration::Duration,
+    helper, journal,
+    list::AppointmentList,
+    recurrence::RecurrenceList,
+    time::{AppointmentDateTime, AppointmentTime},
+    zone::Zone,
+    Appointment,
+};
+
+/// Date format used when none is configured
+pub const DEFAULT_DATE_FORMAT: &str = "%d/%m/%Y";
+
+/// Name of the calendar stored directly in the data dir
+pub const DEFAULT_CALENDAR: &str = "default";
+
+/// Dir inside the data dir that holds one dir per named calendar
+const CALENDARS_DIR: &str = "calendars";
+
+/// Date format configured for the current process, see `set_date_format`
+static DATE_FORMAT: OnceLock<String> = OnceLock::new();
 
 /// Represent the config needed to save the appointments
 pub struct Config {
     pub appointment_file_path_current_day: Box<PathBuf>,
     pub appointment_file_path_builder: Box<dyn Fn(NaiveDate) -> PathBuf>,
+    pub recurrence_file_path: Box<PathBuf>,
+    /// File recording the changes made to the appointment files, see `Journal`
+    pub journal_file_path: Box<PathBuf>,
 }
 
 impl Config {
-    /// Returns the standard config with the usable paths according to the data dir of the current
-    /// operating system
+    /// Returns the config with the usable paths inside a data dir
     ///
     /// # Example
     ///
     /// ```
-    /// #[cfg(target_os = "linux")]
     /// use todayiwill::Config;
     /// use std::path::PathBuf;
     /// use chrono::{Local, NaiveDate};
     ///
-    /// let config = Config::standard();
-    /// let data_path = dirs::data_dir().unwrap();
-    /// assert_eq!(PathBuf::from(format!("{}/todayiwill/appointments_01012024.txt", data_path.to_str().unwrap())), (config.appointment_file_path_builder)(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()));
-    /// assert_eq!(PathBuf::from(format!("{}/todayiwill/appointments_{}.txt", data_path.to_str().unwrap(), Local::now().format("%d%m%Y"))), *config.appointment_file_path_current_day);
+    /// let config = Config::new(PathBuf::from("/tmp/todayiwill"));
+    /// assert_eq!(PathBuf::from("/tmp/todayiwill/appointments_01012024.txt"), (config.appointment_file_path_builder)(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()));
+    /// assert_eq!(PathBuf::from(format!("/tmp/todayiwill/appointments_{}.txt", Local::now().format("%d%m%Y"))), *config.appointment_file_path_current_day);
+    /// assert_eq!(PathBuf::from("/tmp/todayiwill/recurrences.txt"), *config.recurrence_file_path);
+    /// assert_eq!(PathBuf::from("/tmp/todayiwill/journal.txt"), *config.journal_file_path);
     /// ```
-    pub fn standard() -> Self {
-        let appointment_path_builder = |date: NaiveDate| {
-            dirs::data_dir()
-                .unwrap()
-                .join("todayiwill")
-                .join(format!("appointments_{}.txt", helper::date_code(date)))
+    pub fn new(data_dir: PathBuf) -> Self {
+        let recurrence_file_path = data_dir.join("recurrences.txt");
+        let journal_file_path = data_dir.join(journal::JOURNAL_FILE);
+        let appointment_path_builder = move |date: NaiveDate| {
+            data_dir.join(format!("appointments_{}.txt", helper::date_code(date)))
         };
         Self {
             appointment_file_path_current_day: Box::new(appointment_path_builder(
-                Local::now().date_naive(),
+                Zone::current().today(),
             )),
             appointment_file_path_builder: Box::new(appointment_path_builder),
+            recurrence_file_path: Box::new(recurrence_file_path),
+            journal_file_path: Box::new(journal_file_path),
         }
     }
+
+    /// Returns the dates that have a file in the dir of the appointment files, in chronological
+    /// order. Only the files named by `appointment_file_path_builder` are considered
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Config;
+    /// use std::{fs, path::PathBuf};
+    /// use chrono::NaiveDate;
+    ///
+    /// let data_dir = PathBuf::from("/tmp/todayiwill-dates-example");
+    /// fs::create_dir_all(&data_dir).unwrap();
+    /// fs::write(data_dir.join("appointments_02012024.txt"), "").unwrap();
+    /// fs::write(data_dir.join("appointments_2024.txt"), "").unwrap();
+    /// let config = Config::new(data_dir);
+    /// assert!(config.appointment_dates().contains(&NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()));
+    /// ```
+    pub fn appointment_dates(&self) -> Vec<NaiveDate> {
+        let Some(dir) = self.appointment_file_path_current_day.parent() else {
+            return vec![];
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return vec![];
+        };
+        let mut dates: Vec<NaiveDate> = entries
+            .flatten()
+            .filter_map(|entry| self.date_of(&entry.path()))
+            .collect();
+        dates.sort();
+        dates
+    }
+
+    /// Returns the date of an appointment file, `None` when the path is not named by
+    /// `appointment_file_path_builder`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Config;
+    /// use std::path::PathBuf;
+    /// use chrono::NaiveDate;
+    ///
+    /// let config = Config::new(PathBuf::from("/tmp/todayiwill"));
+    /// assert_eq!(NaiveDate::from_ymd_opt(2024, 1, 2), config.date_of(&PathBuf::from("/tmp/todayiwill/appointments_02012024.txt")));
+    /// assert_eq!(None, config.date_of(&PathBuf::from("/tmp/todayiwill/recurrences.txt")));
+    /// ```
+    pub fn date_of(&self, path: &Path) -> Option<NaiveDate> {
+        let stem = path.file_stem()?.to_str()?;
+        let code = stem.get(stem.len().checked_sub(8)?..)?;
+        let date = NaiveDate::parse_from_str(code, "%d%m%Y").ok()?;
+        ((self.appointment_file_path_builder)(date) == path).then_some(date)
+    }
+
+    /// Returns the appointments shown on a date in the current time zone, each with the date of
+    /// the file it is stored in. Converting them may move them to another date, so the days
+    /// around the date are read too. Appointments of the day before still in progress at the
+    /// given moment, or when the date starts, are shown as well. Appointments without a time
+    /// zone are taken in the default one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentDateTime, AppointmentList, AppointmentTime, Config, Duration, RecurrenceList};
+    /// use std::{fs, path::PathBuf};
+    /// use chrono::NaiveDate;
+    ///
+    /// let data_dir = PathBuf::from("/tmp/todayiwill-shown-on-example");
+    /// if data_dir.exists() {
+    ///     fs::remove_dir_all(&data_dir).unwrap();
+    /// }
+    /// let config = Config::new(data_dir);
+    /// let monday = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let path = (config.appointment_file_path_builder)(monday);
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// AppointmentList::new(&reference_time, &path)
+    ///     .add(Appointment::new(String::from("Night shift"), AppointmentTime::new(22, 0).unwrap()).with_duration(Duration::from_minutes(480)))
+    ///     .unwrap();
+    ///
+    /// let recurrences = RecurrenceList::new(&config.recurrence_file_path);
+    /// let now = AppointmentDateTime::new(monday, &reference_time);
+    /// let shown = config.appointments_shown_on(&recurrences, monday.succ_opt().unwrap(), now, None);
+    /// assert_eq!(monday, shown[0].0);
+    /// assert_eq!("22:00-06:00 Night shift", shown[0].1.to_string());
+    /// ```
+    pub fn appointments_shown_on(
+        &self,
+        recurrences: &RecurrenceList,
+        date: NaiveDate,
+        now: AppointmentDateTime,
+        default_zone: Option<Zone>,
+    ) -> Vec<(NaiveDate, Appointment)> {
+        let now = now.max(AppointmentDateTime::new(
+            date,
+            &AppointmentTime::min_value(),
+        ));
+        let current_zone = Zone::current();
+        let reference_time = now.time();
+        let mut appointments = vec![];
+        for day in [date.pred_opt(), Some(date), date.succ_opt()]
+            .into_iter()
+            .flatten()
+        {
+            let path = (self.appointment_file_path_builder)(day);
+            let list = AppointmentList::new(&reference_time, &path)
+                .with_occurrences(recurrences.occurrences(day))
+                .for_date(day, now);
+            appointments.extend(
+                list.appointments()
+                    .iter()
+                    .map(|appointment| appointment.in_zone(day, default_zone, &current_zone))
+                    .filter(|(shown_on, shown)| {
+                        *shown_on == date || (*shown_on < date && shown.is_in_progress_at(&now))
+                    })
+                    .map(|(_, appointment)| (day, appointment)),
+            );
+        }
+        appointments
+    }
+
+    /// Returns the config of a calendar. Named calendars are stored in their own dir inside the data
+    /// dir, the default one in the data dir itself
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Config;
+    /// use std::path::PathBuf;
+    ///
+    /// let config = Config::for_calendar(PathBuf::from("/tmp/todayiwill"), "work").unwrap();
+    /// assert_eq!(PathBuf::from("/tmp/todayiwill/calendars/work/recurrences.txt"), *config.recurrence_file_path);
+    /// assert!(Config::for_calendar(PathBuf::from("/tmp/todayiwill"), "../work").is_err());
+    /// ```
+    pub fn for_calendar(data_dir: PathBuf, calendar: &str) -> Result<Self, String> {
+        if calendar == DEFAULT_CALENDAR {
+            return Ok(Self::new(data_dir));
+        }
+        if calendar.is_empty()
+            || !calendar
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid calendar name \"{calendar}\", use only letters, numbers, \"-\" and \"_\""
+            ));
+        }
+        Ok(Self::new(data_dir.join(CALENDARS_DIR).join(calendar)))
+    }
+
+    /// Returns the standard config with the usable paths according to the data dir of the current
+    /// operating system. Fails when the operating system has no data dir
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Config;
+    /// use chrono::NaiveDate;
+    ///
+    /// if let (Ok(config), Some(data_path)) = (Config::standard(), dirs::data_dir()) {
+    ///     assert_eq!(data_path.join("todayiwill/appointments_01012024.txt"), (config.appointment_file_path_builder)(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()));
+    /// }
+    /// ```
+    pub fn standard() -> Result<Self, String> {
+        let data_dir = dirs::data_dir().ok_or(String::from(
+            "Could not find a data dir, set one with --data-dir or TODAYIWILL_DATA_DIR.",
+        ))?;
+        Ok(Self::new(data_dir.join("todayiwill")))
+    }
+}
+
+/// Returns the names of the calendars inside a data dir, the default one first and the others in
+/// alphabetical order
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+/// use std::path::Path;
+///
+/// assert_eq!(vec!["default"], helper::calendars(Path::new("/nonexistent")));
+/// ```
+pub fn calendars(data_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(data_dir.join(CALENDARS_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| name != DEFAULT_CALENDAR)
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.insert(0, String::from(DEFAULT_CALENDAR));
+    names
+}
+
+/// Sets the date format used to print dates and to parse the ones not given as expressions. Only
+/// the first call has effect
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+/// use chrono::NaiveDate;
+///
+/// helper::set_date_format("%Y-%m-%d");
+/// assert_eq!("2024-06-01", helper::format_date(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()));
+/// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(), helper::str_to_naive_date("2024-06-01").unwrap());
+/// ```
+pub fn set_date_format(format: &str) {
+    let _ = DATE_FORMAT.set(format.to_string());
+}
+
+/// Returns the configured date format, `DD/MM/YYYY` by default
+pub fn date_format() -> &'static str {
+    DATE_FORMAT
+        .get()
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_DATE_FORMAT)
+}
+
+/// Checks if a date format only contains known specifiers
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+///
+/// assert!(helper::is_valid_date_format("%m/%d/%Y"));
+/// assert!(!helper::is_valid_date_format("%Q"));
+/// ```
+pub fn is_valid_date_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| item == Item::Error)
+}
+
+/// Returns the date in the configured date format
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+/// use chrono::NaiveDate;
+///
+/// assert_eq!("01/06/2024", helper::format_date(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()));
+/// ```
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(date_format()).to_string()
 }
 
 /// Parses string time (hours and minutes) and returns a tuple with both values
@@ -58,6 +336,84 @@ pub fn parse_time(time: &str) -> Option<(i32, i32)> {
     Some((hour, minutes))
 }
 
+/// Parses the duration of an appointment ("45", "90m", "2h", "1h30m"), which should be at least a
+/// minute long
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{helper, Duration};
+///
+/// assert_eq!(Ok(Duration::from_minutes(90)), helper::parse_appointment_duration("1h30m"));
+/// assert_eq!(Ok(Duration::from_minutes(45)), helper::parse_appointment_duration("45"));
+/// assert!(helper::parse_appointment_duration("0m").is_err());
+/// ```
+pub fn parse_appointment_duration(duration: &str) -> Result<Duration, String> {
+    let duration = Duration::from(duration)?;
+    if duration.minutes() <= 0 {
+        return Err(String::from(
+            "Duration should be a positive number of minutes",
+        ));
+    }
+    Ok(duration)
+}
+
+/// Parses a tag, written with or without the leading `#`, and returns it in lowercase without it
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+///
+/// assert_eq!(Ok(String::from("oncall")), helper::parse_tag("#OnCall"));
+/// assert!(helper::parse_tag("#").is_err());
+/// ```
+pub fn parse_tag(tag: &str) -> Result<String, String> {
+    let name = tag.trim().strip_prefix('#').unwrap_or(tag.trim());
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid tag \"{tag}\", use only letters, numbers, \"-\" and \"_\""
+        ));
+    }
+    Ok(name.to_lowercase())
+}
+
+/// Splits the tags written at the end of a description (`Deploy #work #oncall`) from the rest of
+/// it. Words starting with `#` followed by a number are not taken as tags
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+///
+/// assert_eq!((String::from("Deploy"), vec![String::from("oncall")]), helper::split_tags("Deploy #oncall"));
+/// assert_eq!((String::from("Review #42"), vec![]), helper::split_tags("Review #42"));
+/// ```
+pub fn split_tags(description: &str) -> (String, Vec<String>) {
+    let mut words: Vec<&str> = description.split_whitespace().collect();
+    let mut tags = vec![];
+    while let Some(word) = words.last() {
+        let is_tag = word.starts_with('#')
+            && word.chars().nth(1).is_some_and(char::is_alphabetic)
+            && words.len() > 1;
+        match (is_tag, parse_tag(word)) {
+            (true, Ok(tag)) => {
+                tags.insert(0, tag);
+                words.pop();
+            }
+            _ => break,
+        }
+    }
+    if tags.is_empty() {
+        return (description.to_string(), tags);
+    }
+    (words.join(" "), tags)
+}
+
 /// Returns a string code for a given date
 ///
 /// # Example
@@ -86,11 +442,103 @@ pub fn str_dmy_to_naive_date(date: &str) -> Result<NaiveDate, ParseError> {
     NaiveDate::parse_from_str(date, "%d/%m/%Y")
 }
 
+/// Parses a date expression relative to a reference date. Besides the configured date format
+/// (`DD/MM/YYYY` by default), it accepts `today`, `tomorrow`, `yesterday`, day or week offsets
+/// (`+3d`, `-1w`) and weekday names (`monday`, `next monday`, `last friday`). Weekday names
+/// without a prefix refer to the next one
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+/// use chrono::NaiveDate;
+///
+/// let today = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+/// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 13).unwrap(), helper::parse_date("tomorrow", today).unwrap());
+/// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(), helper::parse_date("+3d", today).unwrap());
+/// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 17).unwrap(), helper::parse_date("next monday", today).unwrap());
+/// assert_eq!(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), helper::parse_date("02/01/2024", today).unwrap());
+/// ```
+pub fn parse_date(date: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let expression = date.trim().to_lowercase();
+    match expression.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Days::new(1)),
+        "yesterday" => return Ok(today - Days::new(1)),
+        _ => (),
+    }
+    if let Some(offset) = parse_date_offset(&expression) {
+        return offset
+            .and_then(|days| today.checked_add_signed(chrono::Duration::days(days)))
+            .ok_or(String::from("Date offset is out of range"));
+    }
+    if let Some(date) = parse_relative_weekday(&expression, today) {
+        return Ok(date);
+    }
+    NaiveDate::parse_from_str(date.trim(), date_format()).map_err(|error| error.to_string())
+}
+
+/// Converts a date expression to a naive date, relative to the date in the current time zone. See `parse_date` for
+/// the accepted expressions
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::helper;
+/// use chrono::{Days, Local};
+///
+/// assert_eq!(Local::now().date_naive() + Days::new(1), helper::str_to_naive_date("tomorrow").unwrap());
+/// ```
+pub fn str_to_naive_date(date: &str) -> Result<NaiveDate, String> {
+    parse_date(date, Zone::current().today())
+}
+
+/// Parses offsets like `+3d` or `-2w` into a number of days. Returns `None` when the expression is
+/// not an offset and `Some(None)` when it is an offset that could not be parsed
+fn parse_date_offset(expression: &str) -> Option<Option<i64>> {
+    let sign = match expression.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (amount, unit_days) = match expression.chars().last()? {
+        'd' => (&expression[1..expression.len() - 1], 1),
+        'w' => (&expression[1..expression.len() - 1], 7),
+        _ => return None,
+    };
+    let amount: i64 = amount.parse().ok()?;
+    Some(amount.checked_mul(unit_days).map(|days| sign * days))
+}
+
+/// Parses weekday expressions like `monday`, `next monday` or `last monday`
+fn parse_relative_weekday(expression: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (forward, weekday) = match expression.split_once(' ') {
+        Some(("next", weekday)) => (true, weekday),
+        Some(("last", weekday)) => (false, weekday),
+        Some(..) => return None,
+        None => (true, expression),
+    };
+    let weekday: Weekday = weekday.trim().parse().ok()?;
+    let today_index = today.weekday().num_days_from_monday() as i64;
+    let target_index = weekday.num_days_from_monday() as i64;
+    let days = if forward {
+        (target_index - today_index - 1).rem_euclid(7) + 1
+    } else {
+        -((today_index - target_index - 1).rem_euclid(7) + 1)
+    };
+    today.checked_add_signed(chrono::Duration::days(days))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use chrono::NaiveDate;
 
-    use super::{date_code, parse_time, str_dmy_to_naive_date, Config};
+    use super::{
+        date_code, parse_appointment_duration, parse_date, parse_tag, parse_time, split_tags,
+        str_dmy_to_naive_date, Config, Duration,
+    };
 
     #[test]
     fn parse_wellformed_time() {
@@ -104,6 +552,51 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn parse_durations() {
+        assert_eq!(
+            parse_appointment_duration("1H05M"),
+            Ok(Duration::from_minutes(65))
+        );
+        assert_eq!(
+            parse_appointment_duration("-5m"),
+            Err(String::from(
+                "Duration should be a positive number of minutes"
+            ))
+        );
+        assert_eq!(
+            parse_appointment_duration("30s"),
+            Err(String::from(
+                "Duration should be a positive number of minutes"
+            ))
+        );
+        assert!(parse_appointment_duration("an hour")
+            .unwrap_err()
+            .starts_with("Invalid duration \"an hour\""));
+    }
+
+    #[test]
+    fn split_trailing_tags() {
+        assert_eq!(
+            split_tags("Deploy  #work #OnCall"),
+            (
+                String::from("Deploy"),
+                vec![String::from("work"), String::from("oncall")]
+            )
+        );
+        assert_eq!(
+            split_tags("Fix #7 and #bug in CI"),
+            (String::from("Fix #7 and #bug in CI"), vec![])
+        );
+        assert_eq!(split_tags("#work"), (String::from("#work"), vec![]));
+        assert_eq!(
+            parse_tag("on call"),
+            Err(String::from(
+                "Invalid tag \"on call\", use only letters, numbers, \"-\" and \"_\""
+            ))
+        );
+    }
+
     #[test]
     fn date_code_check() {
         let result = date_code(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
@@ -132,16 +625,87 @@ mod tests {
     }
 
     #[test]
-    fn config_default_should_return_a_builder_fn() {
-        let result = (Config::standard().appointment_file_path_builder)(
-            NaiveDate::from_ymd_opt(2023, 10, 21).unwrap(),
+    fn parse_date_relative_expressions() {
+        let today = NaiveDate::from_ymd_opt(2024, 2, 28).unwrap();
+        let expected = |d, m, y| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(parse_date("today", today).unwrap(), today);
+        assert_eq!(
+            parse_date("Tomorrow", today).unwrap(),
+            expected(29, 2, 2024)
+        );
+        assert_eq!(
+            parse_date("yesterday", today).unwrap(),
+            expected(27, 2, 2024)
+        );
+        assert_eq!(parse_date("+2d", today).unwrap(), expected(1, 3, 2024));
+        assert_eq!(parse_date("-1w", today).unwrap(), expected(21, 2, 2024));
+        assert_eq!(
+            parse_date("05/03/2024", today).unwrap(),
+            expected(5, 3, 2024)
+        );
+    }
+
+    #[test]
+    fn parse_date_weekdays() {
+        let wednesday = NaiveDate::from_ymd_opt(2024, 2, 28).unwrap();
+        let expected = |d, m, y| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            parse_date("friday", wednesday).unwrap(),
+            expected(1, 3, 2024)
+        );
+        assert_eq!(
+            parse_date("next wednesday", wednesday).unwrap(),
+            expected(6, 3, 2024)
+        );
+        assert_eq!(
+            parse_date("next mon", wednesday).unwrap(),
+            expected(4, 3, 2024)
+        );
+        assert_eq!(
+            parse_date("last wednesday", wednesday).unwrap(),
+            expected(21, 2, 2024)
+        );
+        assert_eq!(
+            parse_date("last tuesday", wednesday).unwrap(),
+            expected(27, 2, 2024)
+        );
+    }
+
+    #[test]
+    fn parse_date_malformed() {
+        let today = NaiveDate::from_ymd_opt(2024, 2, 28).unwrap();
+        assert_eq!(
+            parse_date("01-2023-22", today).unwrap_err(),
+            "input contains invalid characters"
+        );
+        assert!(parse_date("next month", today).is_err());
+        assert!(parse_date("+xd", today).is_err());
+    }
+
+    #[test]
+    fn calendars_should_have_their_own_dir() {
+        let data_dir = PathBuf::from("/data");
+        let date = NaiveDate::from_ymd_opt(2023, 10, 21).unwrap();
+        let config = Config::for_calendar(data_dir.clone(), "work").unwrap();
+        assert_eq!(
+            (config.appointment_file_path_builder)(date),
+            PathBuf::from("/data/calendars/work/appointments_21102023.txt")
         );
+        let config = Config::for_calendar(data_dir.clone(), "default").unwrap();
         assert_eq!(
-            result,
-            dirs::data_dir()
-                .unwrap()
-                .join("todayiwill")
-                .join("appointments_21102023.txt")
+            (config.appointment_file_path_builder)(date),
+            PathBuf::from("/data/appointments_21102023.txt")
+        );
+        for name in ["", "..", "work/team", "my calendar"] {
+            assert!(Config::for_calendar(data_dir.clone(), name).is_err());
+        }
+    }
+
+    #[test]
+    fn config_should_return_a_builder_fn() {
+        let result = (Config::new(PathBuf::from("/data")).appointment_file_path_builder)(
+            NaiveDate::from_ymd_opt(2023, 10, 21).unwrap(),
         );
+        assert_eq!(result, PathBuf::from("/data/appointments_21102023.txt"));
     }
 }
@@ -0,0 +1,782 @@
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+
+use super::{
+    duration::Duration, helper, time::AppointmentDateTime, zone, Appointment, AppointmentTime,
+    Status,
+};
+
+/// Name of the extra field keeping the `UID` of imported events, used for not importing an event
+/// twice in the same day
+pub const UID_FIELD: &str = "ics_uid";
+
+/// Longest line allowed by the iCalendar format, in octets
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Describe the time zone the date and times of an event are written in
+#[derive(Clone, Debug, PartialEq)]
+enum Zone {
+    /// Times ending with `Z`
+    Utc,
+    /// Times with a `TZID` parameter
+    Named(Tz),
+    /// Times without time zone information, which are considered local
+    Floating,
+}
+
+impl Zone {
+    /// Converts a date and time written in the zone to the current time zone. Times skipped by
+    /// daylight saving transitions are moved forward by the transition
+    fn to_local(&self, date_time: NaiveDateTime) -> NaiveDateTime {
+        let from = match self {
+            Zone::Utc => zone::Zone::Named(chrono_tz::UTC),
+            Zone::Named(tz) => zone::Zone::Named(*tz),
+            Zone::Floating => return date_time,
+        };
+        from.convert(
+            AppointmentDateTime::from_naive(date_time),
+            &zone::Zone::current(),
+        )
+        .naive()
+    }
+}
+
+/// Name, parameters and value of a content line
+type Property = (String, Vec<(String, String)>, String);
+
+/// Describe how often a recurring event repeats
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Describe the subset of the `RRULE` property supported
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    /// Last date an occurrence may start, in the time zone of the event
+    until: Option<NaiveDate>,
+    /// Weekdays with an optional position inside the month, like `2TU` or `-1FR`
+    by_day: Vec<(Option<i8>, Weekday)>,
+    by_month_day: Vec<i32>,
+}
+
+/// Describe an event read from an iCalendar file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    uid: String,
+    summary: String,
+    /// Start in the time zone of the event
+    start: NaiveDateTime,
+    zone: Zone,
+    duration: Option<i32>,
+    status: Status,
+    /// Values of the `CATEGORIES` properties that are valid tags
+    tags: Vec<String>,
+    rule: Option<Rule>,
+    /// Start of the occurrences removed from the recurrence, in the time zone of the event
+    exceptions: Vec<NaiveDateTime>,
+    /// Start of the occurrence replaced by this event, in the time zone of the event
+    recurrence_id: Option<NaiveDateTime>,
+}
+
+impl Event {
+    /// Returns the `UID` of the event
+    pub fn uid(&self) -> &str {
+        &self.uid
+    }
+
+    /// Returns the occurrences of the event starting between two local dates, inclusive
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::ics;
+    /// use chrono::NaiveDate;
+    ///
+    /// let content = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nDTSTART:20240603T090000\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE\r\nSUMMARY:Gym\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+    /// let events = ics::parse(content).unwrap();
+    /// let occurrences = events[0].occurrences(
+    ///     NaiveDate::from_ymd_opt(2024, 6, 4).unwrap(),
+    ///     NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+    /// );
+    /// assert_eq!(2, occurrences.len());
+    /// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 5).unwrap(), occurrences[0].0);
+    /// assert_eq!("09:00 Gym", occurrences[0].1.to_string());
+    /// ```
+    pub fn occurrences(&self, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, Appointment)> {
+        // Occurrences are computed in the time zone of the event, which may be a day apart
+        let starts = match &self.rule {
+            None => vec![self.start],
+            Some(rule) => rule.starts(self.start, to + Days::new(1)),
+        };
+        starts
+            .into_iter()
+            .filter(|start| !self.exceptions.contains(start))
+            .map(|start| self.zone.to_local(start))
+            .filter(|start| from <= start.date() && start.date() <= to)
+            .map(|start| (start.date(), self.to_appointment(start.time())))
+            .collect()
+    }
+
+    /// Returns an appointment for the event starting at a local time
+    fn to_appointment(&self, time: NaiveTime) -> Appointment {
+        let time = AppointmentTime::new(time.hour() as i32, time.minute() as i32)
+            .expect("Time of the day should be valid");
+        let mut appointment = Appointment::new(self.summary.clone(), time)
+            .with_status(self.status)
+            .with_tags(self.tags.clone());
+        if let Some(duration) = self.duration {
+            appointment = appointment.with_duration(Duration::from_minutes(duration));
+        }
+        appointment
+            .metadata
+            .extra
+            .insert(String::from(UID_FIELD), self.uid.clone().into());
+        appointment
+    }
+}
+
+impl Rule {
+    /// Returns the starts of the occurrences before a date, in the time zone of the event
+    fn starts(&self, start: NaiveDateTime, before: NaiveDate) -> Vec<NaiveDateTime> {
+        let mut starts = vec![];
+        let mut date = start.date();
+        let last = match self.until {
+            Some(until) if until < before => until,
+            _ => before,
+        };
+        while date <= last {
+            if self.matches(start.date(), date) {
+                starts.push(date.and_time(start.time()));
+                if self.count.is_some_and(|count| starts.len() as u32 >= count) {
+                    break;
+                }
+            }
+            date = match date.succ_opt() {
+                Some(date) => date,
+                None => break,
+            };
+        }
+        starts
+    }
+
+    /// Checks if the rule has an occurrence on a date, for an event starting on another one
+    fn matches(&self, first: NaiveDate, date: NaiveDate) -> bool {
+        let interval = self.interval.max(1) as i64;
+        let months =
+            (date.year() - first.year()) as i64 * 12 + date.month() as i64 - first.month() as i64;
+        match self.frequency {
+            Frequency::Daily => {
+                (date - first).num_days() % interval == 0 && self.matches_by_day(date)
+            }
+            Frequency::Weekly => {
+                let week_start = |date: NaiveDate| {
+                    date - Days::new(date.weekday().num_days_from_monday() as u64)
+                };
+                let weeks = (week_start(date) - week_start(first)).num_weeks();
+                let weekday_matches = match self.by_day.is_empty() {
+                    true => date.weekday() == first.weekday(),
+                    false => self.by_day.iter().any(|(_, day)| *day == date.weekday()),
+                };
+                weeks % interval == 0 && weekday_matches
+            }
+            Frequency::Monthly => {
+                let day_matches = match (self.by_day.is_empty(), self.by_month_day.is_empty()) {
+                    (true, true) => date.day() == first.day(),
+                    _ => self.matches_by_day(date) && self.matches_by_month_day(date),
+                };
+                months % interval == 0 && day_matches
+            }
+            Frequency::Yearly => months % (12 * interval) == 0 && date.day() == first.day(),
+        }
+    }
+
+    /// Checks the `BYDAY` part, positions being counted inside the month
+    fn matches_by_day(&self, date: NaiveDate) -> bool {
+        if self.by_day.is_empty() {
+            return true;
+        }
+        let position = (date.day() as i8 - 1) / 7 + 1;
+        let days_in_month = (date.with_day(1).unwrap() + Months::new(1) - Days::new(1)).day();
+        let position_from_end = -((days_in_month as i8 - date.day() as i8) / 7 + 1);
+        self.by_day.iter().any(|(nth, weekday)| {
+            *weekday == date.weekday()
+                && nth.is_none_or(|nth| nth == position || nth == position_from_end)
+        })
+    }
+
+    /// Checks the `BYMONTHDAY` part, negative days being counted from the end of the month
+    fn matches_by_month_day(&self, date: NaiveDate) -> bool {
+        if self.by_month_day.is_empty() {
+            return true;
+        }
+        let days_in_month = (date.with_day(1).unwrap() + Months::new(1) - Days::new(1)).day();
+        self.by_month_day.iter().any(|day| {
+            *day == date.day() as i32 || *day == date.day() as i32 - days_in_month as i32 - 1
+        })
+    }
+
+    /// Parses the value of a `RRULE` property
+    fn from(value: &str) -> Result<Self, String> {
+        let invalid = || format!("Unsupported recurrence rule \"{value}\"");
+        let mut rule = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: vec![],
+            by_month_day: vec![],
+        };
+        let mut frequency = None;
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part.split_once('=').ok_or_else(invalid)?;
+            match name.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(invalid()),
+                    })
+                }
+                "INTERVAL" => rule.interval = value.parse().map_err(|_| invalid())?,
+                "COUNT" => rule.count = Some(value.parse().map_err(|_| invalid())?),
+                "UNTIL" => {
+                    rule.until = Some(match value.len() {
+                        8 => NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?,
+                        _ => parse_date_time(value.trim_end_matches('Z'))?.date(),
+                    })
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(invalid)?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(|day| day.parse().ok())
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(invalid)?
+                }
+                "WKST" => (),
+                _ => return Err(invalid()),
+            }
+        }
+        rule.frequency = frequency.ok_or_else(invalid)?;
+        Ok(rule)
+    }
+}
+
+/// Parses a weekday of the `BYDAY` part, like `MO`, `2TU` or `-1FR`
+fn parse_by_day(value: &str) -> Option<(Option<i8>, Weekday)> {
+    let split = value.len().checked_sub(2)?;
+    let (nth, day) = value.split_at(split);
+    let weekday = match day.to_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    match nth {
+        "" => Some((None, weekday)),
+        nth => Some((Some(nth.trim_start_matches('+').parse().ok()?), weekday)),
+    }
+}
+
+/// Parses the content of an iCalendar file, returning its events. All-day events are not
+/// supported and are left out
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::ics;
+///
+/// let content = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nDTSTART:20240610T090000\r\nSUMMARY:Standup\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+/// assert_eq!("1", ics::parse(content).unwrap()[0].uid());
+/// ```
+pub fn parse(content: &str) -> Result<Vec<Event>, String> {
+    let mut events: Vec<Event> = vec![];
+    let mut properties: Option<Vec<Property>> = None;
+    let mut depth = 0;
+    for line in unfold(content) {
+        let (name, params, value) = parse_property(&line)?;
+        match (name.as_str(), value.to_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => {
+                properties = Some(vec![]);
+                depth = 0;
+            }
+            ("END", "VEVENT") => match properties.take() {
+                Some(event_properties) => {
+                    if let Some(event) = parse_event(&event_properties)? {
+                        events.push(event);
+                    }
+                }
+                None => return Err(String::from("Unexpected END:VEVENT")),
+            },
+            ("BEGIN", _) => depth += 1,
+            ("END", _) => depth -= 1,
+            _ => {
+                if let (Some(event_properties), 0) = (properties.as_mut(), depth) {
+                    event_properties.push((name, params, value));
+                }
+            }
+        }
+    }
+
+    // Occurrences replaced by other events are removed from the recurring ones
+    let replaced: Vec<(String, NaiveDateTime)> = events
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|id| (e.uid.clone(), id)))
+        .collect();
+    for event in events.iter_mut().filter(|e| e.rule.is_some()) {
+        for (uid, start) in &replaced {
+            if *uid == event.uid {
+                event.exceptions.push(*start);
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Builds an event from its properties, returning `None` for all-day events
+fn parse_event(properties: &[Property]) -> Result<Option<Event>, String> {
+    let property = |name: &str| properties.iter().find(|(n, _, _)| n == name);
+    let (_, params, value) = match property("DTSTART") {
+        Some(start) => start,
+        None => return Err(String::from("Event without DTSTART")),
+    };
+    if value.len() == 8 || param(params, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) {
+        return Ok(None);
+    }
+    let (start, zone) = parse_zoned(params, value)?;
+
+    let duration = match (property("DTEND"), property("DURATION")) {
+        (Some((_, params, value)), _) => {
+            let (end, end_zone) = parse_zoned(params, value)?;
+            Some((end_zone.to_local(end) - zone.to_local(start)).num_minutes() as i32)
+        }
+        (None, Some((_, _, value))) => Some(parse_duration(value)?),
+        (None, None) => None,
+    }
+    .filter(|duration| *duration > 0);
+
+    let mut exceptions = vec![];
+    for (_, params, value) in properties.iter().filter(|(n, _, _)| n == "EXDATE") {
+        for value in value.split(',') {
+            let (exception, exception_zone) = parse_zoned(params, value)?;
+            exceptions.push(match exception_zone == zone {
+                true => exception,
+                false => exception_zone.to_local(exception),
+            });
+        }
+    }
+
+    Ok(Some(Event {
+        uid: property("UID")
+            .map(|(_, _, uid)| uid.clone())
+            .unwrap_or_else(|| format!("{}@todayiwill", start.format("%Y%m%dT%H%M%S"))),
+        summary: property("SUMMARY")
+            .map(|(_, _, summary)| unescape(summary))
+            .unwrap_or_default(),
+        start,
+        zone,
+        duration,
+        status: match property("STATUS") {
+            Some((_, _, status)) if status.eq_ignore_ascii_case("CANCELLED") => Status::Cancelled,
+            _ => Status::Pending,
+        },
+        tags: properties
+            .iter()
+            .filter(|(name, _, _)| name == "CATEGORIES")
+            .flat_map(|(_, _, value)| split_list(value))
+            .filter_map(|category| helper::parse_tag(&category.replace(' ', "-")).ok())
+            .collect(),
+        rule: property("RRULE")
+            .map(|(_, _, rule)| Rule::from(rule))
+            .transpose()?,
+        exceptions,
+        recurrence_id: property("RECURRENCE-ID")
+            .map(|(_, params, value)| parse_zoned(params, value).map(|(id, _)| id))
+            .transpose()?,
+    }))
+}
+
+/// Returns the value of a property parameter
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a String> {
+    params.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+}
+
+/// Parses a date and time along with its time zone
+fn parse_zoned(params: &[(String, String)], value: &str) -> Result<(NaiveDateTime, Zone), String> {
+    if let Some(value) = value.strip_suffix('Z') {
+        return Ok((parse_date_time(value)?, Zone::Utc));
+    }
+    let zone = match param(params, "TZID") {
+        Some(tzid) => match tzid.trim_matches('"').parse::<Tz>() {
+            Ok(tz) => Zone::Named(tz),
+            Err(..) => return Err(format!("Unknown time zone \"{tzid}\"")),
+        },
+        None => Zone::Floating,
+    };
+    Ok((parse_date_time(value)?, zone))
+}
+
+/// Parses a date and time in the `YYYYMMDDTHHMMSS` format
+fn parse_date_time(value: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .map_err(|_| format!("Invalid date and time \"{value}\""))
+}
+
+/// Parses a duration like `PT1H30M` or `P1D` and returns it in minutes
+fn parse_duration(value: &str) -> Result<i32, String> {
+    let invalid = || format!("Invalid duration \"{value}\"");
+    let mut minutes = 0;
+    let mut number = String::new();
+    for c in value.trim_start_matches(['+', 'P']).chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => (),
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let amount: i32 = number.parse().map_err(|_| invalid())?;
+                number.clear();
+                minutes += match c {
+                    'W' => amount * 7 * 24 * 60,
+                    'D' => amount * 24 * 60,
+                    'H' => amount * 60,
+                    'M' => amount,
+                    _ => 0,
+                };
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(minutes)
+}
+
+/// Joins the lines folded with a leading space or tab, ignoring empty ones
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.trim().is_empty() => (),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits a content line into its name, parameters and value
+fn parse_property(line: &str) -> Result<Property, String> {
+    let mut in_quotes = false;
+    let separator = line.char_indices().find(|(_, c)| {
+        if *c == '"' {
+            in_quotes = !in_quotes;
+        }
+        *c == ':' && !in_quotes
+    });
+    let (head, value) = match separator {
+        Some((index, _)) => (&line[..index], &line[index + 1..]),
+        None => return Err(format!("Invalid iCalendar line \"{line}\"")),
+    };
+    let mut parts = head.split(';');
+    let name = parts.next().unwrap_or_default().to_uppercase();
+    let params = parts
+        .filter_map(|part| part.split_once('='))
+        .map(|(name, value)| (name.to_uppercase(), value.to_string()))
+        .collect();
+    Ok((name, params, value.to_string()))
+}
+
+/// Unescapes a text value
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => text.push('\n'),
+                Some(escaped) => text.push(escaped),
+                None => (),
+            },
+            (c, false) => text.push(c),
+        }
+    }
+    text
+}
+
+/// Escapes a text value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits a list value on the commas that are not escaped, unescaping each item
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = vec![];
+    let mut item = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                item.push(c);
+                if let Some(escaped) = chars.next() {
+                    item.push(escaped);
+                }
+            }
+            ',' => items.push(unescape(&std::mem::take(&mut item))),
+            c => item.push(c),
+        }
+    }
+    items.push(unescape(&item));
+    items
+        .into_iter()
+        .filter(|item| !item.trim().is_empty())
+        .collect()
+}
+
+/// Folds a content line so it is not longer than allowed, without splitting characters
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Exports the appointments of each date as an iCalendar file. Times are written in UTC
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{ics, Appointment, AppointmentTime};
+/// use chrono::NaiveDate;
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let appointments = vec![Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap())];
+/// let content = ics::export(&[(date, appointments)]);
+/// assert!(content.starts_with("BEGIN:VCALENDAR\r\n"));
+/// assert!(content.contains("SUMMARY:Standup\r\n"));
+/// assert_eq!(ics::parse(&content).unwrap()[0].occurrences(date, date).len(), 1);
+/// ```
+pub fn export(days: &[(NaiveDate, Vec<Appointment>)]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//todayiwill//todayiwill//EN"),
+    ];
+    for (date, appointments) in days {
+        for appointment in appointments {
+            let uid = match (
+                appointment
+                    .metadata
+                    .extra
+                    .get(UID_FIELD)
+                    .and_then(|uid| uid.as_str()),
+                appointment.recurrence_id(),
+            ) {
+                (Some(uid), _) => uid.to_string(),
+                (None, Some(recurrence_id)) => {
+                    format!("{}-r{recurrence_id}@todayiwill", date.format("%Y%m%d"))
+                }
+                (None, None) => {
+                    format!("{}-{}@todayiwill", date.format("%Y%m%d"), appointment.id())
+                }
+            };
+            lines.push(String::from("BEGIN:VEVENT"));
+            lines.push(format!("UID:{uid}"));
+            lines.push(format!("DTSTAMP:{stamp}"));
+            lines.push(format!("DTSTART:{}", utc(appointment.starts_at(*date))));
+            if appointment.duration().is_some() {
+                lines.push(format!("DTEND:{}", utc(appointment.ends_at(*date))));
+            }
+            lines.push(format!("SUMMARY:{}", escape(appointment.description())));
+            if !appointment.tags().is_empty() {
+                let tags: Vec<String> = appointment.tags().iter().map(|tag| escape(tag)).collect();
+                lines.push(format!("CATEGORIES:{}", tags.join(",")));
+            }
+            if appointment.status() == Status::Cancelled {
+                lines.push(String::from("STATUS:CANCELLED"));
+            }
+            lines.push(String::from("END:VEVENT"));
+        }
+    }
+    lines.push(String::from("END:VCALENDAR"));
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Returns a date and time of the current time zone in the UTC format used by iCalendar
+fn utc(date_time: AppointmentDateTime) -> String {
+    zone::Zone::current()
+        .convert(date_time, &zone::Zone::Named(chrono_tz::UTC))
+        .naive()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate, TimeZone, Utc};
+
+    use super::{export, fold, parse, parse_duration, unescape, Zone};
+    use crate::appointment::{duration::Duration, Appointment, AppointmentTime, Status};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{events}END:VCALENDAR\r\n")
+    }
+
+    #[test]
+    fn parse_single_event_with_end() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:abc\r\nDTSTART:20240610T140000\r\nDTEND:20240610T153000\r\nSUMMARY:Review\\, then ship\r\n long text\r\nSTATUS:CANCELLED\r\nBEGIN:VALARM\r\nSUMMARY:Alarm\r\nEND:VALARM\r\nEND:VEVENT\r\n",
+        ))
+        .unwrap();
+        let occurrences = events[0].occurrences(date(1), date(30));
+        assert_eq!(occurrences.len(), 1);
+        assert_eq!(occurrences[0].0, date(10));
+        assert_eq!(
+            occurrences[0].1,
+            Appointment::new(
+                String::from("Review, then shiplong text"),
+                AppointmentTime::new(14, 0).unwrap()
+            )
+            .with_duration(Duration::from_minutes(90))
+            .with_status(Status::Cancelled)
+        );
+    }
+
+    #[test]
+    fn all_day_events_should_be_left_out() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:abc\r\nDTSTART;VALUE=DATE:20240610\r\nSUMMARY:Holiday\r\nEND:VEVENT\r\n",
+        ))
+        .unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn recurring_events_should_be_expanded() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:gym\r\nDTSTART:20240603T070000\r\nDURATION:PT1H\r\nRRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;UNTIL=20240630T000000Z\r\nEXDATE:20240617T070000\r\nSUMMARY:Gym\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:gym\r\nRECURRENCE-ID:20240607T070000\r\nDTSTART:20240607T080000\r\nSUMMARY:Gym later\r\nEND:VEVENT\r\n",
+        ))
+        .unwrap();
+        let dates: Vec<NaiveDate> = events[0]
+            .occurrences(date(1), date(30))
+            .into_iter()
+            .map(|(date, _)| date)
+            .collect();
+        assert_eq!(dates, vec![date(3), date(21)]);
+        assert_eq!(
+            events[1].occurrences(date(1), date(30))[0].1.to_string(),
+            "08:00 Gym later"
+        );
+    }
+
+    #[test]
+    fn monthly_and_counted_rules() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:a\r\nDTSTART:20240101T100000\r\nRRULE:FREQ=MONTHLY;BYDAY=-1FR\r\nSUMMARY:Retro\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:b\r\nDTSTART:20240601T100000\r\nRRULE:FREQ=DAILY;COUNT=3\r\nSUMMARY:Course\r\nEND:VEVENT\r\n",
+        ))
+        .unwrap();
+        let retro: Vec<NaiveDate> = events[0]
+            .occurrences(date(1), NaiveDate::from_ymd_opt(2024, 7, 31).unwrap())
+            .into_iter()
+            .map(|(date, _)| date)
+            .collect();
+        assert_eq!(
+            retro,
+            vec![date(28), NaiveDate::from_ymd_opt(2024, 7, 26).unwrap()]
+        );
+        assert_eq!(events[1].occurrences(date(1), date(30)).len(), 3);
+    }
+
+    #[test]
+    fn time_zones_should_be_converted_to_local_time() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:a\r\nDTSTART;TZID=America/Sao_Paulo:20240610T090000\r\nSUMMARY:Call\r\nEND:VEVENT\r\n",
+        ))
+        .unwrap();
+        assert_eq!(
+            events[0].zone,
+            Zone::Named("America/Sao_Paulo".parse().unwrap())
+        );
+        let expected = Utc
+            .from_utc_datetime(&date(10).and_hms_opt(12, 0, 0).unwrap())
+            .with_timezone(&Local)
+            .naive_local();
+        let occurrences = events[0].occurrences(date(1), date(30));
+        assert_eq!(occurrences[0].0, expected.date());
+        assert_eq!(
+            occurrences[0].1.time().to_string(),
+            expected.format("%H:%M").to_string()
+        );
+        let unknown = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:a\r\nDTSTART;TZID=Mars/Olympus:20240610T090000\r\nEND:VEVENT\r\n",
+        ));
+        assert_eq!(unknown.unwrap_err(), "Unknown time zone \"Mars/Olympus\"");
+    }
+
+    #[test]
+    fn export_should_be_parsed_back() {
+        let appointments = vec![
+            Appointment::new(
+                String::from("Plan; review, ship"),
+                AppointmentTime::new(14, 0).unwrap(),
+            )
+            .with_duration(Duration::from_minutes(45)),
+            Appointment::new(String::from("Lunch"), AppointmentTime::new(12, 0).unwrap())
+                .with_status(Status::Cancelled),
+        ];
+        let content = export(&[(date(10), appointments.clone())]);
+        let events = parse(&content).unwrap();
+        let occurrences: Vec<Appointment> = events
+            .iter()
+            .flat_map(|e| e.occurrences(date(10), date(10)))
+            .map(|(_, appointment)| appointment)
+            .collect();
+        assert_eq!(occurrences, appointments);
+    }
+
+    #[test]
+    fn helpers() {
+        assert_eq!(parse_duration("PT1H30M"), Ok(90));
+        assert_eq!(parse_duration("P1DT2H"), Ok(26 * 60));
+        assert_eq!(unescape("a\\,b\\;c\\nd\\\\"), "a,b;c\nd\\");
+        let line = "X".repeat(100);
+        assert_eq!(
+            fold(&line),
+            format!("{}\r\n {}\r\n", "X".repeat(75), "X".repeat(25))
+        );
+    }
+}
@@ -0,0 +1,347 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::storage;
+
+/// Name of the journal file, kept in the dir of the files it records
+pub const JOURNAL_FILE: &str = "journal.txt";
+
+/// Number of operations kept in the journal, the oldest ones are dropped first
+const MAX_OPERATIONS: usize = 100;
+
+/// Describe a change made to an appointments file, with the content of the file before and after
+/// it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Name of the file changed, in the dir of the journal
+    pub file: String,
+    /// Content of the file before the operation, `None` when it did not exist
+    pub before: Option<String>,
+    /// Content of the file after the operation, `None` when it was removed
+    pub after: Option<String>,
+}
+
+impl FileChange {
+    /// Returns the change of a file in the dir of the journal
+    pub fn new(file: &Path, before: Option<String>, after: Option<String>) -> Self {
+        Self {
+            file: file
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            before,
+            after,
+        }
+    }
+}
+
+/// Describe a change made to the appointments, with the changes of every file it wrote. They are
+/// undone and redone together
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Operation {
+    pub id: u32,
+    pub at: DateTime<Local>,
+    /// What was done, like `add` or `clear`
+    pub name: String,
+    /// What was changed, like `[09:00] Standup`
+    pub summary: String,
+    /// Changes of the files, in the order they were written
+    pub files: Vec<FileChange>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub undone: bool,
+}
+
+/// Describe the operations recorded in a file, one JSON object per line. The operations undone
+/// are always the last ones and are dropped when a new operation is recorded
+pub struct Journal<'a> {
+    path: &'a PathBuf,
+    operations: Vec<Operation>,
+}
+
+impl<'a> Journal<'a> {
+    /// Initialize a journal. The operations will be loaded from the path informed, lines that
+    /// cannot be read are ignored
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Journal;
+    /// use std::path::PathBuf;
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("journal_non_existent.txt");
+    /// assert!(Journal::new(&path).operations().is_empty());
+    /// ```
+    pub fn new(path: &'a PathBuf) -> Self {
+        let operations = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .flat_map(serde_json::from_str)
+            .collect();
+        Self { path, operations }
+    }
+
+    /// Returns the operations recorded, from the oldest to the newest
+    pub fn operations(&self) -> &Vec<Operation> {
+        &self.operations
+    }
+
+    /// Records an operation made to files in the dir of the journal, dropping the operations
+    /// undone. The lock of the dir should already be held
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{journal::FileChange, Journal};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill-journal-record").join("journal.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let file = path.with_file_name("appointments_01012024.txt");
+    /// let mut journal = Journal::new(&path);
+    /// let change = FileChange::new(&file, Some(String::from("{\"version\":1}\n")), None);
+    /// journal.record("clear", "2 appointments", vec![change]).unwrap();
+    /// assert_eq!("appointments_01012024.txt", Journal::new(&path).operations()[0].files[0].file);
+    /// ```
+    pub fn record(
+        &mut self,
+        name: &str,
+        summary: &str,
+        files: Vec<FileChange>,
+    ) -> Result<(), String> {
+        self.operations.retain(|operation| !operation.undone);
+        let id = self.operations.last().map(|o| o.id).unwrap_or(0) + 1;
+        self.operations.push(Operation {
+            id,
+            at: Local::now(),
+            name: name.to_string(),
+            summary: summary.to_string(),
+            files,
+            undone: false,
+        });
+        let excess = self.operations.len().saturating_sub(MAX_OPERATIONS);
+        self.operations.drain(..excess);
+        self.write()
+    }
+
+    /// Restores the files changed by the last operation not undone to their content before it
+    pub fn undo(&mut self) -> Result<Operation, String> {
+        let _lock = storage::lock(self.path)?;
+        *self = Journal::new(self.path);
+        let Some(index) = self.operations.iter().rposition(|o| !o.undone) else {
+            return Err(String::from("There is nothing to undo."));
+        };
+        let changes = self.operations[index]
+            .files
+            .iter()
+            .rev()
+            .map(|change| (change.file.as_str(), &change.after, &change.before))
+            .collect();
+        self.restore(changes)?;
+        self.operations[index].undone = true;
+        self.write()?;
+        Ok(self.operations[index].clone())
+    }
+
+    /// Applies again the first operation undone, restoring its files to their content after it
+    pub fn redo(&mut self) -> Result<Operation, String> {
+        let _lock = storage::lock(self.path)?;
+        *self = Journal::new(self.path);
+        let Some(index) = self.operations.iter().position(|o| o.undone) else {
+            return Err(String::from("There is nothing to redo."));
+        };
+        let changes = self.operations[index]
+            .files
+            .iter()
+            .map(|change| (change.file.as_str(), &change.before, &change.after))
+            .collect();
+        self.restore(changes)?;
+        self.operations[index].undone = false;
+        self.write()?;
+        Ok(self.operations[index].clone())
+    }
+
+    /// Replaces the content of files by other ones, given as the file, its expected content and
+    /// its new content. Nothing is replaced unless every file has the expected content, and the
+    /// files already replaced are put back when one fails. `None` stands for a file that does not
+    /// exist
+    fn restore(
+        &self,
+        changes: Vec<(&str, &Option<String>, &Option<String>)>,
+    ) -> Result<(), String> {
+        for (file, expected, _) in &changes {
+            let path = self.path.with_file_name(file);
+            if fs::read_to_string(&path).ok() != **expected {
+                return Err(format!(
+                    "{} was changed after the operation and cannot be restored.",
+                    path.display()
+                ));
+            }
+        }
+        for (index, (file, _, content)) in changes.iter().enumerate() {
+            let path = self.path.with_file_name(file);
+            if let Err(error) = replace(&path, content) {
+                for (file, expected, _) in &changes[..index] {
+                    let _ = replace(&self.path.with_file_name(file), expected);
+                }
+                return Err(format!("Failed to restore {}. {error}", path.display()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the operations to the path
+    fn write(&self) -> Result<(), String> {
+        let content: String = self
+            .operations
+            .iter()
+            .map(|operation| {
+                serde_json::to_string(operation).expect("Operation should be serializable") + "\n"
+            })
+            .collect();
+        match storage::write_atomically(self.path, content.as_bytes()) {
+            Ok(..) => Ok(()),
+            Err(error) => Err(format!("Error while saving the journal. Error: {error}")),
+        }
+    }
+}
+
+/// Writes the content of a file, removing it when the content is `None`
+fn replace(path: &Path, content: &Option<String>) -> io::Result<()> {
+    match content {
+        Some(content) => storage::write_atomically(path, content.as_bytes()),
+        None => match fs::remove_file(path) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{FileChange, Journal};
+
+    fn generate_dir_for_test(test_name: &str) -> PathBuf {
+        let dir = PathBuf::from("/tmp")
+            .join("todayilearn-tests")
+            .join(format!("journal_test_{}", test_name));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).expect("Failed to clean test dir");
+        }
+        fs::create_dir_all(&dir).expect("Failed to create test dir");
+        dir
+    }
+
+    #[test]
+    fn undo_and_redo_should_restore_the_file() {
+        let dir = generate_dir_for_test("undo_and_redo_should_restore_the_file");
+        let path = dir.join("journal.txt");
+        let file = dir.join("appointments_01012024.txt");
+        let mut journal = Journal::new(&path);
+        fs::write(&file, "first").unwrap();
+        journal
+            .record(
+                "add",
+                "first",
+                vec![FileChange::new(&file, None, Some(String::from("first")))],
+            )
+            .unwrap();
+        fs::write(&file, "second").unwrap();
+        journal
+            .record(
+                "add",
+                "second",
+                vec![FileChange::new(
+                    &file,
+                    Some(String::from("first")),
+                    Some(String::from("second")),
+                )],
+            )
+            .unwrap();
+
+        assert_eq!("second", journal.undo().unwrap().summary);
+        assert_eq!("first", fs::read_to_string(&file).unwrap());
+        assert_eq!("first", journal.undo().unwrap().summary);
+        assert!(!file.exists());
+        assert_eq!("There is nothing to undo.", journal.undo().unwrap_err());
+
+        assert_eq!("first", journal.redo().unwrap().summary);
+        assert_eq!("first", fs::read_to_string(&file).unwrap());
+
+        journal
+            .record(
+                "clear",
+                "1 appointments",
+                vec![FileChange::new(&file, Some(String::from("first")), None)],
+            )
+            .unwrap();
+        fs::remove_file(&file).unwrap();
+        assert_eq!("There is nothing to redo.", journal.redo().unwrap_err());
+        assert_eq!(2, Journal::new(&path).operations().len());
+    }
+
+    #[test]
+    fn undo_and_redo_should_restore_every_file() {
+        let dir = generate_dir_for_test("undo_and_redo_should_restore_every_file");
+        let path = dir.join("journal.txt");
+        let monday = dir.join("appointments_01012024.txt");
+        let tuesday = dir.join("appointments_02012024.txt");
+        let mut journal = Journal::new(&path);
+        fs::write(&monday, "monday").unwrap();
+        fs::write(&tuesday, "tuesday").unwrap();
+        journal
+            .record(
+                "add",
+                "2 appointments",
+                vec![
+                    FileChange::new(&monday, None, Some(String::from("monday"))),
+                    FileChange::new(&tuesday, None, Some(String::from("tuesday"))),
+                ],
+            )
+            .unwrap();
+
+        fs::write(&tuesday, "changed by hand").unwrap();
+        assert!(journal.undo().is_err());
+        assert!(monday.exists());
+
+        fs::write(&tuesday, "tuesday").unwrap();
+        journal.undo().unwrap();
+        assert!(!monday.exists() && !tuesday.exists());
+        journal.redo().unwrap();
+        assert_eq!("monday", fs::read_to_string(&monday).unwrap());
+        assert_eq!("tuesday", fs::read_to_string(&tuesday).unwrap());
+    }
+
+    #[test]
+    fn undo_should_not_overwrite_later_changes() {
+        let dir = generate_dir_for_test("undo_should_not_overwrite_later_changes");
+        let path = dir.join("journal.txt");
+        let file = dir.join("appointments_01012024.txt");
+        let mut journal = Journal::new(&path);
+        journal
+            .record(
+                "add",
+                "first",
+                vec![FileChange::new(&file, None, Some(String::from("first")))],
+            )
+            .unwrap();
+        fs::write(&file, "changed by hand").unwrap();
+
+        assert!(journal
+            .undo()
+            .unwrap_err()
+            .ends_with("was changed after the operation and cannot be restored."));
+        assert_eq!("changed by hand", fs::read_to_string(&file).unwrap());
+    }
+}
@@ -1,18 +1,27 @@
 use core::fmt;
-use std::{
-    fs::{self, File},
-    io::{self, BufWriter, Write},
-    path::PathBuf,
-};
+use std::{fs, path::PathBuf};
+
+use chrono::{Local, NaiveDate};
 
-use crate::appointment::AppointmentTime;
+use crate::appointment::{AppointmentDateTime, AppointmentTime};
 
-use super::Appointment;
+use super::{
+    duration::Duration,
+    journal::{FileChange, Journal},
+    storage::{self, Format, InvalidLine},
+    Appointment, AppointmentChanges, Status,
+};
 
 /// Describe the filter options available for filtering appointments
 pub enum FilterOption {
     ByReferenceTime,
-    ByReferenceAndExpireTime(i32),
+    /// Keeps the appointments not passed that start within the duration. Appointments of the
+    /// next days are not in the list and should be loaded from their own files
+    ByReferenceAndExpireTime(Duration),
+    /// Keeps the appointments with the tag
+    ByTag(String),
+    /// Keeps the appointments without the tag
+    ExcludingTag(String),
 }
 
 /// Describe a list of appointments
@@ -20,6 +29,16 @@ pub struct AppointmentList<'a> {
     reference_time: &'a AppointmentTime,
     path: &'a PathBuf,
     appointments: Vec<Appointment>,
+    occurrences: Vec<Appointment>,
+    format: Format,
+    invalid: Vec<InvalidLine>,
+    /// Identifier of the next appointment added, see `storage::next_id`
+    next_id: u32,
+    journal: Option<PathBuf>,
+    /// If the appointments are shown in another time zone than the one they are stored in
+    converted: bool,
+    /// Date of the appointments and moment they are compared to, see `for_date`
+    date: Option<(NaiveDate, AppointmentDateTime)>,
 }
 
 impl<'a> AppointmentList<'a> {
@@ -42,11 +61,131 @@ impl<'a> AppointmentList<'a> {
             reference_time,
             path,
             appointments: vec![],
+            occurrences: vec![],
+            format: Format::JsonLines(storage::FORMAT_VERSION),
+            invalid: vec![],
+            next_id: 1,
+            journal: None,
+            converted: false,
+            date: None,
         };
         new_appointment.load();
         new_appointment
     }
 
+    /// Sets the occurrences of recurrences for the day of the list. They are merged with the
+    /// appointments loaded from the path but never written to it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentList, AppointmentTime, RecurrenceList};
+    /// use chrono::NaiveDate;
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let recurrences_path = PathBuf::from("/tmp").join("todayiwill").join("recurrences_test_occurrences.txt");
+    /// fs::create_dir_all(recurrences_path.parent().unwrap()).expect("Failed to create test dir");
+    /// fs::write(&recurrences_path, "1|01062024|daily|12:30||Lunch\n").expect("Failed to write test file");
+    /// let recurrences = RecurrenceList::new(&recurrences_path);
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_occurrences.txt");
+    /// let list = AppointmentList::new(&reference_time, &path)
+    ///     .with_occurrences(recurrences.occurrences(NaiveDate::from_ymd_opt(2024, 6, 10).unwrap()));
+    /// assert_eq!("[12:30] Lunch", list.to_string());
+    /// ```
+    pub fn with_occurrences(mut self, occurrences: Vec<Appointment>) -> Self {
+        self.occurrences = occurrences;
+        self.load();
+        self
+    }
+
+    /// Sets the date of the appointments of the list and the moment they are compared to, instead
+    /// of the reference time on a day left unknown. Appointments crossing midnight are then in
+    /// progress on the next day too
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentDateTime, AppointmentList, AppointmentTime, FilterOption};
+    /// use chrono::NaiveDate;
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_for_date.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// fs::write(&path, "07:00 Gym\n").expect("Failed to write test file");
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 11).unwrap();
+    /// let now = AppointmentDateTime::new(date.pred_opt().unwrap(), &AppointmentTime::new(9, 0).unwrap());
+    /// let reference_time = AppointmentTime::new(9, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path).for_date(date, now);
+    /// list.filter(FilterOption::ByReferenceTime);
+    /// assert_eq!(Some(date), list.appointments()[0].date());
+    /// ```
+    pub fn for_date(mut self, date: NaiveDate, now: AppointmentDateTime) -> Self {
+        self.date = Some((date, now));
+        self.set_dates();
+        self.appointments.sort();
+        self
+    }
+
+    /// Records the changes made to the file in a journal, see `Journal`. The journal should be in
+    /// the same dir as the file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime, Journal};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let dir = PathBuf::from("/tmp").join("todayiwill-list-journal");
+    /// let path = dir.join("appointments_01012024.txt");
+    /// let journal_path = dir.join("journal.txt");
+    /// fs::create_dir_all(&dir).expect("Failed to create test dir");
+    /// for file in [&path, &journal_path] {
+    ///     if file.exists() {
+    ///         fs::remove_file(file).expect("Failed to clean test file");
+    ///     }
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path).with_journal(journal_path.clone());
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(9, 0).unwrap())).unwrap();
+    /// assert_eq!("[09:00] Standup", Journal::new(&journal_path).operations()[0].summary);
+    /// ```
+    pub fn with_journal(mut self, journal: PathBuf) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Replaces the appointments by the ones shown on the day of the list in another time zone,
+    /// see `Appointment::in_zone`. The list is only for showing them afterwards, it cannot be
+    /// written
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime};
+    /// use std::path::PathBuf;
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_shown_in_zone.txt");
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.show_in_zone(vec![Appointment::new(String::from("Sync"), AppointmentTime::new(22, 0).unwrap())]);
+    /// assert_eq!("[22:00] Sync", list.to_string());
+    /// assert!(list.write().is_err());
+    /// ```
+    pub fn show_in_zone(&mut self, mut appointments: Vec<Appointment>) {
+        if let Some((date, _)) = self.date {
+            for appointment in appointments.iter_mut() {
+                appointment.date.get_or_insert(date);
+            }
+        }
+        appointments.sort();
+        self.appointments = appointments;
+        self.converted = true;
+    }
+
     /// Returns a reference of the current state of appontments
     ///
     /// # Example
@@ -81,6 +220,71 @@ impl<'a> AppointmentList<'a> {
         self.appointments.is_empty()
     }
 
+    /// Returns if the file was loaded from the legacy `HH:MM description` format. Legacy files are
+    /// migrated to the current format on the next write
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_legacy.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// fs::write(&path, "09:00 An old appointment\n").expect("Failed to write test file");
+    ///
+    /// let reference_time = AppointmentTime::now();
+    /// let list = AppointmentList::new(&reference_time, &path);
+    /// assert!(list.is_legacy_format());
+    /// list.write().unwrap();
+    /// assert!(!AppointmentList::new(&reference_time, &path).is_legacy_format());
+    /// ```
+    pub fn is_legacy_format(&self) -> bool {
+        self.format == Format::Legacy
+    }
+
+    /// Returns the format the file was loaded from
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the lines of the file that could not be parsed. They are written back as they are
+    /// until moved with `quarantine`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_invalid_lines.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// fs::write(&path, "{\"version\":1}\n{\"time\":\"25:00\",\"description\":\"Late\"}\n").expect("Failed to write test file");
+    ///
+    /// let reference_time = AppointmentTime::now();
+    /// let list = AppointmentList::new(&reference_time, &path);
+    /// assert_eq!(2, list.invalid_lines()[0].number);
+    /// ```
+    pub fn invalid_lines(&self) -> &[InvalidLine] {
+        &self.invalid
+    }
+
+    /// Moves the lines that could not be parsed to the quarantine file, see
+    /// `storage::quarantine_path`. Returns how many lines were moved
+    pub fn quarantine(&mut self) -> Result<usize, String> {
+        if self.invalid.is_empty() {
+            return Ok(0);
+        }
+        let mut moved = 0;
+        self.update("quarantine", |list| {
+            storage::quarantine(list.path, &list.invalid)?;
+            moved = list.invalid.len();
+            list.invalid.clear();
+            Ok(format!("{moved} invalid lines"))
+        })?;
+        Ok(moved)
+    }
+
     /// Reads the current path and fill the appointments vector. It is automatically done at
     /// AppointmentList instantiation
     ///
@@ -100,19 +304,15 @@ impl<'a> AppointmentList<'a> {
     /// assert_eq!(&vec![Appointment::new(String::from("Example appointment"), AppointmentTime::new(7, 45).unwrap())], list.appointments());
     /// ```
     pub fn load(&mut self) -> &Self {
-        let file_result = fs::read_to_string(self.path);
-        let file_content = match file_result {
-            Ok(content) => content,
-            Err(..) => String::new(),
-        };
-        let appointments: Vec<Result<Appointment, String>> =
-            file_content.lines().map(Appointment::from).collect();
-        self.appointments = appointments.into_iter().flatten().collect();
+        (self.format, self.appointments, self.invalid, self.next_id) = read_appointments(self.path);
+        self.appointments.extend(self.occurrences.iter().cloned());
+        self.set_dates();
         self.appointments.sort();
+        self.converted = false;
         self
     }
 
-    /// Add an appointment to the list and the file
+    /// Add an appointment to the list and the file. Other appointments at the same time are kept
     ///
     /// # Example
     ///
@@ -130,14 +330,115 @@ impl<'a> AppointmentList<'a> {
     /// let mut list = AppointmentList::new(&reference_time, &path);
     /// list.add(Appointment::new(String::from("New appointment"), AppointmentTime::new(14, 8).unwrap())).unwrap();
     /// assert_eq!(&vec![Appointment::new(String::from("New appointment"), AppointmentTime::new(14, 8).unwrap())], list.appointments());
-    /// assert_eq!("14:08 New appointment\n", fs::read_to_string(&path).expect("Failed to read file content"));
+    /// assert_eq!(list.appointments(), AppointmentList::new(&reference_time, &path).appointments());
     /// ```
     pub fn add(&mut self, appointment: Appointment) -> Result<(), String> {
-        self.appointments.retain(|a| a.time != appointment.time);
-        self.appointments.push(appointment);
-        self.appointments.sort();
-        self.write()?;
-        Ok(())
+        self.update("add", |list| {
+            let summary = summary(&appointment);
+            list.insert(appointment);
+            Ok(summary)
+        })
+    }
+
+    /// Adds many appointments to the list and writes the file once. With `replace`, the
+    /// appointments at the same time of each new one are removed first, see `replace`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_add_all.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add_all(vec![
+    ///     Appointment::new(String::from("Standup"), AppointmentTime::new(9, 0).unwrap()),
+    ///     Appointment::new(String::from("Lunch"), AppointmentTime::new(12, 0).unwrap()),
+    /// ], false).unwrap();
+    /// assert_eq!(2, AppointmentList::new(&reference_time, &path).appointments().len());
+    /// ```
+    pub fn add_all(&mut self, appointments: Vec<Appointment>, replace: bool) -> Result<(), String> {
+        self.update("add", |list| Ok(list.insert_all(appointments, replace)))
+    }
+
+    /// Adds appointments to many lists of the same directory, see `add_all`. The lock is held
+    /// once and every list is reloaded and changed before any file is written. If writing a file
+    /// fails, the ones already written are restored, so either every file is changed or none
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let dir = PathBuf::from("/tmp").join("todayiwill");
+    /// let monday = dir.join("appointments_test_add_to_lists_monday.txt");
+    /// let tuesday = dir.join("appointments_test_add_to_lists_tuesday.txt");
+    /// fs::create_dir_all(&dir).expect("Failed to create test dir");
+    /// for path in [&monday, &tuesday] {
+    ///     if path.exists() {
+    ///         fs::remove_file(path).expect("Failed to clean test file");
+    ///     }
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let standup = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 0).unwrap());
+    /// let lunch = Appointment::new(String::from("Lunch"), AppointmentTime::new(12, 0).unwrap());
+    /// AppointmentList::add_to_lists(vec![
+    ///     (AppointmentList::new(&reference_time, &monday), vec![standup]),
+    ///     (AppointmentList::new(&reference_time, &tuesday), vec![lunch]),
+    /// ], false).unwrap();
+    /// assert_eq!(1, AppointmentList::new(&reference_time, &monday).appointments().len());
+    /// assert_eq!(1, AppointmentList::new(&reference_time, &tuesday).appointments().len());
+    /// ```
+    pub fn add_to_lists(
+        lists: Vec<(AppointmentList<'a>, Vec<Appointment>)>,
+        replace: bool,
+    ) -> Result<(), String> {
+        let Some((first, _)) = lists.first() else {
+            return Ok(());
+        };
+        let _lock = storage::lock(first.path)?;
+        let first_journal = first.journal.clone();
+        let added: usize = lists
+            .iter()
+            .map(|(_, appointments)| appointments.len())
+            .sum();
+        let mut staged = vec![];
+        for (mut list, appointments) in lists {
+            let before = fs::read_to_string(list.path).ok();
+            list.load();
+            let summary = list.insert_all(appointments, replace);
+            staged.push((list, summary, before));
+        }
+        for (index, (list, ..)) in staged.iter().enumerate() {
+            if let Err(error) = list.save() {
+                for (written, _, before) in &staged[..index] {
+                    written.restore(before);
+                }
+                return Err(error);
+            }
+        }
+        let summary = match &staged[..] {
+            [(_, summary, _)] => summary.clone(),
+            _ => format!("{added} appointments"),
+        };
+        let changes: Vec<FileChange> = staged
+            .iter()
+            .filter_map(|(list, _, before)| list.change(before.clone()))
+            .collect();
+        match &first_journal {
+            Some(journal) if !changes.is_empty() => {
+                Journal::new(journal).record("add", &summary, changes)
+            }
+            _ => Ok(()),
+        }
     }
 
     /// Removes an appointment from the list and the file
@@ -160,26 +461,385 @@ impl<'a> AppointmentList<'a> {
     /// list.add(Appointment::new(String::from("Other appointment"), AppointmentTime::new(22, 48).unwrap()));
     /// list.remove(AppointmentTime::new(22, 48).unwrap()).unwrap();
     /// assert_eq!(&vec![Appointment::new(String::from("New appointment"), AppointmentTime::new(5, 32).unwrap())], list.appointments());
-    /// assert_eq!("05:32 New appointment\n", fs::read_to_string(&path).expect("Failed to read file content"));
+    /// assert_eq!(list.appointments(), AppointmentList::new(&reference_time, &path).appointments());
     /// ```
     pub fn remove(&mut self, time: AppointmentTime) -> Result<(), String> {
-        match self.appointments.iter().position(|a| a.time == time) {
-            Some(index) => {
-                if self.appointments[index].is_equal_or_earlier_than(self.reference_time) {
-                    return Err(String::from(
-                        "This appointment is already past and cannot be removed.",
-                    ));
-                }
-                self.appointments.remove(index);
+        self.update("remove", |list| {
+            let index = list.index_at(&time)?;
+            list.remove_at(index)
+        })
+    }
+
+    /// Removes an appointment from the list and the file by its id
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_remove_by_id.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// list.add(Appointment::new(String::from("Call Bob"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// list.remove_by_id(1).unwrap();
+    /// assert_eq!(&vec![Appointment::new(String::from("Call Bob"), AppointmentTime::new(10, 0).unwrap())], list.appointments());
+    /// ```
+    pub fn remove_by_id(&mut self, id: u32) -> Result<(), String> {
+        self.update("remove", |list| {
+            let index = list.index_of(id)?;
+            list.remove_at(index)
+        })
+    }
+
+    /// Records what happened to the appointment at a given time. Occurrences of recurrences are
+    /// stored as regular appointments, so the recurrence should be skipped on that day
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime, Status};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_set_status.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(11, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// list.set_status(AppointmentTime::new(10, 0).unwrap(), Status::Done).unwrap();
+    /// assert_eq!(Status::Done, AppointmentList::new(&reference_time, &path).appointments()[0].status());
+    /// ```
+    pub fn set_status(&mut self, time: AppointmentTime, status: Status) -> Result<(), String> {
+        self.update(&status.to_string(), |list| {
+            let index = list.index_at(&time)?;
+            list.edit_at(index, status_change(status))
+        })
+    }
+
+    /// Records what happened to an appointment by its id, see `set_status`
+    pub fn set_status_by_id(&mut self, id: u32, status: Status) -> Result<(), String> {
+        self.update(&status.to_string(), |list| {
+            let index = list.index_of(id)?;
+            list.edit_at(index, status_change(status))
+        })
+    }
+
+    /// Changes the appointment at a given time, past ones included. Occurrences of recurrences
+    /// are stored as regular appointments, so the recurrence should be skipped on that day
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentChanges, AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_edit.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(11, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// let changes = AppointmentChanges { description: Some(String::from("Daily")), ..Default::default() };
+    /// list.edit(AppointmentTime::new(10, 0).unwrap(), changes).unwrap();
+    /// assert_eq!("10:00 Daily", AppointmentList::new(&reference_time, &path).appointments()[0].to_string());
+    /// ```
+    pub fn edit(
+        &mut self,
+        time: AppointmentTime,
+        changes: AppointmentChanges,
+    ) -> Result<(), String> {
+        self.update("edit", |list| {
+            let index = list.index_at(&time)?;
+            list.edit_at(index, changes)
+        })
+    }
+
+    /// Changes an appointment by its id, see `edit`
+    pub fn edit_by_id(&mut self, id: u32, changes: AppointmentChanges) -> Result<(), String> {
+        self.update("edit", |list| {
+            let index = list.index_of(id)?;
+            list.edit_at(index, changes)
+        })
+    }
+
+    /// Changes the occurrence of a recurrence, storing it as a regular appointment that keeps what
+    /// is not changed, see `edit`. The recurrence should be skipped on that day
+    pub fn edit_occurrence(
+        &mut self,
+        recurrence_id: u32,
+        changes: AppointmentChanges,
+    ) -> Result<(), String> {
+        self.update("edit", |list| {
+            let index = list
+                .appointments
+                .iter()
+                .position(|a| a.recurrence_id == Some(recurrence_id))
+                .ok_or(String::from(
+                    "The recurrence has no occurrence on the given date.",
+                ))?;
+            list.edit_at(index, changes)
+        })
+    }
+
+    /// Replaces all the appointments stored for the day. Appointments without an id or with a
+    /// repeated one get a new id
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_replace_all.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(11, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// list.replace_all(vec![Appointment::new(String::from("Gym"), AppointmentTime::new(7, 0).unwrap())]).unwrap();
+    /// assert_eq!("[07:00] Gym", AppointmentList::new(&reference_time, &path).to_string());
+    /// ```
+    pub fn replace_all(&mut self, appointments: Vec<Appointment>) -> Result<(), String> {
+        let now = Local::now();
+        let mut stored: Vec<Appointment> = vec![];
+        for mut appointment in appointments {
+            appointment.recurrence_id = None;
+            if appointment.metadata.id == 0
+                || stored
+                    .iter()
+                    .any(|a| a.metadata.id == appointment.metadata.id)
+            {
+                appointment.metadata.id = 0;
             }
-            None => {
-                return Err(String::from(
-                    "There is no appointment at this specific time.",
-                ))
+            appointment.metadata.created_at.get_or_insert(now);
+            stored.push(appointment);
+        }
+        self.update("edit", |list| {
+            list.next_id = list.next_id.max(max_id(&stored) + 1);
+            for appointment in stored.iter_mut() {
+                if appointment.metadata.id == 0 {
+                    appointment.metadata.id = list.take_id();
+                }
             }
-        };
-        self.write()?;
-        Ok(())
+            let summary = format!("{} appointments", stored.len());
+            list.appointments = list.occurrences.clone();
+            list.appointments.extend(stored);
+            list.set_dates();
+            list.appointments.sort();
+            Ok(summary)
+        })
+    }
+
+    /// Returns the index of the only appointment at a given time
+    fn index_at(&self, time: &AppointmentTime) -> Result<usize, String> {
+        let indexes = self.indexes_at(time);
+        match indexes[..] {
+            [] => Err(String::from(
+                "There is no appointment at this specific time.",
+            )),
+            [index] => Ok(index),
+            _ => Err(format!(
+                "There are {} appointments at {time} ({}). Choose one by its id.",
+                indexes.len(),
+                indexes
+                    .iter()
+                    .map(|index| &self.appointments[*index])
+                    .map(|a| format!("#{} {}", a.metadata.id, a.description))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Returns the index of the appointment with a given id. Occurrences of recurrences have no id
+    fn index_of(&self, id: u32) -> Result<usize, String> {
+        match self
+            .appointments
+            .iter()
+            .position(|a| a.recurrence_id.is_none() && a.metadata.id == id)
+        {
+            Some(index) => Ok(index),
+            None => Err(String::from("There is no appointment with this id.")),
+        }
+    }
+
+    /// Replaces the appointments at the same time of the given one by it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_replace.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// list.replace(Appointment::new(String::from("Planning"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// assert_eq!(&vec![Appointment::new(String::from("Planning"), AppointmentTime::new(10, 0).unwrap())], list.appointments());
+    /// ```
+    pub fn replace(&mut self, appointment: Appointment) -> Result<(), String> {
+        self.update("replace", |list| {
+            let summary = summary(&appointment);
+            list.appointments.retain(|a| a.time != appointment.time);
+            list.insert(appointment);
+            Ok(summary)
+        })
+    }
+
+    /// Returns the next pending appointment, the one in progress or starting at the reference
+    /// time first, then the earliest one starting after it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime, Status};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_next.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(9, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Breakfast"), AppointmentTime::new(8, 0).unwrap())).unwrap();
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap()).with_status(Status::Cancelled)).unwrap();
+    /// list.add(Appointment::new(String::from("Lunch"), AppointmentTime::new(12, 0).unwrap())).unwrap();
+    /// assert_eq!("Lunch", list.next().unwrap().description());
+    /// ```
+    pub fn next(&self) -> Option<&Appointment> {
+        let (day, now) = (self.day(), self.now());
+        self.appointments
+            .iter()
+            .filter(|a| a.status().is_pending())
+            .filter(|a| a.starts_at(day) >= now || a.is_in_progress_at(&now))
+            .min_by_key(|a| a.starts_at(day))
+    }
+
+    /// Returns the appointments at a given time. Occurrences of recurrences are only returned when
+    /// there is no other appointment at that time
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_appointments_at.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Standup"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// list.add(Appointment::new(String::from("Call Bob"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// assert_eq!(2, list.appointments_at(&AppointmentTime::new(10, 0).unwrap()).len());
+    /// ```
+    pub fn appointments_at(&self, time: &AppointmentTime) -> Vec<&Appointment> {
+        self.indexes_at(time)
+            .into_iter()
+            .map(|index| &self.appointments[index])
+            .collect()
+    }
+
+    /// Returns the appointments taking place at the same time as the given one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime, Duration};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_overlapping.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let reference_time = AppointmentTime::new(8, 0).unwrap();
+    /// let mut list = AppointmentList::new(&reference_time, &path);
+    /// list.add(Appointment::new(String::from("Workshop"), AppointmentTime::new(14, 0).unwrap()).with_duration(Duration::from_minutes(120))).unwrap();
+    /// list.add(Appointment::new(String::from("Dinner"), AppointmentTime::new(19, 0).unwrap())).unwrap();
+    /// let call = Appointment::new(String::from("Call"), AppointmentTime::new(15, 0).unwrap());
+    /// assert_eq!("Workshop", list.overlapping(&call)[0].description());
+    /// assert_eq!(1, list.overlapping(&call).len());
+    /// ```
+    pub fn overlapping(&self, appointment: &Appointment) -> Vec<&Appointment> {
+        self.appointments
+            .iter()
+            .filter(|a| a.overlaps(appointment))
+            .collect()
+    }
+
+    /// Returns the indexes of the appointments at a given time, see `appointments_at`
+    fn indexes_at(&self, time: &AppointmentTime) -> Vec<usize> {
+        let mut indexes: Vec<usize> = (0..self.appointments.len())
+            .filter(|index| self.appointments[*index].time == *time)
+            .collect();
+        if indexes
+            .iter()
+            .any(|index| self.appointments[*index].recurrence_id.is_none())
+        {
+            indexes.retain(|index| self.appointments[*index].recurrence_id.is_none());
+        }
+        indexes
+    }
+
+    /// Applies the changes to the appointment at the index informed. Occurrences of recurrences
+    /// become regular appointments. Returns the summary of the appointment before the changes
+    fn edit_at(&mut self, index: usize, changes: AppointmentChanges) -> Result<String, String> {
+        let original = &self.appointments[index];
+        let summary = summary(original);
+        let mut appointment = original.clone().apply(changes)?;
+        if appointment.recurrence_id.is_some() {
+            appointment.recurrence_id = None;
+            self.occurrences
+                .retain(|a| !(a.time == original.time && a.description == original.description));
+        }
+        self.appointments.remove(index);
+        self.insert(appointment);
+        Ok(summary)
+    }
+
+    /// Removes the appointment at the index informed, checking if it can be removed. Returns the
+    /// summary of the appointment removed
+    fn remove_at(&mut self, index: usize) -> Result<String, String> {
+        if self.appointments[index].is_passed_at(&self.now()) {
+            return Err(String::from(
+                "This appointment is already past and cannot be removed.",
+            ));
+        }
+        if self.appointments[index].recurrence_id.is_some() {
+            return Err(String::from(
+                "This appointment is an occurrence of a recurrence and should be skipped instead.",
+            ));
+        }
+        Ok(summary(&self.appointments.remove(index)))
     }
 
     /// Write the current state of appointments to the path. It is automaically done in some
@@ -202,9 +862,118 @@ impl<'a> AppointmentList<'a> {
     /// list.add(Appointment::new(String::from("New appointment"), AppointmentTime::new(5, 32).unwrap()));
     /// list.add(Appointment::new(String::from("Other appointment"), AppointmentTime::new(22, 48).unwrap()));
     /// list.write().unwrap();
-    /// assert_eq!("05:32 New appointment\n22:48 Other appointment\n", fs::read_to_string(&path).expect("Failed to read file content"));
+    /// assert_eq!(2, AppointmentList::new(&reference_time, &path).appointments().len());
     /// ```
     pub fn write(&self) -> Result<(), String> {
+        let _lock = storage::lock(self.path)?;
+        self.save()
+    }
+
+    /// Reloads the file and applies a change holding the lock of its directory, then writes the
+    /// appointments and records the operation. Changes made by other processes since the list was
+    /// loaded are kept. The change returns the summary of what was changed
+    fn update(
+        &mut self,
+        operation: &str,
+        change: impl FnOnce(&mut Self) -> Result<String, String>,
+    ) -> Result<(), String> {
+        let _lock = storage::lock(self.path)?;
+        let before = fs::read_to_string(self.path).ok();
+        self.load();
+        let summary = change(self)?;
+        self.save()?;
+        self.record(operation, &summary, before)
+    }
+
+    /// Records an operation in the journal, when the list has one. The lock should already be
+    /// held
+    fn record(&self, operation: &str, summary: &str, before: Option<String>) -> Result<(), String> {
+        let (Some(journal), Some(change)) = (&self.journal, self.change(before)) else {
+            return Ok(());
+        };
+        Journal::new(journal).record(operation, summary, vec![change])
+    }
+
+    /// Returns the change of the file from its content before, `None` when it did not change
+    fn change(&self, before: Option<String>) -> Option<FileChange> {
+        let after = fs::read_to_string(self.path).ok();
+        (before != after).then(|| FileChange::new(self.path, before, after))
+    }
+
+    /// Inserts many appointments, see `add_all`, returning the summary of what was added
+    fn insert_all(&mut self, appointments: Vec<Appointment>, replace: bool) -> String {
+        let summary = match &appointments[..] {
+            [appointment] => summary(appointment),
+            _ => format!("{} appointments", appointments.len()),
+        };
+        for appointment in appointments {
+            if replace {
+                self.appointments.retain(|a| a.time != appointment.time);
+            }
+            self.insert(appointment);
+        }
+        summary
+    }
+
+    /// Puts back the content a file had before being written, removing it when it did not
+    /// exist. The lock should already be held. Failures are ignored, since this is done while
+    /// reporting another error
+    fn restore(&self, before: &Option<String>) {
+        let _ = match before {
+            Some(content) => storage::write_atomically(self.path, content.as_bytes()),
+            None => fs::remove_file(self.path),
+        };
+    }
+
+    /// Inserts an appointment in its position, assigning an id to it when it has none
+    fn insert(&mut self, mut appointment: Appointment) {
+        let now = Local::now();
+        if appointment.metadata.id == 0 {
+            appointment.metadata.id = self.take_id();
+        }
+        self.next_id = self.next_id.max(appointment.metadata.id + 1);
+        appointment.metadata.created_at.get_or_insert(now);
+        appointment.metadata.updated_at = Some(now);
+        self.appointments.push(appointment);
+        self.set_dates();
+        self.appointments.sort();
+    }
+
+    /// Returns the identifier of the next appointment added and moves on to the following one
+    fn take_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    /// Sets the date of the list to the appointments without one, see `for_date`
+    fn set_dates(&mut self) {
+        if let Some((date, _)) = self.date {
+            for appointment in self.appointments.iter_mut() {
+                appointment.date.get_or_insert(date);
+            }
+        }
+    }
+
+    /// Returns the date of the appointments without one. Lists without a date use an arbitrary one
+    fn day(&self) -> NaiveDate {
+        self.date.map(|(date, _)| date).unwrap_or_default()
+    }
+
+    /// Returns the moment the appointments are compared to, see `for_date`
+    pub fn now(&self) -> AppointmentDateTime {
+        match self.date {
+            Some((_, now)) => now,
+            None => AppointmentDateTime::new(self.day(), self.reference_time),
+        }
+    }
+
+    /// Writes the appointments, see `write`. The lock should already be held
+    fn save(&self) -> Result<(), String> {
+        if self.converted {
+            return Err(String::from(
+                "Appointments shown in another time zone cannot be saved.",
+            ));
+        }
         match self.write_to_file() {
             Ok(..) => Ok(()),
             Err(error) => Err(format!(
@@ -220,7 +989,7 @@ impl<'a> AppointmentList<'a> {
     /// # Example
     ///
     /// ```
-    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime, FilterOption};
+    /// use todayiwill::{Appointment, AppointmentList, AppointmentTime, Duration, FilterOption};
     /// use std::{fs, path::PathBuf};
     ///
     /// let path = PathBuf::from("/tmp").join("todayiwill").join("appointments_test_filter.txt");
@@ -233,20 +1002,24 @@ impl<'a> AppointmentList<'a> {
     /// let mut list = AppointmentList::new(&reference_time, &path);
     /// list.add(Appointment::new(String::from("New appointment"), AppointmentTime::new(18, 56).unwrap()));
     /// list.add(Appointment::new(String::from("Other appointment"), AppointmentTime::new(10, 2).unwrap()));
-    /// list.filter(FilterOption::ByReferenceAndExpireTime(5));
+    /// list.filter(FilterOption::ByReferenceAndExpireTime(Duration::from_minutes(5)));
     /// assert_eq!(&vec![Appointment::new(String::from("New appointment"), AppointmentTime::new(18, 56).unwrap())], list.appointments());
     /// ```
     pub fn filter(&mut self, options: FilterOption) -> &Self {
-        let filter_by_reference_time = |a: &Appointment| a.time > *self.reference_time;
+        let (day, now) = (self.day(), self.now());
+        let filter_by_reference_time =
+            |a: &Appointment| !a.is_passed_at(&now) || a.is_in_progress_at(&now);
         match options {
             FilterOption::ByReferenceTime => {
                 self.appointments.retain(filter_by_reference_time);
             }
-            FilterOption::ByReferenceAndExpireTime(expire_in_seconds) => {
+            FilterOption::ByReferenceAndExpireTime(expire_in) => {
                 self.appointments.retain(filter_by_reference_time);
-                self.appointments
-                    .retain(|a| a.time <= self.reference_time.clone() + expire_in_seconds);
+                let until = now + expire_in;
+                self.appointments.retain(|a| a.starts_at(day) <= until);
             }
+            FilterOption::ByTag(tag) => self.appointments.retain(|a| a.has_tag(&tag)),
+            FilterOption::ExcludingTag(tag) => self.appointments.retain(|a| !a.has_tag(&tag)),
         }
         self
     }
@@ -279,7 +1052,10 @@ impl<'a> AppointmentList<'a> {
     /// assert_eq!(&vec![Appointment::new(String::from("Example appointment"), AppointmentTime::new(15, 58).unwrap())], list.appointments());
     /// ```
     pub fn copy(&mut self, from: &PathBuf) -> Result<(), String> {
-        if !self.appointments.is_empty() {
+        let _lock = storage::lock(self.path)?;
+        let before = fs::read_to_string(self.path).ok();
+        self.load();
+        if self.appointments.iter().any(|a| a.recurrence_id.is_none()) {
             return Err(String::from(
                 "Copy not possible, there are appointments for the current day.",
             ));
@@ -287,16 +1063,23 @@ impl<'a> AppointmentList<'a> {
         if !from.exists() {
             return Err(String::from("Given day has no appointments."));
         }
-        match fs::copy(from, self.path) {
-            Ok(..) => {
-                self.load();
-                Ok(())
-            }
-            Err(error) => Err(format!(
+        let (_, mut appointments, _, next_id) = read_appointments(from);
+        let summary = format!("{} appointments", appointments.len());
+        // The copies are on the date of the list, not the one they were written with
+        for appointment in appointments.iter_mut() {
+            appointment.date = None;
+        }
+        self.next_id = self.next_id.max(next_id);
+        self.appointments.extend(appointments);
+        self.set_dates();
+        self.appointments.sort();
+        if let Err(error) = self.write_to_file() {
+            return Err(format!(
                 "An error ocurred while copying an appointments file. {}",
                 error
-            )),
+            ));
         }
+        self.record("copy", &summary, before)
     }
 
     /// Clears the appointments added for the current day
@@ -319,34 +1102,97 @@ impl<'a> AppointmentList<'a> {
     /// assert!(list.no_appointments());
     /// ```
     pub fn clear(&mut self) -> Result<(), String> {
-        self.appointments = vec![];
-        match fs::remove_file(self.path) {
-            Ok(..) => Ok(()),
-            Err(error) => Err(format!(
+        let _lock = storage::lock(self.path)?;
+        let before = fs::read_to_string(self.path).ok();
+        self.load();
+        let summary = format!(
+            "{} appointments",
+            self.appointments
+                .iter()
+                .filter(|a| a.recurrence_id.is_none())
+                .count()
+        );
+        self.appointments = self.occurrences.clone();
+        self.invalid.clear();
+        // The file is kept with its header, so the identifiers of the cleared appointments are
+        // not given again
+        if let Err(error) = self.write_to_file() {
+            return Err(format!(
                 "An error occurred while clearing the appointments. {}",
                 error
-            )),
+            ));
         }
+        self.record("clear", &summary, before)
     }
 
     /// Writes the appointments vector in the path supplied
-    fn write_to_file(&self) -> Result<(), io::Error> {
-        fs::create_dir_all(self.path.parent().unwrap())?;
-        let file = File::create(self.path)?;
-        let mut writer = BufWriter::new(file);
-        for appointment in &self.appointments {
-            writeln!(writer, "{}", appointment)?;
+    fn write_to_file(&self) -> Result<(), String> {
+        let appointments: Vec<&Appointment> = self
+            .appointments
+            .iter()
+            .filter(|a| a.recurrence_id.is_none())
+            .collect();
+        let mut content = storage::serialize(&appointments, self.format, self.next_id)?;
+        for line in &self.invalid {
+            content.push_str(&line.content);
+            content.push('\n');
         }
-        Ok(())
+        storage::write_atomically(self.path, content.as_bytes()).map_err(|error| error.to_string())
     }
 }
 
+/// Reads the appointments stored in a path, assigning identifiers to the ones without it, the
+/// lines that could not be parsed and the identifier of the next appointment. Missing files are
+/// considered empty
+fn read_appointments(path: &PathBuf) -> (Format, Vec<Appointment>, Vec<InvalidLine>, u32) {
+    let file_content = fs::read_to_string(path).unwrap_or_default();
+    let (format, mut appointments, invalid) = storage::parse_valid(&file_content);
+    let mut next_id = storage::next_id(&file_content)
+        .unwrap_or(1)
+        .max(max_id(&appointments) + 1);
+    for appointment in appointments.iter_mut() {
+        if appointment.metadata.id == 0 {
+            appointment.metadata.id = next_id;
+            next_id += 1;
+        }
+    }
+    (format, appointments, invalid, next_id)
+}
+
+/// Returns how an appointment is shown in the journal
+fn summary(appointment: &Appointment) -> String {
+    format!(
+        "[{}] {}{}",
+        appointment.time_range(),
+        appointment.description,
+        appointment.tags_suffix()
+    )
+}
+
+/// Returns the changes needed for recording what happened to an appointment
+fn status_change(status: Status) -> AppointmentChanges {
+    AppointmentChanges {
+        status: Some(status),
+        ..Default::default()
+    }
+}
+
+/// Returns the highest identifier of the appointments, `0` when there is none
+fn max_id(appointments: &[Appointment]) -> u32 {
+    appointments
+        .iter()
+        .map(|a| a.metadata.id)
+        .max()
+        .unwrap_or(0)
+}
+
 impl<'a> fmt::Display for AppointmentList<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let now = self.now();
         let appointments_text = self
             .appointments
             .iter()
-            .map(|a| a.to_string_display(self.reference_time))
+            .map(|a| a.to_string_display_at(&now))
             .collect::<Vec<String>>()
             .join("\n");
 
@@ -356,11 +1202,17 @@ impl<'a> fmt::Display for AppointmentList<'a> {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs, fs::File, io::Write, path::PathBuf};
+    use std::{
+        fs,
+        fs::File,
+        io::Write,
+        path::{Path, PathBuf},
+    };
 
     use crate::appointment::{
+        duration::Duration,
         list::{AppointmentList, FilterOption},
-        Appointment, AppointmentTime,
+        storage, Appointment, AppointmentChanges, AppointmentTime,
     };
 
     use colored::Colorize;
@@ -376,12 +1228,23 @@ mod tests {
         path
     }
 
+    #[allow(clippy::ptr_arg)]
     fn write_to_path(path: &PathBuf, content: &[u8]) {
         let mut file = File::create(path.to_str().unwrap()).expect("Failed to create test file");
         file.write_all(content)
             .expect("Failed to write to test file");
     }
 
+    fn read_from_path(path: &Path) -> Vec<String> {
+        let content = fs::read_to_string(path).expect("Failed to read file content");
+        let (format, appointments) = storage::parse(&content);
+        assert_eq!(format, storage::Format::JsonLines(storage::FORMAT_VERSION));
+        appointments
+            .into_iter()
+            .map(|a| a.unwrap().to_string())
+            .collect()
+    }
+
     #[test]
     fn parse_and_load_file_content_should_be_ok() {
         let test_file_path = PathBuf::from("/tmp")
@@ -498,8 +1361,10 @@ mod tests {
                 String::from("Do the laundry"),
                 AppointmentTime::new(16, 28).unwrap(),
             ),],
-            list.filter(FilterOption::ByReferenceAndExpireTime(5))
-                .appointments()
+            list.filter(FilterOption::ByReferenceAndExpireTime(
+                Duration::from_minutes(5)
+            ))
+            .appointments()
         );
     }
 
@@ -538,26 +1403,33 @@ mod tests {
         list.write().expect("Failed to write appointments on file");
 
         assert_eq!(
-            "15:46 Call aunt Anna\n16:56 Buy new cup\n20:45 Clean kitchen floor\n",
-            fs::read_to_string(&path).expect("Failed to read file content")
+            vec![
+                "15:46 Call aunt Anna",
+                "16:56 Buy new cup",
+                "20:45 Clean kitchen floor"
+            ],
+            read_from_path(&path)
         );
 
         list.remove(AppointmentTime::new(16, 56).unwrap()).unwrap();
 
         assert_eq!(
-            "15:46 Call aunt Anna\n20:45 Clean kitchen floor\n",
-            fs::read_to_string(&path).expect("Failed to read file content")
+            vec!["15:46 Call aunt Anna", "20:45 Clean kitchen floor"],
+            read_from_path(&path)
         );
 
-        list.add(Appointment::new(
+        list.replace(Appointment::new(
             String::from("Appointment to override existent"),
             AppointmentTime::new(15, 46).unwrap(),
         ))
         .unwrap();
 
         assert_eq!(
-            "15:46 Appointment to override existent\n20:45 Clean kitchen floor\n",
-            fs::read_to_string(&path).expect("Failed to read file content")
+            vec![
+                "15:46 Appointment to override existent",
+                "20:45 Clean kitchen floor"
+            ],
+            read_from_path(&path)
         );
     }
 
@@ -600,14 +1472,22 @@ mod tests {
         assert!(path.exists());
         let reference_time = AppointmentTime::new(22, 4).unwrap();
         let mut list = AppointmentList::new(&reference_time, &path);
-        list.clear().expect("Failed to remove file");
-        assert!(!path.exists());
+        list.clear().expect("Failed to clear file");
+        assert!(AppointmentList::new(&reference_time, &path).no_appointments());
+        list.add(Appointment::new(
+            String::from("Another appointment"),
+            AppointmentTime::new(23, 0).unwrap(),
+        ))
+        .unwrap();
+        assert_eq!(2, list.appointments()[0].id());
     }
 
     #[test]
-    fn past_appointments_should_be_strikethrough() {
+    #[allow(clippy::to_string_in_format_args)]
+    fn past_appointments_should_be_dimmed() {
+        colored::control::set_override(true);
         let reference_time = AppointmentTime::new(14, 38).unwrap();
-        let path = generate_path_for_test("past_appointments_should_be_strikethrough");
+        let path = generate_path_for_test("past_appointments_should_be_dimmed");
         let mut list = AppointmentList::new(&reference_time, &path);
 
         list.add(Appointment::new(
@@ -649,13 +1529,9 @@ mod tests {
         assert_eq!(
             format!(
                 "{}\n{}\n{}\n{}\n{}\n{}",
-                "[09:47] Make restaurant reservations"
-                    .strikethrough()
-                    .to_string(),
-                "[12:51] Backup the vacation pictures"
-                    .strikethrough()
-                    .to_string(),
-                "[14:38] Play fifa".strikethrough().to_string(),
+                "[09:47] Make restaurant reservations".dimmed().to_string(),
+                "[12:51] Backup the vacation pictures".dimmed().to_string(),
+                "[14:38] Play fifa".dimmed().to_string(),
                 "[14:39] Rest",
                 "[16:08] Buy new sunglasses",
                 "[19:17] Update my professional portfolio"
@@ -684,6 +1560,327 @@ mod tests {
         );
     }
 
+    #[test]
+    fn legacy_files_should_be_migrated_on_write() {
+        let path = generate_path_for_test("legacy_files_should_be_migrated_on_write");
+        write_to_path(&path, b"15:30 Go to the bank\n09:15 Wash the dishes\n");
+
+        let reference_time = AppointmentTime::new(6, 43).unwrap();
+        let list = AppointmentList::new(&reference_time, &path);
+        assert!(list.is_legacy_format());
+        assert_eq!(
+            list.appointments()
+                .iter()
+                .map(|a| (a.id(), a.to_string()))
+                .collect::<Vec<(u32, String)>>(),
+            vec![
+                (2, String::from("09:15 Wash the dishes")),
+                (1, String::from("15:30 Go to the bank"))
+            ]
+        );
+
+        list.write().unwrap();
+        let list = AppointmentList::new(&reference_time, &path);
+        assert!(!list.is_legacy_format());
+        assert_eq!(
+            vec!["09:15 Wash the dishes", "15:30 Go to the bank"],
+            read_from_path(&path)
+        );
+    }
+
+    #[test]
+    fn added_appointments_should_get_ids_and_timestamps() {
+        let path = generate_path_for_test("added_appointments_should_get_ids_and_timestamps");
+        let reference_time = AppointmentTime::new(6, 43).unwrap();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        list.add(Appointment::new(
+            String::from("Water the plants"),
+            AppointmentTime::new(8, 0).unwrap(),
+        ))
+        .unwrap();
+        list.add(Appointment::new(
+            String::from("Feed the cat"),
+            AppointmentTime::new(7, 0).unwrap(),
+        ))
+        .unwrap();
+
+        let list = AppointmentList::new(&reference_time, &path);
+        let appointments = list.appointments();
+        assert_eq!(appointments[0].id(), 2);
+        assert_eq!(appointments[1].id(), 1);
+        assert!(appointments[0].metadata().created_at.is_some());
+        assert_eq!(
+            appointments[0].metadata().created_at,
+            appointments[0].metadata().updated_at
+        );
+    }
+
+    #[test]
+    fn filter_should_retain_by_tag() {
+        let path = generate_path_for_test("filter_should_retain_by_tag");
+        let reference_time = AppointmentTime::new(8, 0).unwrap();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        for appointment in [
+            "09:00 Check the alerts #oncall",
+            "10:00 Review #work",
+            "11:00 Rotate the pager #oncall #work",
+        ] {
+            list.add(Appointment::from(appointment).unwrap()).unwrap();
+        }
+        list.filter(FilterOption::ByTag(String::from("work")));
+        list.filter(FilterOption::ExcludingTag(String::from("#OnCall")));
+        assert_eq!(
+            &vec![Appointment::from("10:00 Review #work").unwrap()],
+            list.appointments()
+        );
+    }
+
+    #[test]
+    fn filter_should_keep_appointments_in_progress() {
+        let path = generate_path_for_test("filter_should_keep_appointments_in_progress");
+        let reference_time = AppointmentTime::new(15, 0).unwrap();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        list.add(
+            Appointment::new(
+                String::from("Workshop"),
+                AppointmentTime::new(14, 0).unwrap(),
+            )
+            .with_duration(Duration::from_minutes(120)),
+        )
+        .unwrap();
+        list.add(Appointment::new(
+            String::from("Lunch"),
+            AppointmentTime::new(12, 0).unwrap(),
+        ))
+        .unwrap();
+        list.filter(FilterOption::ByReferenceTime);
+        assert_eq!(
+            &vec![Appointment::new(
+                String::from("Workshop"),
+                AppointmentTime::new(14, 0).unwrap()
+            )
+            .with_duration(Duration::from_minutes(120))],
+            list.appointments()
+        );
+        assert_eq!(
+            vec!["12:00 Lunch", "14:00-16:00 Workshop"],
+            read_from_path(&path)
+        );
+    }
+
+    #[test]
+    fn edit_should_change_past_appointments_and_occurrences() {
+        let path = generate_path_for_test("edit_should_change_past_appointments_and_occurrences");
+        let reference_time = AppointmentTime::new(15, 0).unwrap();
+        let mut list =
+            AppointmentList::new(&reference_time, &path).with_occurrences(vec![Appointment::new(
+                String::from("Lunch"),
+                AppointmentTime::new(12, 30).unwrap(),
+            )
+            .with_recurrence_id(1)]);
+        list.add(Appointment::new(
+            String::from("Standup"),
+            AppointmentTime::new(9, 0).unwrap(),
+        ))
+        .unwrap();
+        list.edit(
+            AppointmentTime::new(9, 0).unwrap(),
+            AppointmentChanges {
+                time: Some(AppointmentTime::new(9, 15).unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        list.edit(
+            AppointmentTime::new(12, 30).unwrap(),
+            AppointmentChanges {
+                description: Some(String::from("Lunch with Ana")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["09:15 Standup", "12:30 Lunch with Ana"],
+            read_from_path(&path)
+        );
+        assert_eq!(2, list.appointments().len());
+        assert_eq!(
+            list.edit_by_id(7, AppointmentChanges::default()),
+            Err(String::from("There is no appointment with this id."))
+        );
+    }
+
+    #[test]
+    fn edit_occurrence_should_keep_what_is_not_changed() {
+        let path = generate_path_for_test("edit_occurrence_should_keep_what_is_not_changed");
+        let reference_time = AppointmentTime::new(8, 0).unwrap();
+        let mut list =
+            AppointmentList::new(&reference_time, &path).with_occurrences(vec![Appointment::new(
+                String::from("Gym"),
+                AppointmentTime::new(18, 0).unwrap(),
+            )
+            .with_duration(Duration::from_minutes(60))
+            .with_tag("health")
+            .unwrap()
+            .with_recurrence_id(3)]);
+        list.edit_occurrence(
+            3,
+            AppointmentChanges {
+                time: Some(AppointmentTime::new(19, 0).unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(vec!["19:00-20:00 Gym #health"], read_from_path(&path));
+        assert_eq!(None, list.appointments()[0].recurrence_id());
+        assert_eq!(
+            list.edit_occurrence(3, AppointmentChanges::default()),
+            Err(String::from(
+                "The recurrence has no occurrence on the given date."
+            ))
+        );
+    }
+
+    #[test]
+    fn replace_all_should_assign_repeated_ids() {
+        let path = generate_path_for_test("replace_all_should_assign_repeated_ids");
+        let reference_time = AppointmentTime::new(15, 0).unwrap();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        let mut appointments = vec![];
+        for (hour, description) in [(8, "Gym"), (9, "Standup")] {
+            let mut appointment = Appointment::new(
+                String::from(description),
+                AppointmentTime::new(hour, 0).unwrap(),
+            );
+            appointment.metadata.id = 3;
+            appointments.push(appointment);
+        }
+        list.replace_all(appointments).unwrap();
+        let ids: Vec<u32> = AppointmentList::new(&reference_time, &path)
+            .appointments()
+            .iter()
+            .map(|a| a.id())
+            .collect();
+        assert_eq!(vec![3, 4], ids);
+    }
+
+    #[test]
+    fn add_all_should_assign_ids_and_replace() {
+        let path = generate_path_for_test("add_all_should_assign_ids_and_replace");
+        let reference_time = AppointmentTime::new(6, 0).unwrap();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        list.add(Appointment::new(
+            String::from("Standup"),
+            AppointmentTime::new(10, 0).unwrap(),
+        ))
+        .unwrap();
+        list.add_all(
+            vec![
+                Appointment::new(
+                    String::from("Planning"),
+                    AppointmentTime::new(10, 0).unwrap(),
+                ),
+                Appointment::new(String::from("Lunch"), AppointmentTime::new(12, 0).unwrap()),
+            ],
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(vec!["10:00 Planning", "12:00 Lunch"], read_from_path(&path));
+        let ids: Vec<u32> = list.appointments().iter().map(|a| a.id()).collect();
+        assert_eq!(vec![2, 3], ids);
+    }
+
+    #[test]
+    fn invalid_lines_should_be_kept_until_quarantined() {
+        let path = generate_path_for_test("invalid_lines_should_be_kept_until_quarantined");
+        let quarantine_path = storage::quarantine_path(&path);
+        if quarantine_path.exists() {
+            fs::remove_file(&quarantine_path).expect("Failed to clean test file");
+        }
+        write_to_path(
+            &path,
+            b"{\"version\":1}\n{\"id\":1,\"time\":\"09:00\",\"description\":\"Standup\"}\n{\"id\":2,\"time\":\"25:00\",\"description\":\"Late\"}\n",
+        );
+        let reference_time = AppointmentTime::new(6, 0).unwrap();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        assert_eq!(3, list.invalid_lines()[0].number);
+
+        list.add(Appointment::new(
+            String::from("Lunch"),
+            AppointmentTime::new(12, 0).unwrap(),
+        ))
+        .unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.ends_with("{\"id\":2,\"time\":\"25:00\",\"description\":\"Late\"}\n"));
+
+        assert_eq!(1, list.quarantine().unwrap());
+        assert!(AppointmentList::new(&reference_time, &path)
+            .invalid_lines()
+            .is_empty());
+        assert_eq!(
+            "{\"id\":2,\"time\":\"25:00\",\"description\":\"Late\"}\n",
+            fs::read_to_string(&quarantine_path).unwrap()
+        );
+    }
+
+    #[test]
+    fn concurrent_adds_should_all_be_kept() {
+        let path = generate_path_for_test("concurrent_adds_should_all_be_kept");
+        std::thread::scope(|scope| {
+            for hour in 0..20 {
+                let path = &path;
+                scope.spawn(move || {
+                    let reference_time = AppointmentTime::new(0, 0).unwrap();
+                    let mut list = AppointmentList::new(&reference_time, path);
+                    list.add(Appointment::new(
+                        format!("Appointment {hour}"),
+                        AppointmentTime::new(hour, 30).unwrap(),
+                    ))
+                    .unwrap();
+                });
+            }
+        });
+
+        let reference_time = AppointmentTime::new(0, 0).unwrap();
+        let list = AppointmentList::new(&reference_time, &path);
+        assert_eq!(20, list.appointments().len());
+        let mut ids: Vec<u32> = list.appointments().iter().map(|a| a.id()).collect();
+        ids.sort();
+        assert_eq!((1..=20).collect::<Vec<u32>>(), ids);
+    }
+
+    #[test]
+    fn appointments_at_the_same_time_should_be_kept() {
+        let path = generate_path_for_test("appointments_at_the_same_time_should_be_kept");
+        let reference_time = AppointmentTime::new(6, 0).unwrap();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        list.add(Appointment::new(
+            String::from("Standup"),
+            AppointmentTime::new(10, 0).unwrap(),
+        ))
+        .unwrap();
+        list.add(Appointment::new(
+            String::from("Call Bob"),
+            AppointmentTime::new(10, 0).unwrap(),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            vec!["10:00 Call Bob", "10:00 Standup"],
+            read_from_path(&path)
+        );
+        assert_eq!(
+            list.remove(AppointmentTime::new(10, 0).unwrap())
+                .unwrap_err(),
+            "There are 2 appointments at 10:00 (#2 Call Bob, #1 Standup). Choose one by its id."
+        );
+
+        list.remove_by_id(2).unwrap();
+        list.remove(AppointmentTime::new(10, 0).unwrap()).unwrap();
+        assert!(list.no_appointments());
+    }
+
     #[test]
     fn remove_should_be_ok() {
         let reference_time = AppointmentTime::new(22, 7).unwrap();
@@ -0,0 +1,443 @@
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use super::{
+    duration::Duration,
+    helper,
+    time::{AppointmentDateTime, AppointmentTime},
+    zone::Zone,
+    Appointment,
+};
+
+/// Error returned for expressions that are not times at all
+const INVALID_TIME: &str = "Invalid string for appointment time";
+
+/// Describe a time written by the user, either at a time of the day or relative to now
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeExpression {
+    /// A time of the day (`14:30`, `9am`, `2:30pm`, `noon`)
+    At(AppointmentTime),
+    /// A duration from now (`in 45 minutes`, `in 2h`)
+    In(Duration),
+}
+
+impl TimeExpression {
+    /// Parses a time expression. Besides the `HH:MM` format, it accepts 12-hour times (`9am`,
+    /// `2:30 pm`, `12:15 a.m.`), `noon`, `midnight` and relative times (`in 45 minutes`,
+    /// `in 2h`, `in 1h30m`, `in an hour`). Hours from 1 to 12 without minutes, am or pm are
+    /// ambiguous and refused
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, Duration, TimeExpression};
+    ///
+    /// assert_eq!(Ok(TimeExpression::At(AppointmentTime::new(14, 30).unwrap())), TimeExpression::parse("2:30pm"));
+    /// assert_eq!(Ok(TimeExpression::At(AppointmentTime::new(12, 0).unwrap())), TimeExpression::parse("noon"));
+    /// assert_eq!(Ok(TimeExpression::In(Duration::from_minutes(45))), TimeExpression::parse("in 45 minutes"));
+    /// assert_eq!(
+    ///     Err(String::from("Ambiguous time \"9\", use \"9am\", \"9pm\" or \"09:00\"")),
+    ///     TimeExpression::parse("9")
+    /// );
+    /// ```
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let text = expression.trim().to_lowercase();
+        match text.as_str() {
+            "noon" | "midday" => return at(12, 0),
+            "midnight" => return at(0, 0),
+            _ => (),
+        }
+        if let Some(relative) = text.strip_prefix("in ") {
+            return parse_relative(relative.trim(), expression);
+        }
+        let compact = text.replace(['.', ' '], "");
+        if let Some(clock) = compact.strip_suffix("am") {
+            return parse_twelve_hour(clock, false, expression);
+        }
+        if let Some(clock) = compact.strip_suffix("pm") {
+            return parse_twelve_hour(clock, true, expression);
+        }
+        if text.contains(':') {
+            return AppointmentTime::from(&text).map(TimeExpression::At);
+        }
+        match text.parse::<i32>() {
+            Ok(hour) if (1..=12).contains(&hour) => Err(format!(
+                "Ambiguous time \"{}\", use \"{hour}am\", \"{hour}pm\" or \"{:02}:00\"",
+                expression.trim(),
+                if hour < 8 { hour + 12 } else { hour }
+            )),
+            Ok(hour) if hour == 0 || (13..=23).contains(&hour) => at(hour, 0),
+            _ => Err(String::from(INVALID_TIME)),
+        }
+    }
+
+    /// Checks if the expression is relative to now
+    pub fn is_relative(&self) -> bool {
+        matches!(self, TimeExpression::In(..))
+    }
+
+    /// Returns the time zone the expression is written in, given the one asked for and the
+    /// default one of the calendar. Relative times are counted in the current time zone
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, Duration, TimeExpression, Zone};
+    ///
+    /// let berlin = Zone::from("Europe/Berlin").unwrap();
+    /// let at = TimeExpression::At(AppointmentTime::new(9, 0).unwrap());
+    /// assert_eq!(Ok(Some(berlin)), at.zone(Some(berlin), None));
+    /// assert_eq!(Ok(None), TimeExpression::In(Duration::from_minutes(45)).zone(None, None));
+    /// assert!(TimeExpression::In(Duration::from_minutes(45)).zone(Some(berlin), None).is_err());
+    /// ```
+    pub fn zone(&self, zone: Option<Zone>, default: Option<Zone>) -> Result<Option<Zone>, String> {
+        match self {
+            TimeExpression::In(..) if zone.is_some() => Err(String::from(
+                "Relative times cannot be combined with a time zone",
+            )),
+            TimeExpression::In(..) if default.is_some_and(|zone| zone != Zone::current()) => {
+                Ok(Some(Zone::current()))
+            }
+            _ => Ok(zone),
+        }
+    }
+
+    /// Returns the date and time the expression refers to. Times of the day are taken on the
+    /// given date, relative times are counted from now and may fall on another date
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, Duration, TimeExpression};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let now = date.and_hms_opt(23, 30, 0).unwrap();
+    /// assert_eq!(
+    ///     (NaiveDate::from_ymd_opt(2024, 6, 11).unwrap(), AppointmentTime::new(0, 15).unwrap()),
+    ///     TimeExpression::In(Duration::from_minutes(45)).resolve(date, now)
+    /// );
+    /// ```
+    pub fn resolve(&self, date: NaiveDate, now: NaiveDateTime) -> (NaiveDate, AppointmentTime) {
+        match self {
+            TimeExpression::At(time) => (date, time.clone()),
+            TimeExpression::In(duration) => {
+                let at = AppointmentDateTime::from_naive(now) + *duration;
+                (at.date(), at.time())
+            }
+        }
+    }
+}
+
+impl FromStr for TimeExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TimeExpression::parse(s)
+    }
+}
+
+/// Parses an appointment written as `[date] time[-end] description`, like
+/// `tomorrow 3pm call Bob #work`, `09:00-10:30 Workshop` or `in 2h stretch`. The date accepts the
+/// expressions of `helper::parse_date` and defaults to the given one, the time accepts the
+/// expressions of `TimeExpression::parse`. Relative dates and times are counted from now
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::natural;
+/// use chrono::NaiveDate;
+///
+/// let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let now = today.and_hms_opt(9, 0, 0).unwrap();
+/// let (date, appointment) = natural::parse_appointment("tomorrow 3pm call Bob", today, now).unwrap();
+/// assert_eq!(NaiveDate::from_ymd_opt(2024, 6, 11).unwrap(), date);
+/// assert_eq!("15:00 call Bob", appointment.to_string());
+/// ```
+pub fn parse_appointment(
+    line: &str,
+    date: NaiveDate,
+    now: NaiveDateTime,
+) -> Result<(NaiveDate, Appointment), String> {
+    parse_appointment_in_zone(line, date, now, None, None)
+}
+
+/// Parses an appointment like `parse_appointment`, written in the given time zone or in the
+/// default one of the calendar, see `TimeExpression::zone`
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{natural, Zone};
+/// use chrono::NaiveDate;
+///
+/// let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let now = today.and_hms_opt(9, 0, 0).unwrap();
+/// let berlin = Zone::from("Europe/Berlin").unwrap();
+/// let (_, appointment) = natural::parse_appointment_in_zone("3pm call Bob", today, now, Some(berlin), None).unwrap();
+/// assert_eq!(Some(berlin), appointment.zone());
+/// ```
+pub fn parse_appointment_in_zone(
+    line: &str,
+    date: NaiveDate,
+    now: NaiveDateTime,
+    zone: Option<Zone>,
+    default_zone: Option<Zone>,
+) -> Result<(NaiveDate, Appointment), String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut index = 0;
+    let mut given_date = None;
+    for count in [2, 1] {
+        if words.len() <= count {
+            continue;
+        }
+        if let Ok(parsed) = helper::parse_date(&words[..count].join(" "), now.date()) {
+            given_date = Some(parsed);
+            index = count;
+            break;
+        }
+    }
+
+    let mut time = None;
+    let mut first_error = None;
+    for count in [3, 2, 1] {
+        if words.len() < index + count {
+            continue;
+        }
+        match parse_time_range(&words[index..index + count].join(" ")) {
+            Ok(parsed) => {
+                time = Some(parsed);
+                index += count;
+                break;
+            }
+            Err(error) => first_error = Some(error),
+        }
+    }
+    let (time, end_time) = match time {
+        Some(time) => time,
+        None => return Err(first_error.unwrap_or(String::from(INVALID_TIME))),
+    };
+    if given_date.is_some() && time.is_relative() {
+        return Err(String::from(
+            "Relative times cannot be combined with a date",
+        ));
+    }
+
+    let zone = time.zone(zone, default_zone)?;
+
+    let description = words[index..].join(" ");
+    if description.is_empty() {
+        return Err(String::from("The appointment should have a description"));
+    }
+    let (description, tags) = helper::split_tags(&description);
+    let (date, time) = time.resolve(given_date.unwrap_or(date), now);
+    let mut appointment = Appointment::new(description, time).with_tags(tags);
+    if let Some(zone) = zone {
+        appointment = appointment.with_zone(zone);
+    }
+    match end_time {
+        Some(end_time) => Ok((date, appointment.until(end_time)?)),
+        None => Ok((date, appointment)),
+    }
+}
+
+/// Parses a time expression or a range of times of the day (`9am-10:30am`, `14:00-15:00`)
+fn parse_time_range(text: &str) -> Result<(TimeExpression, Option<AppointmentTime>), String> {
+    if let Ok(time) = TimeExpression::parse(text) {
+        return Ok((time, None));
+    }
+    if let Some((start, end)) = text.split_once('-') {
+        if let (Ok(TimeExpression::At(start)), Ok(TimeExpression::At(end))) =
+            (TimeExpression::parse(start), TimeExpression::parse(end))
+        {
+            return Ok((TimeExpression::At(start), Some(end)));
+        }
+    }
+    TimeExpression::parse(text).map(|time| (time, None))
+}
+
+/// Returns a time of the day expression
+fn at(hour: i32, minutes: i32) -> Result<TimeExpression, String> {
+    AppointmentTime::new(hour, minutes).map(TimeExpression::At)
+}
+
+/// Parses `9`, `9:30` or `12:05` followed by am or pm
+fn parse_twelve_hour(clock: &str, pm: bool, expression: &str) -> Result<TimeExpression, String> {
+    let (hour, minutes) = match clock.split_once(':') {
+        Some((hour, minutes)) => (hour, minutes),
+        None => (clock, "0"),
+    };
+    let (hour, minutes): (i32, i32) = match (hour.parse(), minutes.parse()) {
+        (Ok(hour), Ok(minutes)) => (hour, minutes),
+        _ => return Err(String::from(INVALID_TIME)),
+    };
+    if !(1..=12).contains(&hour) {
+        return Err(format!(
+            "Invalid time \"{}\", hours should be between 1 and 12 with am or pm",
+            expression.trim()
+        ));
+    }
+    at(hour % 12 + if pm { 12 } else { 0 }, minutes)
+}
+
+/// Parses the part after `in` of a relative time, which needs a unit
+fn parse_relative(relative: &str, expression: &str) -> Result<TimeExpression, String> {
+    let invalid = || {
+        format!(
+            "Invalid relative time \"{}\", use a unit like \"in 45m\" or \"in 2h\"",
+            expression.trim()
+        )
+    };
+    let mut compact = match relative {
+        "an hour" => String::from("1h"),
+        "a minute" => String::from("1m"),
+        "half an hour" => String::from("30m"),
+        relative => relative.replace(' ', ""),
+    };
+    for (word, unit) in [
+        ("hours", "h"),
+        ("hour", "h"),
+        ("hrs", "h"),
+        ("hr", "h"),
+        ("minutes", "m"),
+        ("minute", "m"),
+        ("mins", "m"),
+        ("min", "m"),
+    ] {
+        compact = compact.replace(word, unit);
+    }
+    if !compact.ends_with(['h', 'm']) {
+        return Err(invalid());
+    }
+    match Duration::from(&compact) {
+        Ok(duration) if duration.minutes() > 0 => Ok(TimeExpression::In(duration)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{parse_appointment, TimeExpression};
+    use crate::appointment::{duration::Duration, time::AppointmentTime};
+
+    fn at(hour: i32, minutes: i32) -> Result<TimeExpression, String> {
+        Ok(TimeExpression::At(
+            AppointmentTime::new(hour, minutes).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn parse_twelve_hour_times() {
+        assert_eq!(TimeExpression::parse("9am"), at(9, 0));
+        assert_eq!(TimeExpression::parse("9 PM"), at(21, 0));
+        assert_eq!(TimeExpression::parse("12am"), at(0, 0));
+        assert_eq!(TimeExpression::parse("12:15 p.m."), at(12, 15));
+        assert_eq!(
+            TimeExpression::parse("13pm"),
+            Err(String::from(
+                "Invalid time \"13pm\", hours should be between 1 and 12 with am or pm"
+            ))
+        );
+        assert_eq!(
+            TimeExpression::parse("2:75pm"),
+            Err(String::from("Minutes should be between 0 and 59"))
+        );
+    }
+
+    #[test]
+    fn parse_twenty_four_hour_times() {
+        assert_eq!(TimeExpression::parse("14:05"), at(14, 5));
+        assert_eq!(TimeExpression::parse("18"), at(18, 0));
+        assert_eq!(TimeExpression::parse("midnight"), at(0, 0));
+        assert_eq!(
+            TimeExpression::parse("3"),
+            Err(String::from(
+                "Ambiguous time \"3\", use \"3am\", \"3pm\" or \"15:00\""
+            ))
+        );
+        assert_eq!(
+            TimeExpression::parse("24:10"),
+            Err(String::from("Hour should be between 0 and 23"))
+        );
+        assert_eq!(
+            TimeExpression::parse("soon"),
+            Err(String::from("Invalid string for appointment time"))
+        );
+    }
+
+    #[test]
+    fn parse_relative_times() {
+        assert_eq!(
+            TimeExpression::parse("in 2h"),
+            Ok(TimeExpression::In(Duration::from_minutes(120)))
+        );
+        assert_eq!(
+            TimeExpression::parse("in 1 hour 30 minutes"),
+            Ok(TimeExpression::In(Duration::from_minutes(90)))
+        );
+        assert_eq!(
+            TimeExpression::parse("in an hour"),
+            Ok(TimeExpression::In(Duration::from_minutes(60)))
+        );
+        assert_eq!(
+            TimeExpression::parse("in 45"),
+            Err(String::from(
+                "Invalid relative time \"in 45\", use a unit like \"in 45m\" or \"in 2h\""
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_appointment_lines() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let now = today.and_hms_opt(9, 0, 0).unwrap();
+        let parse = |line| {
+            parse_appointment(line, today, now)
+                .map(|(date, appointment)| (date.to_string(), appointment.to_string()))
+        };
+        assert_eq!(
+            parse("20:46 Finish final assignment"),
+            Ok((
+                String::from("2024-06-10"),
+                String::from("20:46 Finish final assignment")
+            ))
+        );
+        assert_eq!(
+            parse("next monday 2:30 pm Review #work"),
+            Ok((
+                String::from("2024-06-17"),
+                String::from("14:30 Review #work")
+            ))
+        );
+        assert_eq!(
+            parse("9am-10:30am Workshop"),
+            Ok((
+                String::from("2024-06-10"),
+                String::from("09:00-10:30 Workshop")
+            ))
+        );
+        assert_eq!(
+            parse("in 45 minutes stretch"),
+            Ok((String::from("2024-06-10"), String::from("09:45 stretch")))
+        );
+        assert_eq!(
+            parse("tomorrow in 2h call"),
+            Err(String::from(
+                "Relative times cannot be combined with a date"
+            ))
+        );
+        assert_eq!(
+            parse("tomorrow 3 call Bob"),
+            Err(String::from(
+                "Ambiguous time \"3\", use \"3am\", \"3pm\" or \"15:00\""
+            ))
+        );
+        assert_eq!(
+            parse("noon"),
+            Err(String::from("The appointment should have a description"))
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use super::{duration::Duration, time::AppointmentDateTime, Appointment};
+
+/// Template used for the next appointment when none is given
+pub const DEFAULT_TEMPLATE: &str = "{time} {desc} (in {mins}m)";
+
+/// Describe how close the next appointment is, each case exits `next` with its own code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Upcoming {
+    /// The next appointment starts after the soon duration
+    Later,
+    /// There are no pending appointments left
+    Nothing,
+    /// The next appointment starts within the soon duration
+    Soon,
+    /// An appointment is in progress or starts at the reference time
+    Now,
+}
+
+impl Upcoming {
+    /// Classifies the next appointment relative to the current moment. Appointments without a
+    /// date are taken on the date of the moment
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{next::Upcoming, Appointment, AppointmentDateTime, AppointmentTime, Duration};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let at = |hour, minutes| AppointmentDateTime::new(date, &AppointmentTime::new(hour, minutes).unwrap());
+    /// let appointment = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap());
+    /// let soon = Duration::from_minutes(5);
+    /// assert_eq!(Upcoming::Later, Upcoming::of(Some(&appointment), &at(9, 0), soon));
+    /// assert_eq!(Upcoming::Soon, Upcoming::of(Some(&appointment), &at(9, 26), soon));
+    /// assert_eq!(Upcoming::Now, Upcoming::of(Some(&appointment), &at(9, 30), soon));
+    /// assert_eq!(Upcoming::Nothing, Upcoming::of(None, &at(9, 30), soon));
+    /// ```
+    pub fn of(
+        appointment: Option<&Appointment>,
+        now: &AppointmentDateTime,
+        soon: Duration,
+    ) -> Self {
+        match appointment {
+            None => Upcoming::Nothing,
+            Some(appointment) => match minutes_until(appointment, now) {
+                0 => Upcoming::Now,
+                minutes if minutes * 60 <= soon.seconds() => Upcoming::Soon,
+                _ => Upcoming::Later,
+            },
+        }
+    }
+
+    /// Returns the exit code of the case. `1` and `2` are left for errors
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Upcoming::Later => 0,
+            Upcoming::Nothing => 3,
+            Upcoming::Soon => 4,
+            Upcoming::Now => 5,
+        }
+    }
+}
+
+/// Describe a piece of a template, either text printed as it is or a placeholder
+enum Part<'a> {
+    Text(&'a str),
+    Time,
+    End,
+    Description,
+    Minutes,
+    Tags,
+}
+
+/// Splits a template into its parts, failing on unknown or unclosed placeholders
+fn parse(template: &str) -> Result<Vec<Part<'_>>, String> {
+    let mut parts = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            parts.push(Part::Text(&rest[..start]));
+        }
+        let Some(length) = rest[start..].find('}') else {
+            return Err(format!(
+                "Unclosed placeholder in the template \"{template}\""
+            ));
+        };
+        parts.push(match &rest[start + 1..start + length] {
+            "time" => Part::Time,
+            "end" => Part::End,
+            "desc" => Part::Description,
+            "mins" => Part::Minutes,
+            "tags" => Part::Tags,
+            name => {
+                return Err(format!(
+                    "Unknown placeholder {{{name}}}, expected {{time}}, {{end}}, {{desc}}, {{mins}} or {{tags}}"
+                ))
+            }
+        });
+        rest = &rest[start + length + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+/// Checks if a template can be used with `render`
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::next;
+///
+/// assert!(next::validate("{time} {desc}").is_ok());
+/// assert!(next::validate("{when}").is_err());
+/// ```
+pub fn validate(template: &str) -> Result<(), String> {
+    parse(template).map(|_| ())
+}
+
+/// Fills a template with the fields of an appointment. `{time}` and `{end}` are the start and end
+/// times, `{desc}` the description, `{mins}` the minutes until the start, `0` when in progress,
+/// and `{tags}` the tags with their leading `#`. The minutes count from the current moment, so they
+/// include the hours until midnight for an appointment of the next day
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{next, Appointment, AppointmentDateTime, AppointmentTime};
+/// use chrono::NaiveDate;
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let now = AppointmentDateTime::new(date, &AppointmentTime::new(9, 18).unwrap());
+/// let appointment = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap());
+/// assert_eq!(Ok(String::from("09:30 Standup (in 12m)")), next::render(next::DEFAULT_TEMPLATE, &appointment, &now));
+/// let appointment = appointment.with_date(date.succ_opt().unwrap());
+/// assert_eq!(Ok(String::from("09:30 Standup (in 1452m)")), next::render(next::DEFAULT_TEMPLATE, &appointment, &now));
+/// ```
+pub fn render(
+    template: &str,
+    appointment: &Appointment,
+    now: &AppointmentDateTime,
+) -> Result<String, String> {
+    Ok(parse(template)?
+        .into_iter()
+        .map(|part| match part {
+            Part::Text(text) => text.to_string(),
+            Part::Time => appointment.time().to_string(),
+            Part::End => appointment.end_time().to_string(),
+            Part::Description => appointment.description().to_string(),
+            Part::Minutes => minutes_until(appointment, now).to_string(),
+            Part::Tags => appointment.tags_suffix().trim_start().to_string(),
+        })
+        .collect())
+}
+
+/// Returns the minutes from the current moment until the appointment starts, `0` when it
+/// already started. A part of a minute left counts as a whole one, so an appointment is only
+/// `Now` once it started
+fn minutes_until(appointment: &Appointment, now: &AppointmentDateTime) -> i32 {
+    ((appointment.starts_at(now.date()) - *now).seconds() + 59)
+        .div_euclid(60)
+        .max(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeDelta};
+
+    use super::{render, validate, Upcoming};
+    use crate::appointment::{
+        duration::Duration, time::AppointmentDateTime, Appointment, AppointmentTime,
+    };
+
+    fn time(hour: i32, minutes: i32) -> AppointmentTime {
+        AppointmentTime::new(hour, minutes).unwrap()
+    }
+
+    fn at(hour: i32, minutes: i32) -> AppointmentDateTime {
+        AppointmentDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            &time(hour, minutes),
+        )
+    }
+
+    #[test]
+    fn render_every_placeholder() {
+        let appointment = Appointment::new(String::from("Workshop"), time(14, 0))
+            .with_duration(Duration::from_minutes(90))
+            .with_tag("work")
+            .unwrap();
+        assert_eq!(
+            Ok(String::from("14:00-15:30 Workshop #work in 45")),
+            render(
+                "{time}-{end} {desc} {tags} in {mins}",
+                &appointment,
+                &at(13, 15)
+            )
+        );
+        assert_eq!(
+            Ok(String::from("Workshop 0")),
+            render("{desc} {mins}", &appointment, &at(14, 30))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_templates() {
+        assert_eq!(
+            Err(String::from(
+                "Unknown placeholder {date}, expected {time}, {end}, {desc}, {mins} or {tags}"
+            )),
+            validate("{date} {desc}")
+        );
+        assert_eq!(
+            Err(String::from(
+                "Unclosed placeholder in the template \"{time} {desc\""
+            )),
+            validate("{time} {desc")
+        );
+        assert!(validate("no placeholders").is_ok());
+    }
+
+    #[test]
+    fn appointments_in_progress_are_now() {
+        let appointment = Appointment::new(String::from("Workshop"), time(14, 0))
+            .with_duration(Duration::from_minutes(90));
+        let soon = Duration::from_minutes(10);
+        assert_eq!(
+            Upcoming::Now,
+            Upcoming::of(Some(&appointment), &at(15, 0), soon)
+        );
+        assert_eq!(
+            Upcoming::Soon,
+            Upcoming::of(Some(&appointment), &at(13, 50), soon)
+        );
+        assert_eq!(
+            Upcoming::Later,
+            Upcoming::of(Some(&appointment), &at(13, 49), soon)
+        );
+    }
+
+    #[test]
+    fn appointments_are_now_only_once_started() {
+        let appointment = Appointment::new(String::from("Standup"), time(9, 30));
+        let now = AppointmentDateTime::from_naive(at(9, 29).naive() + TimeDelta::seconds(30));
+        let soon = Duration::from_minutes(5);
+        assert_eq!(Upcoming::Soon, Upcoming::of(Some(&appointment), &now, soon));
+        assert_eq!(Ok(String::from("1")), render("{mins}", &appointment, &now));
+    }
+
+    #[test]
+    fn appointments_of_the_next_day_count_the_minutes_until_midnight() {
+        let tomorrow = NaiveDate::from_ymd_opt(2024, 6, 11).unwrap();
+        let appointment = Appointment::new(String::from("Deploy"), time(0, 5)).with_date(tomorrow);
+        let soon = Duration::from_minutes(10);
+        assert_eq!(
+            Upcoming::Soon,
+            Upcoming::of(Some(&appointment), &at(23, 58), soon)
+        );
+        assert_eq!(
+            Upcoming::Later,
+            Upcoming::of(Some(&appointment), &at(0, 0), soon)
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+
+use super::{Appointment, Status};
+
+/// Describe the formats the appointments can be printed in for other programs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `[HH:MM] description` lines, like the default output but without colors
+    Plain,
+    /// An array of objects with the fields described in `Row`
+    Json,
+    /// A header line followed by one line per appointment, fields quoted when needed
+    Csv,
+    /// A header line followed by one line per appointment, tabs and line breaks replaced by spaces
+    Tsv,
+}
+
+impl OutputFormat {
+    /// Parses an output format from its name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::OutputFormat;
+    ///
+    /// assert_eq!(Ok(OutputFormat::Json), OutputFormat::from("json"));
+    /// assert!(OutputFormat::from("xml").is_err());
+    /// ```
+    pub fn from(format: &str) -> Result<Self, String> {
+        match format.trim().to_lowercase().as_str() {
+            "plain" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(format!(
+                "Invalid format \"{format}\", expected json, csv, tsv or plain"
+            )),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OutputFormat::from(s)
+    }
+}
+
+/// Describe the fields printed for each appointment. The names and meanings are part of the
+/// output schema and should only be extended
+#[derive(Serialize)]
+struct Row<'a> {
+    /// Appointment id inside its day, `null` for occurrences of recurrences
+    id: Option<u32>,
+    /// Date in the `YYYY-MM-DD` format
+    date: String,
+    /// Start time in the `HH:MM` format
+    time: String,
+    /// End time in the `HH:MM` format, `null` when there is no duration
+    end_time: Option<String>,
+    description: &'a str,
+    /// One of `pending`, `done`, `skipped` or `cancelled`
+    status: String,
+    /// If the appointment already started and is not in progress
+    passed: bool,
+    /// Minutes from now until the start, negative when it already started
+    minutes_until: i64,
+    /// Tags without the leading `#`, separated by spaces in CSV and TSV
+    tags: &'a [String],
+}
+
+const HEADER: [&str; 9] = [
+    "id",
+    "date",
+    "time",
+    "end_time",
+    "description",
+    "status",
+    "passed",
+    "minutes_until",
+    "tags",
+];
+
+/// Renders the appointments of a date in the given format, comparing them to the current date
+/// and time
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{output, Appointment, AppointmentTime, OutputFormat};
+/// use chrono::NaiveDate;
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let now = date.and_hms_opt(9, 0, 0).unwrap();
+/// let appointments = vec![Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap())];
+/// assert_eq!(
+///     "id,date,time,end_time,description,status,passed,minutes_until,tags\n0,2024-06-10,09:30,,Standup,pending,false,30,",
+///     output::render(&appointments, date, now, OutputFormat::Csv)
+/// );
+/// ```
+pub fn render(
+    appointments: &[Appointment],
+    date: NaiveDate,
+    now: NaiveDateTime,
+    format: OutputFormat,
+) -> String {
+    render_days(&[(date, appointments.to_vec())], now, format)
+}
+
+/// Renders the appointments of several dates in the given format, one after the other, like
+/// `render`
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{output, Appointment, AppointmentTime, OutputFormat};
+/// use chrono::NaiveDate;
+///
+/// let today = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let tomorrow = today.succ_opt().unwrap();
+/// let now = today.and_hms_opt(23, 50, 0).unwrap();
+/// let days = vec![
+///     (today, vec![Appointment::new(String::from("Deploy"), AppointmentTime::new(23, 55).unwrap())]),
+///     (tomorrow, vec![Appointment::new(String::from("Check"), AppointmentTime::new(0, 10).unwrap())]),
+/// ];
+/// assert_eq!(
+///     "id\tdate\ttime\tend_time\tdescription\tstatus\tpassed\tminutes_until\ttags\n0\t2024-06-10\t23:55\t\tDeploy\tpending\tfalse\t5\t\n0\t2024-06-11\t00:10\t\tCheck\tpending\tfalse\t20\t",
+///     output::render_days(&days, now, OutputFormat::Tsv)
+/// );
+/// ```
+pub fn render_days(
+    days: &[(NaiveDate, Vec<Appointment>)],
+    now: NaiveDateTime,
+    format: OutputFormat,
+) -> String {
+    let appointments = days
+        .iter()
+        .flat_map(|(date, appointments)| appointments.iter().map(move |a| (*date, a)));
+    let rows: Vec<Row> = appointments
+        .clone()
+        .map(|(date, appointment)| to_row(appointment, date, now))
+        .collect();
+    match format {
+        OutputFormat::Plain => appointments
+            .map(|(_, appointment)| plain_line(appointment))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        OutputFormat::Json => serde_json::to_string(&rows).expect("Rows should be serializable"),
+        OutputFormat::Csv => delimited(&rows, ',', csv_field),
+        OutputFormat::Tsv => delimited(&rows, '\t', tsv_field),
+    }
+}
+
+/// Returns the fields of an appointment, see `Row`
+fn to_row(appointment: &Appointment, date: NaiveDate, now: NaiveDateTime) -> Row<'_> {
+    let start = appointment.starts_at(date).naive();
+    let in_progress = start <= now && now < appointment.ends_at(date).naive();
+    Row {
+        id: match appointment.recurrence_id() {
+            Some(..) => None,
+            None => Some(appointment.id()),
+        },
+        date: start.date().format("%Y-%m-%d").to_string(),
+        time: appointment.time().to_string(),
+        end_time: appointment
+            .duration()
+            .map(|_| appointment.end_time().to_string()),
+        description: appointment.description(),
+        status: appointment.status().to_string(),
+        passed: start <= now && !in_progress,
+        minutes_until: (start - now).num_minutes(),
+        tags: appointment.tags(),
+    }
+}
+
+/// Returns the appointment as displayed by default, without colors
+fn plain_line(appointment: &Appointment) -> String {
+    let line = format!(
+        "[{}] {}{}",
+        appointment.time_range(),
+        appointment.description(),
+        appointment.tags_suffix()
+    );
+    match appointment.status() {
+        Status::Pending => line,
+        status => format!("{line} ({status})"),
+    }
+}
+
+/// Joins the header and the rows with the separator, escaping each field
+fn delimited(rows: &[Row], separator: char, escape: fn(&str) -> String) -> String {
+    let mut lines = vec![HEADER.join(&separator.to_string())];
+    for row in rows {
+        let fields = [
+            row.id.map(|id| id.to_string()).unwrap_or_default(),
+            row.date.clone(),
+            row.time.clone(),
+            row.end_time.clone().unwrap_or_default(),
+            row.description.to_string(),
+            row.status.clone(),
+            row.passed.to_string(),
+            row.minutes_until.to_string(),
+            row.tags.join(" "),
+        ];
+        lines.push(
+            fields
+                .iter()
+                .map(|field| escape(field))
+                .collect::<Vec<String>>()
+                .join(&separator.to_string()),
+        );
+    }
+    lines.join("\n")
+}
+
+/// Quotes a CSV field when it contains a comma, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Replaces the characters a TSV field cannot contain by spaces
+fn tsv_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{render, OutputFormat};
+    use crate::appointment::{duration::Duration, Appointment, AppointmentTime, Status};
+
+    fn appointments() -> Vec<Appointment> {
+        vec![
+            Appointment::new(
+                String::from("Review, then \"ship\""),
+                AppointmentTime::new(8, 0).unwrap(),
+            )
+            .with_status(Status::Done),
+            Appointment::new(
+                String::from("Workshop\tRust"),
+                AppointmentTime::new(9, 0).unwrap(),
+            )
+            .with_duration(Duration::from_minutes(90))
+            .with_tag("work")
+            .unwrap()
+            .with_tag("rust")
+            .unwrap(),
+            Appointment::new(String::from("Lunch"), AppointmentTime::new(12, 0).unwrap())
+                .with_recurrence_id(1),
+        ]
+    }
+
+    #[test]
+    fn render_json() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let now = date.and_hms_opt(10, 0, 0).unwrap();
+        assert_eq!(
+            render(&appointments(), date, now, OutputFormat::Json),
+            "[{\"id\":0,\"date\":\"2024-06-10\",\"time\":\"08:00\",\"end_time\":null,\"description\":\"Review, then \\\"ship\\\"\",\"status\":\"done\",\"passed\":true,\"minutes_until\":-120,\"tags\":[]},\
+            {\"id\":0,\"date\":\"2024-06-10\",\"time\":\"09:00\",\"end_time\":\"10:30\",\"description\":\"Workshop\\tRust\",\"status\":\"pending\",\"passed\":false,\"minutes_until\":-60,\"tags\":[\"work\",\"rust\"]},\
+            {\"id\":null,\"date\":\"2024-06-10\",\"time\":\"12:00\",\"end_time\":null,\"description\":\"Lunch\",\"status\":\"pending\",\"passed\":false,\"minutes_until\":120,\"tags\":[]}]"
+        );
+    }
+
+    #[test]
+    fn render_csv_and_tsv() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let now = NaiveDate::from_ymd_opt(2024, 6, 11)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert_eq!(
+            render(&appointments(), date, now, OutputFormat::Csv),
+            "id,date,time,end_time,description,status,passed,minutes_until,tags\n\
+            0,2024-06-10,08:00,,\"Review, then \"\"ship\"\"\",done,true,-960,\n\
+            0,2024-06-10,09:00,10:30,Workshop\tRust,pending,true,-900,work rust\n\
+            ,2024-06-10,12:00,,Lunch,pending,true,-720,"
+        );
+        assert_eq!(
+            render(&appointments()[1..2], date, now, OutputFormat::Tsv),
+            "id\tdate\ttime\tend_time\tdescription\tstatus\tpassed\tminutes_until\ttags\n\
+            0\t2024-06-10\t09:00\t10:30\tWorkshop Rust\tpending\ttrue\t-900\twork rust"
+        );
+    }
+
+    #[test]
+    fn render_plain() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let now = date.and_hms_opt(10, 0, 0).unwrap();
+        assert_eq!(
+            render(&appointments(), date, now, OutputFormat::Plain),
+            "[08:00] Review, then \"ship\" (done)\n[09:00-10:30] Workshop\tRust #work #rust\n[12:00] Lunch"
+        );
+    }
+}
@@ -0,0 +1,842 @@
+use core::fmt;
+use std::{fs, io, path::PathBuf, str};
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+use super::{
+    helper,
+    storage::{self, InvalidLine},
+    Appointment, AppointmentTime,
+};
+
+/// Describe the rules available for repeating an appointment
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecurrenceRule {
+    /// Every day
+    Daily,
+    /// From monday to friday
+    Weekdays,
+    /// On the given days of the week
+    Weekly(Vec<Weekday>),
+    /// On a given day of the month. Months without that day are skipped
+    MonthlyOnDay(u32),
+    /// On the nth weekday of the month, `-1` meaning the last one of the month
+    MonthlyOnWeekday(i8, Weekday),
+}
+
+impl RecurrenceRule {
+    /// Creates a `RecurrenceRule` from a string slice. Accepts expressions like `daily`,
+    /// `every weekday`, `every monday,friday`, `every 2nd tuesday`, `last friday of month`,
+    /// `first day of month` or `monthly on day 15`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::RecurrenceRule;
+    /// use chrono::Weekday;
+    ///
+    /// assert_eq!(RecurrenceRule::Weekdays, RecurrenceRule::from("every weekday").unwrap());
+    /// assert_eq!(RecurrenceRule::MonthlyOnWeekday(2, Weekday::Tue), RecurrenceRule::from("every 2nd Tuesday").unwrap());
+    /// assert_eq!(RecurrenceRule::MonthlyOnDay(1), RecurrenceRule::from("first day of month").unwrap());
+    /// ```
+    pub fn from(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim().to_lowercase();
+        let rule = rule.strip_prefix("every ").unwrap_or(&rule);
+        let monthly_rule = rule
+            .strip_suffix(" of the month")
+            .or(rule.strip_suffix(" of month"))
+            .or(rule.strip_prefix("monthly on "));
+        let invalid = || format!("Invalid recurrence rule \"{rule}\"");
+        match (rule, monthly_rule) {
+            ("day" | "daily", None) => Ok(Self::Daily),
+            ("weekday" | "weekdays", None) => Ok(Self::Weekdays),
+            (_, Some(monthly_rule)) => Self::from_monthly(monthly_rule).unwrap_or(Err(invalid())),
+            (rule, None) => Self::from_monthly(rule).unwrap_or_else(|| {
+                rule.strip_prefix("weekly on ")
+                    .unwrap_or(rule)
+                    .replace(" and ", ",")
+                    .split(',')
+                    .map(|w| w.trim().parse::<Weekday>())
+                    .collect::<Result<Vec<Weekday>, _>>()
+                    .map(Self::Weekly)
+                    .map_err(|_| invalid())
+            }),
+        }
+    }
+
+    /// Checks if the rule matches a given date
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::RecurrenceRule;
+    /// use chrono::NaiveDate;
+    ///
+    /// let rule = RecurrenceRule::from("last friday of month").unwrap();
+    /// assert!(rule.matches(NaiveDate::from_ymd_opt(2024, 5, 31).unwrap()));
+    /// assert!(!rule.matches(NaiveDate::from_ymd_opt(2024, 5, 24).unwrap()));
+    /// ```
+    pub fn matches(&self, date: NaiveDate) -> bool {
+        match self {
+            Self::Daily => true,
+            Self::Weekdays => !matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+            Self::Weekly(weekdays) => weekdays.contains(&date.weekday()),
+            Self::MonthlyOnDay(day) => date.day() == *day,
+            Self::MonthlyOnWeekday(nth, weekday) => {
+                if date.weekday() != *weekday {
+                    return false;
+                }
+                match nth {
+                    -1 => (date + Days::new(7)).month() != date.month(),
+                    nth => (date.day() - 1) / 7 + 1 == *nth as u32,
+                }
+            }
+        }
+    }
+
+    /// Parses monthly expressions like `day 15`, `first day` or `2nd tuesday`. Returns `None` when
+    /// the expression is not a monthly one
+    fn from_monthly(rule: &str) -> Option<Result<Self, String>> {
+        if let Some(day) = parse_day_of_month(rule) {
+            return Some(match day {
+                1..=31 => Ok(Self::MonthlyOnDay(day)),
+                _ => Err(String::from("Day of month should be between 1 and 31")),
+            });
+        }
+        let (nth, weekday) = rule.split_once(' ')?;
+        let (nth, weekday) = (parse_ordinal(nth)?, weekday.parse().ok()?);
+        Some(match nth {
+            1..=5 | -1 => Ok(Self::MonthlyOnWeekday(nth, weekday)),
+            _ => Err(String::from(
+                "Weekday of month should be between the 1st and the 5th or the last",
+            )),
+        })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Weekdays => write!(f, "weekdays"),
+            Self::Weekly(weekdays) => {
+                let weekdays = weekdays
+                    .iter()
+                    .map(|w| w.to_string().to_lowercase())
+                    .collect::<Vec<String>>()
+                    .join(",");
+                write!(f, "weekly on {weekdays}")
+            }
+            Self::MonthlyOnDay(day) => write!(f, "monthly on day {day}"),
+            Self::MonthlyOnWeekday(nth, weekday) => {
+                let nth = match nth {
+                    -1 => String::from("last"),
+                    1 => String::from("1st"),
+                    2 => String::from("2nd"),
+                    3 => String::from("3rd"),
+                    nth => format!("{nth}th"),
+                };
+                write!(f, "monthly on {nth} {}", weekday.to_string().to_lowercase())
+            }
+        }
+    }
+}
+
+impl str::FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RecurrenceRule::from(s)
+    }
+}
+
+/// Describe an appointment that repeats according to a rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recurrence {
+    id: u32,
+    rule: RecurrenceRule,
+    time: AppointmentTime,
+    description: String,
+    start: NaiveDate,
+    skipped: Vec<NaiveDate>,
+}
+
+impl Recurrence {
+    /// Returns a new `Recurrence` starting at the given date
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, Recurrence, RecurrenceRule};
+    /// use chrono::NaiveDate;
+    ///
+    /// let recurrence = Recurrence::new(1, RecurrenceRule::Daily, AppointmentTime::new(12, 0).unwrap(), String::from("Lunch"), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+    /// assert_eq!("1|01012024|daily|12:00||Lunch", recurrence.to_string());
+    /// ```
+    pub fn new(
+        id: u32,
+        rule: RecurrenceRule,
+        time: AppointmentTime,
+        description: String,
+        start: NaiveDate,
+    ) -> Self {
+        Self {
+            id,
+            rule,
+            time,
+            description,
+            start,
+            skipped: vec![],
+        }
+    }
+
+    /// Creates a `Recurrence` from a string slice in the format used by the recurrence file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, Recurrence, RecurrenceRule};
+    /// use chrono::NaiveDate;
+    ///
+    /// let recurrence = Recurrence::from("3|05022024|weekdays|09:30||Daily standup").unwrap();
+    /// assert_eq!(3, recurrence.id());
+    /// assert_eq!(&RecurrenceRule::Weekdays, recurrence.rule());
+    /// ```
+    pub fn from(recurrence: &str) -> Result<Self, String> {
+        let invalid = || String::from("Invalid string for recurrence");
+        let mut fields = recurrence.splitn(6, '|');
+        let mut next_field = || fields.next().ok_or_else(invalid);
+        let id = next_field()?.parse().map_err(|_| invalid())?;
+        let start = parse_date_code(next_field()?).ok_or_else(invalid)?;
+        let rule = RecurrenceRule::from(next_field()?)?;
+        let time = AppointmentTime::from(next_field()?)?;
+        let skipped: Option<Vec<NaiveDate>> = next_field()?
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(parse_date_code)
+            .collect();
+        let description = next_field()?.to_string();
+        Ok(Self {
+            id,
+            rule,
+            time,
+            description,
+            start,
+            skipped: skipped.ok_or_else(invalid)?,
+        })
+    }
+
+    /// Returns the recurrence identifier
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the rule used for repeating the appointment
+    pub fn rule(&self) -> &RecurrenceRule {
+        &self.rule
+    }
+
+    /// Checks if an occurrence happens at the given date. Skipped dates and dates before the
+    /// recurrence start are not considered occurrences
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, Recurrence, RecurrenceRule};
+    /// use chrono::NaiveDate;
+    ///
+    /// let recurrence = Recurrence::from("1|01062024|weekly on mon|10:00|03062024|1:1 with manager").unwrap();
+    /// assert!(!recurrence.occurs_on(NaiveDate::from_ymd_opt(2024, 6, 3).unwrap()));
+    /// assert!(recurrence.occurs_on(NaiveDate::from_ymd_opt(2024, 6, 10).unwrap()));
+    /// ```
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        date >= self.start && !self.skipped.contains(&date) && self.rule.matches(date)
+    }
+
+    /// Returns the appointment materialized by the recurrence
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, Recurrence};
+    ///
+    /// let recurrence = Recurrence::from("7|01062024|daily|12:30||Lunch").unwrap();
+    /// let appointment = recurrence.to_appointment();
+    /// assert_eq!("12:30 Lunch", appointment.to_string());
+    /// assert_eq!(Some(7), appointment.recurrence_id());
+    /// ```
+    pub fn to_appointment(&self) -> Appointment {
+        let (description, tags) = helper::split_tags(&self.description);
+        Appointment::new(description, self.time.clone())
+            .with_tags(tags)
+            .with_recurrence_id(self.id)
+    }
+
+    /// Return a string version of the `Recurrence` for displaying to the user
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Recurrence;
+    ///
+    /// let recurrence = Recurrence::from("2|01062024|weekly on tue,thu|18:00||Gym").unwrap();
+    /// assert_eq!("#2 [18:00] Gym (weekly on tue,thu since 01/06/2024)", recurrence.to_string_display());
+    /// ```
+    pub fn to_string_display(&self) -> String {
+        format!(
+            "#{} [{}] {} ({} since {})",
+            self.id,
+            self.time,
+            self.description,
+            self.rule,
+            helper::format_date(self.start)
+        )
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let skipped = self
+            .skipped
+            .iter()
+            .map(|date| helper::date_code(*date))
+            .collect::<Vec<String>>()
+            .join(",");
+        write!(
+            f,
+            "{}|{}|{}|{}|{}|{}",
+            self.id,
+            helper::date_code(self.start),
+            self.rule,
+            self.time,
+            skipped,
+            self.description
+        )
+    }
+}
+
+/// Start of the first line of the recurrence file, followed by the identifier of the next
+/// recurrence added
+const NEXT_ID_HEADER: &str = "next_id|";
+
+/// Describe the list of recurrences stored in a file
+pub struct RecurrenceList<'a> {
+    path: &'a PathBuf,
+    recurrences: Vec<Recurrence>,
+    invalid: Vec<InvalidLine>,
+    /// Identifier of the next recurrence added, so removed ones are never given again
+    next_id: u32,
+}
+
+impl<'a> RecurrenceList<'a> {
+    /// Initialize a list. The recurrences will be loaded from the path informed. Subsequent
+    /// operations will consider the same path
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Recurrence, RecurrenceList};
+    /// use std::path::PathBuf;
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("recurrences_non_existent.txt");
+    /// let list = RecurrenceList::new(&path);
+    /// assert_eq!(&Vec::<Recurrence>::new(), list.recurrences());
+    /// ```
+    pub fn new(path: &'a PathBuf) -> Self {
+        let mut list = Self {
+            path,
+            recurrences: vec![],
+            invalid: vec![],
+            next_id: 1,
+        };
+        list.load();
+        list
+    }
+
+    /// Reads the recurrences and the lines that could not be parsed from the path again
+    fn load(&mut self) {
+        let content = fs::read_to_string(self.path).unwrap_or_default();
+        self.recurrences.clear();
+        self.invalid.clear();
+        self.next_id = 1;
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(next_id) = line.strip_prefix(NEXT_ID_HEADER) {
+                if let Ok(next_id) = next_id.trim().parse::<u32>() {
+                    self.next_id = self.next_id.max(next_id);
+                    continue;
+                }
+            }
+            match Recurrence::from(line) {
+                Ok(recurrence) => self.recurrences.push(recurrence),
+                Err(error) => self.invalid.push(InvalidLine {
+                    number: index + 1,
+                    content: line.to_string(),
+                    error,
+                }),
+            }
+        }
+        let max_id = self.recurrences.iter().map(|r| r.id).max().unwrap_or(0);
+        self.next_id = self.next_id.max(max_id + 1);
+    }
+
+    /// Returns the lines of the file that could not be parsed. They are written back as they are
+    /// until moved with `quarantine`
+    pub fn invalid_lines(&self) -> &[InvalidLine] {
+        &self.invalid
+    }
+
+    /// Moves the lines that could not be parsed to the quarantine file, see
+    /// `storage::quarantine_path`. Returns how many lines were moved
+    pub fn quarantine(&mut self) -> Result<usize, String> {
+        if self.invalid.is_empty() {
+            return Ok(0);
+        }
+        self.update(|list| {
+            if !list.invalid.is_empty() {
+                storage::quarantine(list.path, &list.invalid)?;
+            }
+            let moved = list.invalid.len();
+            list.invalid.clear();
+            Ok(moved)
+        })
+    }
+
+    /// Returns a reference of the current state of recurrences
+    pub fn recurrences(&self) -> &Vec<Recurrence> {
+        &self.recurrences
+    }
+
+    /// Adds a recurrence to the list and the file. Returns the identifier assigned to it, never
+    /// one of a recurrence removed before
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, RecurrenceList, RecurrenceRule};
+    /// use chrono::NaiveDate;
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("recurrences_test_add.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let mut list = RecurrenceList::new(&path);
+    /// let start = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+    /// let id = list.add(RecurrenceRule::Weekdays, AppointmentTime::new(9, 30).unwrap(), String::from("Standup"), start).unwrap();
+    /// assert_eq!(1, id);
+    /// assert_eq!("next_id|2\n1|03062024|weekdays|09:30||Standup\n", fs::read_to_string(&path).unwrap());
+    /// ```
+    pub fn add(
+        &mut self,
+        rule: RecurrenceRule,
+        time: AppointmentTime,
+        description: String,
+        start: NaiveDate,
+    ) -> Result<u32, String> {
+        self.update(|list| {
+            let id = list.next_id;
+            list.next_id += 1;
+            list.recurrences
+                .push(Recurrence::new(id, rule, time, description, start));
+            Ok(id)
+        })
+    }
+
+    /// Removes a recurrence, and therefore all its occurrences, from the list and the file
+    pub fn remove(&mut self, id: u32) -> Result<(), String> {
+        self.update(|list| {
+            let index = list.position(id)?;
+            list.recurrences.remove(index);
+            Ok(())
+        })
+    }
+
+    /// Skips a single occurrence of a recurrence, keeping the remaining ones
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentTime, RecurrenceList, RecurrenceRule};
+    /// use chrono::NaiveDate;
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp").join("todayiwill").join("recurrences_test_skip.txt");
+    /// fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    ///
+    /// let mut list = RecurrenceList::new(&path);
+    /// let start = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+    /// let id = list.add(RecurrenceRule::Daily, AppointmentTime::new(12, 0).unwrap(), String::from("Lunch"), start).unwrap();
+    /// list.skip(id, NaiveDate::from_ymd_opt(2024, 6, 5).unwrap()).unwrap();
+    /// assert!(list.occurrences(NaiveDate::from_ymd_opt(2024, 6, 5).unwrap()).is_empty());
+    /// assert_eq!(1, list.occurrences(NaiveDate::from_ymd_opt(2024, 6, 6).unwrap()).len());
+    /// ```
+    pub fn skip(&mut self, id: u32, date: NaiveDate) -> Result<(), String> {
+        self.update(|list| {
+            let index = list.position(id)?;
+            let recurrence = &mut list.recurrences[index];
+            if !recurrence.occurs_on(date) {
+                return Err(String::from(
+                    "The recurrence has no occurrence on the given date.",
+                ));
+            }
+            recurrence.skipped.push(date);
+            recurrence.skipped.sort();
+            Ok(())
+        })
+    }
+
+    /// Returns the appointments materialized by the recurrences for a given date
+    pub fn occurrences(&self, date: NaiveDate) -> Vec<Appointment> {
+        self.recurrences
+            .iter()
+            .filter(|r| r.occurs_on(date))
+            .map(Recurrence::to_appointment)
+            .collect()
+    }
+
+    /// Returns a recurrence by its identifier
+    pub fn get(&self, id: u32) -> Option<&Recurrence> {
+        self.recurrences.iter().find(|r| r.id == id)
+    }
+
+    /// Write the current state of recurrences to the path
+    pub fn write(&self) -> Result<(), String> {
+        let _lock = storage::lock(self.path)?;
+        self.save()
+    }
+
+    /// Reloads the file and applies a change holding the lock of its directory, then writes the
+    /// recurrences. Changes made by other processes since the list was loaded are kept. Nothing
+    /// is written when the change fails
+    fn update<T>(
+        &mut self,
+        change: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let _lock = storage::lock(self.path)?;
+        self.load();
+        let result = change(self)?;
+        self.save()?;
+        Ok(result)
+    }
+
+    /// Writes the recurrences, see `write`. The lock should already be held
+    fn save(&self) -> Result<(), String> {
+        match self.write_to_file() {
+            Ok(..) => Ok(()),
+            Err(error) => Err(format!(
+                "Error while saving the recurrence. Error: {}",
+                error
+            )),
+        }
+    }
+
+    fn position(&self, id: u32) -> Result<usize, String> {
+        self.recurrences
+            .iter()
+            .position(|r| r.id == id)
+            .ok_or(String::from("There is no recurrence with this id."))
+    }
+
+    /// Writes the recurrences vector in the path supplied
+    fn write_to_file(&self) -> Result<(), io::Error> {
+        let mut content = format!("{NEXT_ID_HEADER}{}\n", self.next_id);
+        for recurrence in &self.recurrences {
+            content.push_str(&format!("{recurrence}\n"));
+        }
+        for line in &self.invalid {
+            content.push_str(&format!("{}\n", line.content));
+        }
+        storage::write_atomically(self.path, content.as_bytes())
+    }
+}
+
+impl<'a> fmt::Display for RecurrenceList<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let recurrences_text = self
+            .recurrences
+            .iter()
+            .map(Recurrence::to_string_display)
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        write!(f, "{recurrences_text}")
+    }
+}
+
+/// Parses a day of month expression like `day 15`, `15th day` or `first day`
+fn parse_day_of_month(expression: &str) -> Option<u32> {
+    if let Some(day) = expression.strip_prefix("day ") {
+        return day.trim().parse().ok();
+    }
+    let day = expression.strip_suffix(" day")?;
+    match parse_ordinal(day)? {
+        -1 => None,
+        day => Some(day as u32),
+    }
+}
+
+/// Parses ordinals like `first`, `2nd` or `last`. `last` is returned as `-1`
+fn parse_ordinal(ordinal: &str) -> Option<i8> {
+    let nth = match ordinal {
+        "first" => 1,
+        "second" => 2,
+        "third" => 3,
+        "fourth" => 4,
+        "fifth" => 5,
+        "last" => -1,
+        ordinal => ordinal
+            .strip_suffix("st")
+            .or(ordinal.strip_suffix("nd"))
+            .or(ordinal.strip_suffix("rd"))
+            .or(ordinal.strip_suffix("th"))?
+            .parse()
+            .ok()?,
+    };
+    Some(nth)
+}
+
+/// Parses a date in the format returned by `helper::date_code`
+fn parse_date_code(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%d%m%Y").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use chrono::{NaiveDate, Weekday};
+
+    use super::{Recurrence, RecurrenceList, RecurrenceRule};
+    use crate::appointment::AppointmentTime;
+
+    fn generate_path_for_test(test_name: &str) -> PathBuf {
+        let path = PathBuf::from("/tmp")
+            .join("todayilearn-tests")
+            .join(format!("recurrences_test_{}.txt", test_name));
+        fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+        if path.exists() {
+            fs::remove_file(&path).expect("Failed to clean test file");
+        }
+        path
+    }
+
+    fn date(day: u32, month: u32, year: i32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parse_rules() {
+        assert_eq!(
+            RecurrenceRule::from("daily").unwrap(),
+            RecurrenceRule::Daily
+        );
+        assert_eq!(
+            RecurrenceRule::from("Every day").unwrap(),
+            RecurrenceRule::Daily
+        );
+        assert_eq!(
+            RecurrenceRule::from("every weekday").unwrap(),
+            RecurrenceRule::Weekdays
+        );
+        assert_eq!(
+            RecurrenceRule::from("every monday and friday").unwrap(),
+            RecurrenceRule::Weekly(vec![Weekday::Mon, Weekday::Fri])
+        );
+        assert_eq!(
+            RecurrenceRule::from("every tue,thu").unwrap(),
+            RecurrenceRule::Weekly(vec![Weekday::Tue, Weekday::Thu])
+        );
+        assert_eq!(
+            RecurrenceRule::from("every 2nd tuesday").unwrap(),
+            RecurrenceRule::MonthlyOnWeekday(2, Weekday::Tue)
+        );
+        assert_eq!(
+            RecurrenceRule::from("last friday of the month").unwrap(),
+            RecurrenceRule::MonthlyOnWeekday(-1, Weekday::Fri)
+        );
+        assert_eq!(
+            RecurrenceRule::from("first day of month").unwrap(),
+            RecurrenceRule::MonthlyOnDay(1)
+        );
+        assert_eq!(
+            RecurrenceRule::from("monthly on day 15").unwrap(),
+            RecurrenceRule::MonthlyOnDay(15)
+        );
+    }
+
+    #[test]
+    fn parse_malformed_rules() {
+        assert_eq!(
+            RecurrenceRule::from("every now and then").unwrap_err(),
+            "Invalid recurrence rule \"now and then\""
+        );
+        assert_eq!(
+            RecurrenceRule::from("monthly on day 32").unwrap_err(),
+            "Day of month should be between 1 and 31"
+        );
+        assert!(RecurrenceRule::from("monthly on monday").is_err());
+        assert_eq!(
+            RecurrenceRule::from("every 9th monday").unwrap_err(),
+            "Weekday of month should be between the 1st and the 5th or the last"
+        );
+        assert_eq!(
+            RecurrenceRule::from("0th tuesday of month").unwrap_err(),
+            "Weekday of month should be between the 1st and the 5th or the last"
+        );
+    }
+
+    #[test]
+    fn rules_should_round_trip_through_display() {
+        let rules = vec![
+            RecurrenceRule::Daily,
+            RecurrenceRule::Weekdays,
+            RecurrenceRule::Weekly(vec![Weekday::Wed, Weekday::Sat]),
+            RecurrenceRule::MonthlyOnDay(28),
+            RecurrenceRule::MonthlyOnWeekday(3, Weekday::Sun),
+            RecurrenceRule::MonthlyOnWeekday(-1, Weekday::Mon),
+        ];
+        for rule in rules {
+            assert_eq!(RecurrenceRule::from(&rule.to_string()).unwrap(), rule);
+        }
+    }
+
+    #[test]
+    fn rules_should_match_dates() {
+        let weekdays = RecurrenceRule::Weekdays;
+        assert!(weekdays.matches(date(7, 6, 2024)));
+        assert!(!weekdays.matches(date(8, 6, 2024)));
+
+        let second_tuesday = RecurrenceRule::MonthlyOnWeekday(2, Weekday::Tue);
+        assert!(second_tuesday.matches(date(11, 6, 2024)));
+        assert!(!second_tuesday.matches(date(4, 6, 2024)));
+        assert!(!second_tuesday.matches(date(12, 6, 2024)));
+
+        let thirty_first = RecurrenceRule::MonthlyOnDay(31);
+        assert!(thirty_first.matches(date(31, 5, 2024)));
+        assert!(!thirty_first.matches(date(30, 6, 2024)));
+    }
+
+    #[test]
+    fn malformed_recurrence_from_string() {
+        assert_eq!(
+            Recurrence::from("1|01062024|daily|25:00||Lunch").unwrap_err(),
+            "Hour should be between 0 and 23"
+        );
+        assert_eq!(
+            Recurrence::from("x|01062024|daily|12:00||Lunch").unwrap_err(),
+            "Invalid string for recurrence"
+        );
+        assert_eq!(
+            Recurrence::from("1|01062024|daily").unwrap_err(),
+            "Invalid string for recurrence"
+        );
+    }
+
+    #[test]
+    fn recurrence_description_may_contain_separator() {
+        let recurrence = Recurrence::from("4|01062024|daily|08:00||Coffee | tea").unwrap();
+        assert_eq!(
+            "08:00 Coffee | tea",
+            recurrence.to_appointment().to_string()
+        );
+    }
+
+    #[test]
+    fn recurrence_list_operations_should_persist() {
+        let path = generate_path_for_test("recurrence_list_operations_should_persist");
+        let mut list = RecurrenceList::new(&path);
+        let standup = list
+            .add(
+                RecurrenceRule::Weekdays,
+                AppointmentTime::new(9, 30).unwrap(),
+                String::from("Standup"),
+                date(3, 6, 2024),
+            )
+            .unwrap();
+        let review = list
+            .add(
+                RecurrenceRule::MonthlyOnDay(5),
+                AppointmentTime::new(15, 0).unwrap(),
+                String::from("Monthly review"),
+                date(1, 6, 2024),
+            )
+            .unwrap();
+        assert_eq!((standup, review), (1, 2));
+
+        list.skip(standup, date(5, 6, 2024)).unwrap();
+        assert_eq!(
+            list.skip(standup, date(8, 6, 2024)).unwrap_err(),
+            "The recurrence has no occurrence on the given date."
+        );
+
+        let list = RecurrenceList::new(&path);
+        assert_eq!(
+            list.occurrences(date(5, 6, 2024))
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<String>>(),
+            vec!["15:00 Monthly review"]
+        );
+        assert_eq!(list.occurrences(date(4, 6, 2024)).len(), 1);
+        assert_eq!(list.occurrences(date(31, 5, 2024)).len(), 0);
+
+        let mut list = RecurrenceList::new(&path);
+        list.remove(review).unwrap();
+        assert_eq!(
+            list.remove(review).unwrap_err(),
+            "There is no recurrence with this id."
+        );
+        assert_eq!(
+            "next_id|3\n1|03062024|weekdays|09:30|05062024|Standup\n",
+            fs::read_to_string(&path).unwrap()
+        );
+        let mut list = RecurrenceList::new(&path);
+        let gym = list
+            .add(
+                RecurrenceRule::Daily,
+                AppointmentTime::new(18, 0).unwrap(),
+                String::from("Gym"),
+                date(3, 6, 2024),
+            )
+            .unwrap();
+        assert_eq!(3, gym);
+    }
+
+    #[test]
+    fn recurrence_list_should_keep_changes_of_other_lists() {
+        let path = generate_path_for_test("recurrence_list_should_keep_changes_of_other_lists");
+        let mut first = RecurrenceList::new(&path);
+        let mut second = RecurrenceList::new(&path);
+        let standup = first
+            .add(
+                RecurrenceRule::Daily,
+                AppointmentTime::new(9, 30).unwrap(),
+                String::from("Standup"),
+                date(3, 6, 2024),
+            )
+            .unwrap();
+        let gym = second
+            .add(
+                RecurrenceRule::Daily,
+                AppointmentTime::new(18, 0).unwrap(),
+                String::from("Gym"),
+                date(3, 6, 2024),
+            )
+            .unwrap();
+        assert_eq!((standup, gym), (1, 2));
+
+        first.skip(gym, date(4, 6, 2024)).unwrap();
+        assert_eq!(
+            "next_id|3\n1|03062024|daily|09:30||Standup\n2|03062024|daily|18:00|04062024|Gym\n",
+            fs::read_to_string(&path).unwrap()
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use super::{duration::Duration, Appointment, AppointmentDateTime};
+
+/// Describe a destination for the reminders of upcoming appointments
+pub trait NotificationSink {
+    /// Notifies that an appointment is about to start
+    fn notify(&mut self, appointment: &Appointment) -> Result<(), String>;
+}
+
+/// Prints the reminders to the standard output, optionally ringing the terminal bell
+pub struct TerminalSink {
+    bell: bool,
+}
+
+impl TerminalSink {
+    /// Returns a new `TerminalSink`
+    pub fn new(bell: bool) -> Self {
+        Self { bell }
+    }
+}
+
+impl NotificationSink for TerminalSink {
+    fn notify(&mut self, appointment: &Appointment) -> Result<(), String> {
+        let bell = if self.bell { "\x07" } else { "" };
+        println!("{bell}{}", reminder_message(appointment));
+        Ok(())
+    }
+}
+
+/// Runs a shell command for each reminder. The appointment is available to the command through
+/// the `TODAYIWILL_TIME` and `TODAYIWILL_DESCRIPTION` environment variables
+pub struct CommandSink {
+    command: String,
+}
+
+impl CommandSink {
+    /// Returns a new `CommandSink`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, CommandSink, NotificationSink};
+    /// use std::fs;
+    ///
+    /// let mut sink = CommandSink::new(String::from("echo \"$TODAYIWILL_DESCRIPTION\" > /tmp/todayiwill_test_command_sink.txt"));
+    /// sink.notify(&Appointment::new(String::from("Call mom"), AppointmentTime::new(10, 0).unwrap())).unwrap();
+    /// assert_eq!("Call mom\n", fs::read_to_string("/tmp/todayiwill_test_command_sink.txt").unwrap());
+    /// ```
+    pub fn new(command: String) -> Self {
+        Self { command }
+    }
+}
+
+impl NotificationSink for CommandSink {
+    fn notify(&mut self, appointment: &Appointment) -> Result<(), String> {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("TODAYIWILL_TIME", appointment.time().to_string())
+            .env("TODAYIWILL_DESCRIPTION", appointment.description())
+            .stdin(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => Err(format!("Reminder command failed with {status}")),
+            Err(error) => Err(format!("Failed to run the reminder command. {error}")),
+        }
+    }
+}
+
+/// Writes the reminders as lines to a file, usually a FIFO read by another program. Opening a
+/// FIFO blocks until there is a reader
+pub struct FifoSink {
+    path: PathBuf,
+}
+
+impl FifoSink {
+    /// Returns a new `FifoSink`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Appointment, AppointmentTime, FifoSink, NotificationSink};
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// let path = PathBuf::from("/tmp/todayiwill_test_fifo_sink.txt");
+    /// if path.exists() {
+    ///     fs::remove_file(&path).expect("Failed to clean test file");
+    /// }
+    /// let mut sink = FifoSink::new(path.clone());
+    /// sink.notify(&Appointment::new(String::from("Stretch"), AppointmentTime::new(16, 0).unwrap())).unwrap();
+    /// assert_eq!("Reminder: [16:00] Stretch\n", fs::read_to_string(&path).unwrap());
+    /// ```
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl NotificationSink for FifoSink {
+    fn notify(&mut self, appointment: &Appointment) -> Result<(), String> {
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", reminder_message(appointment)));
+        match written {
+            Ok(..) => Ok(()),
+            Err(error) => Err(format!(
+                "Failed to write the reminder to {}. {error}",
+                self.path.display()
+            )),
+        }
+    }
+}
+
+/// Returns when the next reminder should be fired, considering the pending appointments and a
+/// lead time. Reminders already due are not considered. Appointments without a date are taken on
+/// the date of the current moment
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{reminder, Appointment, AppointmentDateTime, AppointmentTime, Duration};
+/// use chrono::NaiveDate;
+///
+/// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+/// let appointments = vec![
+///     Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap()),
+///     Appointment::new(String::from("Lunch"), AppointmentTime::new(12, 0).unwrap()),
+///     Appointment::new(String::from("Deploy"), AppointmentTime::new(0, 2).unwrap()).with_date(date.succ_opt().unwrap()),
+/// ];
+/// let now = AppointmentDateTime::new(date, &AppointmentTime::new(9, 26).unwrap());
+/// let next = reminder::next_reminder_time(&appointments, &now, Duration::from_minutes(5));
+/// assert_eq!("2024-06-10 11:55:00", next.unwrap().to_string());
+/// let now = AppointmentDateTime::new(date, &AppointmentTime::new(23, 0).unwrap());
+/// let next = reminder::next_reminder_time(&appointments, &now, Duration::from_minutes(5));
+/// assert_eq!("2024-06-10 23:57:00", next.unwrap().to_string());
+/// ```
+pub fn next_reminder_time(
+    appointments: &[Appointment],
+    now: &AppointmentDateTime,
+    lead_time: Duration,
+) -> Option<AppointmentDateTime> {
+    appointments
+        .iter()
+        .filter(|a| a.status().is_pending())
+        .map(|a| a.starts_at(now.date()) - lead_time)
+        .filter(|at| at > now)
+        .min()
+}
+
+/// Returns the message used for reminding an appointment
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{reminder, Appointment, AppointmentTime};
+///
+/// let appointment = Appointment::new(String::from("Standup"), AppointmentTime::new(9, 30).unwrap());
+/// assert_eq!("Reminder: [09:30] Standup", reminder::reminder_message(&appointment));
+/// ```
+pub fn reminder_message(appointment: &Appointment) -> String {
+    format!(
+        "Reminder: [{}] {}",
+        appointment.time(),
+        appointment.description()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use chrono::NaiveDate;
+
+    use super::{next_reminder_time, CommandSink, FifoSink, NotificationSink};
+    use crate::appointment::{
+        duration::Duration, Appointment, AppointmentDateTime, AppointmentTime, Status,
+    };
+
+    fn appointment(description: &str, hour: i32, minutes: i32) -> Appointment {
+        Appointment::new(
+            String::from(description),
+            AppointmentTime::new(hour, minutes).unwrap(),
+        )
+    }
+
+    fn at(hour: i32, minutes: i32) -> AppointmentDateTime {
+        AppointmentDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            &AppointmentTime::new(hour, minutes).unwrap(),
+        )
+    }
+
+    #[test]
+    fn next_reminder_time_should_skip_due_and_past_reminders() {
+        let appointments = vec![
+            appointment("Breakfast", 7, 0),
+            appointment("Standup", 9, 30),
+            appointment("Review", 10, 0),
+        ];
+        assert_eq!(
+            next_reminder_time(&appointments, &at(9, 25), Duration::from_minutes(5)),
+            Some(at(9, 55))
+        );
+        assert_eq!(
+            next_reminder_time(&appointments, &at(9, 24), Duration::from_minutes(5)),
+            Some(at(9, 25))
+        );
+        assert_eq!(
+            next_reminder_time(&appointments, &at(9, 55), Duration::from_minutes(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn next_reminder_time_should_skip_appointments_not_pending() {
+        let appointments = vec![
+            appointment("Standup", 9, 30).with_status(Status::Cancelled),
+            appointment("Review", 10, 0),
+        ];
+        assert_eq!(
+            next_reminder_time(&appointments, &at(9, 0), Duration::from_minutes(5)),
+            Some(at(9, 55))
+        );
+    }
+
+    #[test]
+    fn next_reminder_time_should_cross_midnight() {
+        let tomorrow = NaiveDate::from_ymd_opt(2024, 6, 11).unwrap();
+        let appointments = vec![appointment("Deploy", 0, 3).with_date(tomorrow)];
+        assert_eq!(
+            next_reminder_time(&appointments, &at(23, 50), Duration::from_minutes(5)),
+            Some(at(23, 58))
+        );
+    }
+
+    #[test]
+    fn next_reminder_time_without_appointments() {
+        assert_eq!(
+            next_reminder_time(&[], &at(9, 25), Duration::from_minutes(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn fifo_sink_should_append_reminders() {
+        let path = PathBuf::from("/tmp")
+            .join("todayilearn-tests")
+            .join("reminders_fifo_sink_should_append_reminders.txt");
+        fs::create_dir_all(path.parent().unwrap()).expect("Failed to create test dir");
+        if path.exists() {
+            fs::remove_file(&path).expect("Failed to clean test file");
+        }
+        let mut sink = FifoSink::new(path.clone());
+        sink.notify(&appointment("Standup", 9, 30)).unwrap();
+        sink.notify(&appointment("Lunch", 12, 0)).unwrap();
+        assert_eq!(
+            "Reminder: [09:30] Standup\nReminder: [12:00] Lunch\n",
+            fs::read_to_string(&path).unwrap()
+        );
+    }
+
+    #[test]
+    fn command_sink_should_report_failures() {
+        let mut sink = CommandSink::new(String::from("exit 3"));
+        assert_eq!(
+            sink.notify(&appointment("Standup", 9, 30)).unwrap_err(),
+            "Reminder command failed with exit status: 3"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use regex::{Regex, RegexBuilder};
+
+/// Describe how the descriptions are matched by `search`
+#[derive(Clone, Debug)]
+pub enum Query {
+    /// Descriptions containing the text, considering the case
+    Substring(String),
+    /// Descriptions containing the text in any case, kept in lowercase
+    IgnoreCase(String),
+    /// Descriptions matching a regular expression
+    Regex(Regex),
+}
+
+impl Query {
+    /// Returns a query for a pattern, which is a regular expression when `regex` is set
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Query;
+    ///
+    /// assert!(Query::new("dentist", true, false).unwrap().matches("Go to the Dentist"));
+    /// assert!(!Query::new("dentist", false, false).unwrap().matches("Go to the Dentist"));
+    /// assert!(Query::new("^go .* dentist$", true, true).unwrap().matches("Go to the Dentist"));
+    /// assert!(Query::new("(", false, true).is_err());
+    /// ```
+    pub fn new(pattern: &str, ignore_case: bool, regex: bool) -> Result<Self, String> {
+        match (regex, ignore_case) {
+            (true, _) => RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+                .map(Query::Regex)
+                .map_err(|error| format!("Invalid regular expression. {error}")),
+            (false, true) => Ok(Query::IgnoreCase(pattern.to_lowercase())),
+            (false, false) => Ok(Query::Substring(pattern.to_string())),
+        }
+    }
+
+    /// Checks if a text is matched by the query
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Query::Substring(pattern) => text.contains(pattern.as_str()),
+            Query::IgnoreCase(pattern) => text.to_lowercase().contains(pattern.as_str()),
+            Query::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Query;
+
+    #[test]
+    fn substring_should_consider_the_case() {
+        let query = Query::new("Run", false, false).unwrap();
+        assert!(query.matches("Morning Run"));
+        assert!(!query.matches("Morning run"));
+    }
+
+    #[test]
+    fn ignore_case_should_match_any_case() {
+        let query = Query::new("ÉCOLE", true, false).unwrap();
+        assert!(query.matches("Pick the kids at école"));
+    }
+
+    #[test]
+    fn regex_should_match() {
+        let query = Query::new(r"call (mom|dad)", false, true).unwrap();
+        assert!(query.matches("call dad after lunch"));
+        assert!(!query.matches("Call dad after lunch"));
+        assert!(Query::new("[a-", false, true)
+            .unwrap_err()
+            .starts_with("Invalid regular expression."));
+    }
+}
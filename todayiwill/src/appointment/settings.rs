@@ -0,0 +1,260 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use super::{duration::Duration, helper, next, zone::Zone};
+
+/// Environment variable that overrides the data dir of the config file
+pub const DATA_DIR_VAR: &str = "TODAYIWILL_DATA_DIR";
+
+/// Environment variable that overrides the path of the config file
+pub const CONFIG_FILE_VAR: &str = "TODAYIWILL_CONFIG";
+
+/// Describe when the output is colored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Colors are used when printing to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Represent the preferences read from the config file (`config.toml` inside the config dir of
+/// the current operating system)
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Directory where the appointments are stored, `~/` is expanded to the home dir
+    pub data_dir: Option<PathBuf>,
+    /// How long before the appointment `watch` sends the reminder, a number of minutes or a
+    /// duration like `90s`
+    pub lead_time: Option<Duration>,
+    /// Format used to print and parse dates, see `chrono::format::strftime`
+    pub date_format: Option<String>,
+    pub color: ColorChoice,
+    /// Time zone the current date and time are taken in, the one of the system by default
+    pub time_zone: Option<Zone>,
+    /// Template `next` prints the next appointment with, see `next::render`
+    pub next_template: Option<String>,
+    /// Preferences of each calendar, by name
+    pub calendars: BTreeMap<String, CalendarSettings>,
+}
+
+/// Represent the preferences of a calendar, read from its `[calendars.NAME]` table
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CalendarSettings {
+    /// Time zone the appointments of the calendar are written in when they have none
+    pub time_zone: Option<Zone>,
+}
+
+impl Settings {
+    /// Parses the settings from the content of a config file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{ColorChoice, Duration, Settings};
+    ///
+    /// let settings = Settings::from("lead_time = 10\ncolor = \"never\"").unwrap();
+    /// assert_eq!(Some(Duration::from_minutes(10)), settings.lead_time);
+    /// assert_eq!(ColorChoice::Never, settings.color);
+    /// let settings = Settings::from("lead_time = \"90s\"").unwrap();
+    /// assert_eq!(Some(Duration::from_seconds(90)), settings.lead_time);
+    /// assert!(Settings::from("lead_time = \"soon\"").is_err());
+    /// ```
+    pub fn from(content: &str) -> Result<Self, String> {
+        let settings: Settings =
+            toml::from_str(content).map_err(|error| error.message().to_string())?;
+        if let Some(format) = &settings.date_format {
+            if !helper::is_valid_date_format(format) {
+                return Err(format!("Invalid date format \"{format}\""));
+            }
+        }
+        if settings
+            .lead_time
+            .is_some_and(|lead_time| lead_time.is_negative())
+        {
+            return Err(String::from("Lead time should not be negative"));
+        }
+        if let Some(template) = &settings.next_template {
+            next::validate(template)?;
+        }
+        Ok(settings)
+    }
+
+    /// Reads the settings from a config file, returning the default ones when it does not exist
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).map_err(|error| {
+            format!("Could not read the config file {}. {error}", path.display())
+        })?;
+        Self::from(&content)
+            .map_err(|error| format!("Invalid config file {}. {error}", path.display()))
+    }
+
+    /// Returns the path of the config file, `TODAYIWILL_CONFIG` when set
+    pub fn path() -> Option<PathBuf> {
+        match env::var_os(CONFIG_FILE_VAR) {
+            Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+            _ => dirs::config_dir().map(|dir| dir.join("todayiwill").join("config.toml")),
+        }
+    }
+
+    /// Returns the dir where the appointments are stored. The given dir takes precedence over
+    /// `TODAYIWILL_DATA_DIR`, which takes precedence over the config file. The data dir of the
+    /// current operating system is used when none of them is set
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Settings;
+    /// use std::path::PathBuf;
+    ///
+    /// let settings = Settings::from("data_dir = \"/srv/appointments\"").unwrap();
+    /// assert_eq!(Ok(PathBuf::from("/tmp/appointments")), settings.data_dir(Some(PathBuf::from("/tmp/appointments"))));
+    /// ```
+    pub fn data_dir(&self, data_dir: Option<PathBuf>) -> Result<PathBuf, String> {
+        if let Some(data_dir) = Self::data_dir_override(data_dir) {
+            return Ok(data_dir);
+        }
+        if let Some(data_dir) = &self.data_dir {
+            return Ok(expand_home(data_dir));
+        }
+        dirs::data_dir()
+            .map(|dir| dir.join("todayiwill"))
+            .ok_or(String::from(
+                "Could not find a data dir, set one with --data-dir, TODAYIWILL_DATA_DIR or the config file.",
+            ))
+    }
+
+    /// Returns the dir where the appointments are stored when it does not come from the config
+    /// file, the given dir or else `TODAYIWILL_DATA_DIR`
+    pub fn data_dir_override(data_dir: Option<PathBuf>) -> Option<PathBuf> {
+        data_dir.or(env::var_os(DATA_DIR_VAR)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from))
+    }
+
+    /// Returns the time zone the appointments of a calendar are written in when they have none
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{Settings, Zone};
+    ///
+    /// let settings = Settings::from("[calendars.work]\ntime_zone = \"America/New_York\"").unwrap();
+    /// assert_eq!(Zone::from("America/New_York").ok(), settings.calendar_zone("work"));
+    /// assert_eq!(None, settings.calendar_zone("default"));
+    /// ```
+    pub fn calendar_zone(&self, calendar: &str) -> Option<Zone> {
+        self.calendars
+            .get(calendar)
+            .and_then(|calendar| calendar.time_zone)
+    }
+
+    /// Applies the color preference to everything printed afterwards
+    pub fn apply_color(&self) {
+        match self.color {
+            ColorChoice::Auto => colored::control::unset_override(),
+            ColorChoice::Always => colored::control::set_override(true),
+            ColorChoice::Never => colored::control::set_override(false),
+        }
+    }
+}
+
+/// Replaces a leading `~` by the home dir, when there is one
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+    };
+
+    use super::{expand_home, CalendarSettings, ColorChoice, Duration, Settings, Zone};
+
+    #[test]
+    fn parse_all_settings() {
+        let settings = Settings::from(
+            "data_dir = \"/srv/todayiwill\"\nlead_time = 15\ndate_format = \"%m/%d/%Y\"\ncolor = \"always\"\ntime_zone = \"Europe/Lisbon\"\nnext_template = \"{time} {desc}\"\n[calendars.work]\ntime_zone = \"Asia/Tokyo\"",
+        )
+        .unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                data_dir: Some(PathBuf::from("/srv/todayiwill")),
+                lead_time: Some(Duration::from_minutes(15)),
+                date_format: Some(String::from("%m/%d/%Y")),
+                color: ColorChoice::Always,
+                time_zone: Zone::from("Europe/Lisbon").ok(),
+                next_template: Some(String::from("{time} {desc}")),
+                calendars: BTreeMap::from([(
+                    String::from("work"),
+                    CalendarSettings {
+                        time_zone: Zone::from("Asia/Tokyo").ok(),
+                    }
+                )]),
+            }
+        );
+    }
+
+    #[test]
+    fn empty_settings_should_be_the_default() {
+        assert_eq!(Settings::from(""), Ok(Settings::default()));
+    }
+
+    #[test]
+    fn parse_invalid_settings() {
+        assert_eq!(
+            Settings::from("date_format = \"%Q\""),
+            Err(String::from("Invalid date format \"%Q\""))
+        );
+        assert_eq!(
+            Settings::from("lead_time = -5"),
+            Err(String::from("Lead time should not be negative"))
+        );
+        assert_eq!(
+            Settings::from("lead_time = \"-5m\""),
+            Err(String::from("Lead time should not be negative"))
+        );
+        assert!(Settings::from("colour = \"never\"").is_err());
+        assert!(Settings::from("color = \"sometimes\"").is_err());
+        assert!(Settings::from("time_zone = \"Mars/Olympus\"").is_err());
+        assert!(Settings::from("next_template = \"{when}\"").is_err());
+        assert!(Settings::from("[calendars.work]\ncolor = \"never\"").is_err());
+    }
+
+    #[test]
+    fn missing_config_file_should_use_the_default_settings() {
+        assert_eq!(
+            Settings::load(Path::new("/nonexistent/todayiwill/config.toml")),
+            Ok(Settings::default())
+        );
+    }
+
+    #[test]
+    fn home_should_be_expanded() {
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_home(Path::new("~/notes")), home.join("notes"));
+        }
+        assert_eq!(
+            expand_home(Path::new("/srv/~notes")),
+            PathBuf::from("/srv/~notes")
+        );
+    }
+}
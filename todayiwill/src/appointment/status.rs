@@ -0,0 +1,98 @@
+use core::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Describe what happened to an appointment
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Nothing was recorded for the appointment yet
+    #[default]
+    Pending,
+    Done,
+    Skipped,
+    Cancelled,
+}
+
+impl Status {
+    /// Parses a status from its name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Status;
+    ///
+    /// assert_eq!(Ok(Status::Done), Status::from("done"));
+    /// assert_eq!(Ok(Status::Cancelled), Status::from("Canceled"));
+    /// assert!(Status::from("finished").is_err());
+    /// ```
+    pub fn from(status: &str) -> Result<Self, String> {
+        match status.trim().to_lowercase().as_str() {
+            "pending" => Ok(Status::Pending),
+            "done" => Ok(Status::Done),
+            "skipped" => Ok(Status::Skipped),
+            "cancelled" | "canceled" => Ok(Status::Cancelled),
+            _ => Err(format!("Invalid status \"{status}\"")),
+        }
+    }
+
+    /// Checks if nothing was recorded for the appointment yet
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Status;
+    ///
+    /// assert!(Status::Pending.is_pending());
+    /// assert!(!Status::Skipped.is_pending());
+    /// ```
+    pub fn is_pending(&self) -> bool {
+        *self == Status::Pending
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Status::from(s)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Status::Pending => "pending",
+            Status::Done => "done",
+            Status::Skipped => "skipped",
+            Status::Cancelled => "cancelled",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn display_and_parse_should_match() {
+        for status in [
+            Status::Pending,
+            Status::Done,
+            Status::Skipped,
+            Status::Cancelled,
+        ] {
+            assert_eq!(Status::from(&status.to_string()), Ok(status));
+        }
+    }
+
+    #[test]
+    fn parse_invalid_status() {
+        assert_eq!(
+            Status::from("later"),
+            Err(String::from("Invalid status \"later\""))
+        );
+    }
+}
@@ -0,0 +1,557 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tempfile::NamedTempFile;
+
+use super::{duration::Duration, zone::Zone, Appointment, AppointmentTime, Metadata, Status};
+
+/// Version of the appointments file format written by this version of the app
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Name of the file locked while the files of a directory are changed
+const LOCK_FILE: &str = ".lock";
+
+/// Describe the formats an appointments file may be written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One `HH:MM description` appointment per line. It is only read for migration purposes and
+    /// will no longer be supported in the next release
+    Legacy,
+    /// A header line carrying the format version followed by one JSON object per appointment
+    JsonLines(u32),
+}
+
+/// Line number of an appointment in the appointments file along with its parsing result
+pub type ParsedLine = (usize, Result<Appointment, String>);
+
+/// Describe a line of a file that could not be parsed. Invalid lines are kept as they are when
+/// the file is written again
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidLine {
+    /// Line number, starting at 1
+    pub number: usize,
+    pub content: String,
+    pub error: String,
+}
+
+/// Header written as the first line of appointment files
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    /// Identifier of the next appointment added, so removed ones are never given again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_id: Option<u32>,
+}
+
+/// Describe how an appointment is written in the appointments file
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    #[serde(default)]
+    id: u32,
+    /// Date the appointment starts on, the one of the file when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    date: Option<NaiveDate>,
+    time: String,
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Status::is_pending")]
+    status: Status,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// IANA name of the time zone the time is written in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<DateTime<Local>>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+/// Detects the format of the content of an appointments file. Empty contents are considered to be
+/// in the current format
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::storage::{self, Format};
+///
+/// assert_eq!(Format::Legacy, storage::detect_format("10:00 Call mom\n"));
+/// assert_eq!(Format::JsonLines(1), storage::detect_format("{\"version\":1}\n"));
+/// assert_eq!(Format::JsonLines(storage::FORMAT_VERSION), storage::detect_format(""));
+/// ```
+pub fn detect_format(content: &str) -> Format {
+    let first_line = match content.lines().find(|line| !line.trim().is_empty()) {
+        Some(line) => line,
+        None => return Format::JsonLines(FORMAT_VERSION),
+    };
+    match serde_json::from_str::<Header>(first_line) {
+        Ok(header) => Format::JsonLines(header.version),
+        Err(..) => Format::Legacy,
+    }
+}
+
+/// Returns the identifier of the next appointment added to a file, as recorded in its header.
+/// Files without one, legacy ones included, return `None`
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::storage;
+///
+/// assert_eq!(Some(5), storage::next_id("{\"version\":1,\"next_id\":5}\n"));
+/// assert_eq!(None, storage::next_id("{\"version\":1}\n"));
+/// assert_eq!(None, storage::next_id("10:00 Call mom\n"));
+/// ```
+pub fn next_id(content: &str) -> Option<u32> {
+    let first_line = content.lines().find(|line| !line.trim().is_empty())?;
+    serde_json::from_str::<Header>(first_line)
+        .ok()
+        .and_then(|header| header.next_id)
+}
+
+/// Parses the content of an appointments file in any supported format. Returns the format found
+/// and a result for each appointment line
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{storage::{self, Format}, Appointment, AppointmentTime};
+///
+/// let content = "{\"version\":1}\n{\"id\":4,\"time\":\"08:30\",\"description\":\"Gym\"}\n";
+/// let (format, appointments) = storage::parse(content);
+/// let appointment = appointments[0].as_ref().unwrap();
+/// assert_eq!(Format::JsonLines(1), format);
+/// assert_eq!(&Appointment::new(String::from("Gym"), AppointmentTime::new(8, 30).unwrap()), appointment);
+/// assert_eq!(4, appointment.id());
+/// ```
+pub fn parse(content: &str) -> (Format, Vec<Result<Appointment, String>>) {
+    let (format, appointments) = parse_lines(content);
+    let appointments = appointments
+        .into_iter()
+        .map(|(_, appointment)| appointment)
+        .collect();
+    (format, appointments)
+}
+
+/// Parses the content of an appointments file like `parse`, returning the line number of each
+/// appointment, starting at 1
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::storage;
+///
+/// let content = "{\"version\":1}\n\n{\"id\":1,\"time\":\"08:30\"}\n";
+/// let (_, appointments) = storage::parse_lines(content);
+/// assert_eq!(3, appointments[0].0);
+/// assert!(appointments[0].1.is_err());
+/// ```
+pub fn parse_lines(content: &str) -> (Format, Vec<ParsedLine>) {
+    let format = detect_format(content);
+    let lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index + 1, line));
+    let appointments = match format {
+        Format::Legacy => lines
+            .map(|(number, line)| (number, Appointment::from(line)))
+            .collect(),
+        Format::JsonLines(..) => lines
+            .skip(1)
+            .map(|(number, line)| (number, parse_entry(line)))
+            .collect(),
+    };
+    (format, appointments)
+}
+
+/// Parses the content of an appointments file like `parse`, separating the appointments from
+/// the lines that could not be parsed
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::storage;
+///
+/// let content = "{\"version\":1}\n{\"time\":\"08:30\",\"description\":\"Gym\"}\nnot an appointment\n";
+/// let (_, appointments, invalid) = storage::parse_valid(content);
+/// assert_eq!(1, appointments.len());
+/// assert_eq!(3, invalid[0].number);
+/// assert_eq!("not an appointment", invalid[0].content);
+/// ```
+pub fn parse_valid(content: &str) -> (Format, Vec<Appointment>, Vec<InvalidLine>) {
+    let (format, parsed) = parse_lines(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut appointments = vec![];
+    let mut invalid = vec![];
+    for (number, appointment) in parsed {
+        match appointment {
+            Ok(appointment) => appointments.push(appointment),
+            Err(error) => invalid.push(InvalidLine {
+                number,
+                content: lines[number - 1].to_string(),
+                error,
+            }),
+        }
+    }
+    (format, appointments, invalid)
+}
+
+/// Returns the path invalid lines of a file are moved to, the same path with the `.invalid`
+/// extension added
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::storage;
+/// use std::path::PathBuf;
+///
+/// assert_eq!(
+///     PathBuf::from("/tmp/appointments_10062024.txt.invalid"),
+///     storage::quarantine_path(&PathBuf::from("/tmp/appointments_10062024.txt"))
+/// );
+/// ```
+pub fn quarantine_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".invalid");
+    path.with_file_name(file_name)
+}
+
+/// Appends invalid lines to the quarantine file of a path, see `quarantine_path`
+pub fn quarantine(path: &Path, lines: &[InvalidLine]) -> Result<(), String> {
+    let quarantine_path = quarantine_path(path);
+    let content: String = lines
+        .iter()
+        .map(|line| format!("{}\n", line.content))
+        .collect();
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&quarantine_path)
+        .and_then(|mut file| file.write_all(content.as_bytes()));
+    match result {
+        Ok(..) => Ok(()),
+        Err(error) => Err(format!(
+            "Failed to write to {}. {error}",
+            quarantine_path.display()
+        )),
+    }
+}
+
+/// Writes the content to a temporary file in the same directory and renames it over the path, so
+/// the file is either fully written or left untouched. The permissions of the file are kept
+pub(crate) fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let mut file = NamedTempFile::new_in(dir)?;
+    file.write_all(content)?;
+    file.as_file().sync_all()?;
+    if let Ok(metadata) = fs::metadata(path) {
+        file.as_file().set_permissions(metadata.permissions())?;
+    }
+    file.persist(path)?;
+    Ok(())
+}
+
+/// Waits for the exclusive lock of the directory of a path. Other processes changing files of the
+/// same directory wait until the returned file is dropped
+pub(crate) fn lock(path: &Path) -> Result<File, String> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let lock_path = dir.join(LOCK_FILE);
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| {
+            OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&lock_path)
+        })
+        .and_then(|file| file.lock_exclusive().map(|_| file));
+    match result {
+        Ok(file) => Ok(file),
+        Err(error) => Err(format!("Failed to lock {}. {error}", lock_path.display())),
+    }
+}
+
+/// Serializes appointments read in a given format in the current one, header included. The
+/// identifier of the next appointment is recorded when it is not `0`. Appointments read in a newer
+/// version of the format are refused, since writing them in the current one would lose what that
+/// version added
+///
+/// # Example
+///
+/// ```
+/// use todayiwill::{storage::{self, Format}, Appointment, AppointmentTime};
+///
+/// let appointment = Appointment::new(String::from("Gym"), AppointmentTime::new(8, 30).unwrap());
+/// assert_eq!(
+///     Ok(String::from("{\"version\":1,\"next_id\":2}\n{\"id\":0,\"time\":\"08:30\",\"description\":\"Gym\"}\n")),
+///     storage::serialize(&[&appointment], Format::Legacy, 2)
+/// );
+/// assert!(storage::serialize(&[&appointment], Format::JsonLines(storage::FORMAT_VERSION + 1), 2).is_err());
+/// ```
+pub fn serialize(
+    appointments: &[&Appointment],
+    format: Format,
+    next_id: u32,
+) -> Result<String, String> {
+    if let Format::JsonLines(version) = format {
+        if version > FORMAT_VERSION {
+            return Err(format!(
+                "The appointments are in version {version} of the file format, newer than the version {FORMAT_VERSION} supported. Update todayiwill to change them."
+            ));
+        }
+    }
+    let mut content = serde_json::to_string(&Header {
+        version: FORMAT_VERSION,
+        next_id: (next_id > 0).then_some(next_id),
+    })
+    .expect("Header should be serializable");
+    content.push('\n');
+    for appointment in appointments {
+        content.push_str(&serialize_entry(appointment));
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+/// Parses a single JSON line into an appointment
+fn parse_entry(line: &str) -> Result<Appointment, String> {
+    let entry: Entry = match serde_json::from_str(line) {
+        Ok(entry) => entry,
+        Err(error) => return Err(format!("Invalid appointment entry. {error}")),
+    };
+    let mut appointment = Appointment::new(entry.description, AppointmentTime::from(&entry.time)?);
+    appointment.date = entry.date;
+    if let Some(duration) = entry.duration_minutes {
+        if duration <= 0 {
+            return Err(String::from(
+                "Duration should be a positive number of minutes",
+            ));
+        }
+        appointment = appointment.with_duration(Duration::from_minutes(duration));
+    }
+    appointment = appointment.with_status(entry.status);
+    for tag in entry.tags {
+        appointment = appointment.with_tag(&tag)?;
+    }
+    if let Some(zone) = entry.time_zone {
+        appointment = appointment.with_zone(Zone::from(&zone)?);
+    }
+    appointment.metadata = Metadata {
+        id: entry.id,
+        created_at: entry.created_at,
+        updated_at: entry.updated_at,
+        extra: entry.extra,
+    };
+    Ok(appointment)
+}
+
+/// Serializes a single appointment as a JSON line
+fn serialize_entry(appointment: &Appointment) -> String {
+    let entry = Entry {
+        id: appointment.metadata.id,
+        date: appointment.date,
+        time: appointment.time.to_string(),
+        description: appointment.description.clone(),
+        duration_minutes: appointment.duration.map(|duration| duration.minutes()),
+        status: appointment.status,
+        tags: appointment.tags.clone(),
+        time_zone: appointment.zone.map(|zone| zone.to_string()),
+        created_at: appointment.metadata.created_at,
+        updated_at: appointment.metadata.updated_at,
+        extra: appointment.metadata.extra.clone(),
+    };
+    serde_json::to_string(&entry).expect("Appointment should be serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    use super::{detect_format, parse, serialize, Format, FORMAT_VERSION};
+    use crate::appointment::{
+        duration::Duration, zone::Zone, Appointment, AppointmentTime, Status,
+    };
+
+    #[test]
+    fn detect_legacy_format() {
+        assert_eq!(detect_format("\n12:00 Lunch\n13:00 Nap\n"), Format::Legacy);
+        assert_eq!(detect_format("{\"time\":\"12:00\"}\n"), Format::Legacy);
+    }
+
+    #[test]
+    fn detect_future_versions() {
+        assert_eq!(detect_format("{\"version\":3}\n"), Format::JsonLines(3));
+    }
+
+    #[test]
+    fn serialize_should_refuse_future_versions() {
+        let appointment =
+            Appointment::new(String::from("Gym"), AppointmentTime::new(8, 30).unwrap());
+        assert_eq!(
+            serialize(&[&appointment], Format::JsonLines(3), 2),
+            Err(String::from("The appointments are in version 3 of the file format, newer than the version 1 supported. Update todayiwill to change them."))
+        );
+        assert!(serialize(&[&appointment], Format::JsonLines(FORMAT_VERSION), 2).is_ok());
+    }
+
+    #[test]
+    fn parse_legacy_content() {
+        let (format, appointments) = parse("22:00 Go to night shift\n212 Nonsense\n");
+        assert_eq!(format, Format::Legacy);
+        assert_eq!(
+            appointments,
+            vec![
+                Ok(Appointment::new(
+                    String::from("Go to night shift"),
+                    AppointmentTime::new(22, 0).unwrap()
+                )),
+                Err(String::from("Invalid string for appointment time"))
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_malformed_entries() {
+        let (_, appointments) = parse(
+            "{\"version\":1}\n{\"id\":1,\"time\":\"25:00\",\"description\":\"Late\"}\n{\"id\":2}\nnot json\n",
+        );
+        assert_eq!(
+            appointments[0],
+            Err(String::from("Hour should be between 0 and 23"))
+        );
+        assert!(appointments[1]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("Invalid appointment entry. missing field `time`"));
+        assert!(appointments[2]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("Invalid appointment entry."));
+    }
+
+    #[test]
+    fn durations_should_be_kept() {
+        let appointment = Appointment::new(
+            String::from("Workshop"),
+            AppointmentTime::new(14, 0).unwrap(),
+        )
+        .with_duration(Duration::from_minutes(90));
+        let content = serialize(&[&appointment], Format::JsonLines(FORMAT_VERSION), 0).unwrap();
+        assert!(content.contains("\"duration_minutes\":90"));
+        let (_, appointments) = parse(&content);
+        assert_eq!(
+            appointments[0].as_ref().unwrap().duration(),
+            Some(Duration::from_minutes(90))
+        );
+        let (_, appointments) = parse(
+            "{\"version\":1}\n{\"id\":1,\"time\":\"14:00\",\"description\":\"Workshop\",\"duration_minutes\":0}\n",
+        );
+        assert_eq!(
+            appointments[0],
+            Err(String::from(
+                "Duration should be a positive number of minutes"
+            ))
+        );
+    }
+
+    #[test]
+    fn dates_should_be_kept() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+        let appointment = Appointment::new(
+            String::from("Night shift"),
+            AppointmentTime::new(22, 0).unwrap(),
+        )
+        .with_date(date);
+        let content = serialize(&[&appointment], Format::JsonLines(FORMAT_VERSION), 0).unwrap();
+        assert!(content.contains("\"date\":\"2024-06-10\",\"time\":\"22:00\""));
+        let (_, appointments) = parse(&content);
+        assert_eq!(appointments[0].as_ref().unwrap().date(), Some(date));
+        let (_, appointments) = parse(
+            "{\"version\":1}\n{\"id\":1,\"time\":\"22:00\",\"description\":\"Night shift\"}\n",
+        );
+        assert_eq!(appointments[0].as_ref().unwrap().date(), None);
+    }
+
+    #[test]
+    fn status_should_be_kept() {
+        let appointment =
+            Appointment::new(String::from("Gym"), AppointmentTime::new(8, 30).unwrap())
+                .with_status(Status::Skipped);
+        let content = serialize(&[&appointment], Format::JsonLines(FORMAT_VERSION), 0).unwrap();
+        assert!(content.contains("\"status\":\"skipped\""));
+        let (_, appointments) = parse(&content);
+        assert_eq!(appointments[0].as_ref().unwrap().status(), Status::Skipped);
+        let (_, appointments) = parse(
+            "{\"version\":1}\n{\"id\":1,\"time\":\"08:30\",\"description\":\"Gym\",\"status\":\"later\"}\n",
+        );
+        assert!(appointments[0]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("Invalid appointment entry. unknown variant `later`"));
+    }
+
+    #[test]
+    fn tags_should_be_kept() {
+        let appointment = Appointment::from("08:30 Gym #health #morning").unwrap();
+        let content = serialize(&[&appointment], Format::JsonLines(FORMAT_VERSION), 0).unwrap();
+        assert!(content.contains("\"description\":\"Gym\",\"tags\":[\"health\",\"morning\"]"));
+        let (_, appointments) = parse(&content);
+        assert_eq!(appointments[0].as_ref().unwrap(), &appointment);
+        let (_, appointments) = parse(
+            "{\"version\":1}\n{\"id\":1,\"time\":\"08:30\",\"description\":\"Gym\",\"tags\":[\"two words\"]}\n",
+        );
+        assert!(appointments[0].is_err());
+    }
+
+    #[test]
+    fn time_zones_should_be_kept() {
+        let appointment = Appointment::from("09:00 Sync")
+            .unwrap()
+            .with_zone(Zone::from("Asia/Tokyo").unwrap());
+        let content = serialize(&[&appointment], Format::JsonLines(FORMAT_VERSION), 0).unwrap();
+        assert!(content.contains("\"time_zone\":\"Asia/Tokyo\""));
+        let (_, appointments) = parse(&content);
+        assert_eq!(appointments[0].as_ref().unwrap(), &appointment);
+        let (_, appointments) = parse(
+            "{\"version\":1}\n{\"id\":1,\"time\":\"09:00\",\"description\":\"Sync\",\"time_zone\":\"Asia/Atlantis\"}\n",
+        );
+        assert!(appointments[0].is_err());
+    }
+
+    #[test]
+    fn unknown_fields_and_timestamps_should_be_kept() {
+        let content = "{\"version\":1}\n{\"id\":7,\"time\":\"09:15\",\"description\":\"Dentist\",\"created_at\":\"2024-06-01T10:00:00-03:00\",\"location\":\"Downtown\"}\n";
+        let (_, appointments) = parse(content);
+        let appointment = appointments[0].as_ref().unwrap();
+        assert_eq!(appointment.id(), 7);
+        assert_eq!(
+            appointment.metadata().created_at,
+            Some(
+                Local.from_utc_datetime(
+                    &chrono::NaiveDate::from_ymd_opt(2024, 6, 1)
+                        .unwrap()
+                        .and_hms_opt(13, 0, 0)
+                        .unwrap()
+                )
+            )
+        );
+        let (_, reparsed) =
+            parse(&serialize(&[appointment], Format::JsonLines(FORMAT_VERSION), 0).unwrap());
+        assert_eq!(
+            reparsed[0].as_ref().unwrap().metadata().extra["location"],
+            "Downtown"
+        );
+    }
+}
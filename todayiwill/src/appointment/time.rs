@@ -4,9 +4,9 @@ use std::{
     str,
 };
 
-use chrono::Local;
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta, Timelike};
 
-use super::helper;
+use super::{duration::Duration, helper, zone::Zone};
 
 /// Describe the time for an appointment
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -37,7 +37,7 @@ impl AppointmentTime {
         Ok(Self { hour, minutes })
     }
 
-    /// Returns the AppointmentTime for the system time
+    /// Returns the AppointmentTime for the system time, in the current time zone
     ///
     /// # Example
     ///
@@ -49,11 +49,30 @@ impl AppointmentTime {
     /// assert_eq!(Local::now().format("%H:%M").to_string(), now.to_string());
     /// ```
     pub fn now() -> Self {
-        let now = Local::now();
-        Self {
-            hour: now.format("%H").to_string().parse().unwrap(),
-            minutes: now.format("%M").to_string().parse().unwrap(),
-        }
+        AppointmentDateTime::now().time()
+    }
+
+    /// Returns the hour of the `AppointmentTime`
+    pub fn hour(&self) -> i32 {
+        self.hour
+    }
+
+    /// Returns the minutes of the `AppointmentTime`
+    pub fn minutes(&self) -> i32 {
+        self.minutes
+    }
+
+    /// Returns the number of minutes since midnight
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::AppointmentTime;
+    ///
+    /// assert_eq!(90, AppointmentTime::new(1, 30).unwrap().minutes_since_midnight());
+    /// ```
+    pub fn minutes_since_midnight(&self) -> i32 {
+        self.hour * 60 + self.minutes
     }
 
     /// Checks if the current time is earlier or equal to a reference to another AppointmentTime
@@ -123,36 +142,22 @@ impl AppointmentTime {
     }
 }
 
-impl Add<i32> for AppointmentTime {
+impl Add<Duration> for AppointmentTime {
     type Output = AppointmentTime;
 
-    fn add(self, rhs: i32) -> Self::Output {
-        let minutes_updated = self.minutes + rhs;
-        let hours_updated = self.hour + (minutes_updated / 60);
-        if hours_updated > 23 {
-            return AppointmentTime::max_value();
-        }
-        Self {
-            hour: hours_updated,
-            minutes: minutes_updated % 60,
-        }
+    /// Adds the duration like a clock does, going back to `00:00` after `23:59`. Use
+    /// `AppointmentDateTime` for knowing the day it falls on
+    fn add(self, rhs: Duration) -> Self::Output {
+        (AppointmentDateTime::new(NaiveDate::default(), &self) + rhs).time()
     }
 }
 
-impl Sub<i32> for AppointmentTime {
+impl Sub<Duration> for AppointmentTime {
     type Output = AppointmentTime;
 
-    fn sub(self, rhs: i32) -> Self::Output {
-        let minutes_updated = self.minutes - rhs;
-        let hours_updated =
-            self.hour + (minutes_updated / 60) - if minutes_updated.is_negative() { 1 } else { 0 };
-        if hours_updated < 0 {
-            return AppointmentTime::min_value();
-        }
-        Self {
-            hour: hours_updated,
-            minutes: minutes_updated.abs() % 60,
-        }
+    /// Subtracts the duration like a clock does, going back to `23:59` before `00:00`
+    fn sub(self, rhs: Duration) -> Self::Output {
+        (AppointmentDateTime::new(NaiveDate::default(), &self) - rhs).time()
     }
 }
 
@@ -170,10 +175,144 @@ impl str::FromStr for AppointmentTime {
     }
 }
 
+/// Describe a moment of a given day with seconds precision. Unlike `AppointmentTime`, adding or
+/// subtracting a duration goes on to the next or previous days
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AppointmentDateTime {
+    date_time: NaiveDateTime,
+}
+
+impl AppointmentDateTime {
+    /// Returns the `AppointmentDateTime` of a time on a date
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentDateTime, AppointmentTime};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let date_time = AppointmentDateTime::new(date, &AppointmentTime::new(22, 48).unwrap());
+    /// assert_eq!("2024-06-10 22:48:00", date_time.to_string());
+    /// ```
+    pub fn new(date: NaiveDate, time: &AppointmentTime) -> Self {
+        let time = date
+            .and_hms_opt(time.hour as u32, time.minutes as u32, 0)
+            .expect("Appointment time should be valid");
+        Self { date_time: time }
+    }
+
+    /// Returns the `AppointmentDateTime` of a `NaiveDateTime`, dropping the fractions of second
+    pub fn from_naive(date_time: NaiveDateTime) -> Self {
+        Self {
+            date_time: date_time.with_nanosecond(0).unwrap_or(date_time),
+        }
+    }
+
+    /// Returns the `AppointmentDateTime` for the system time, in the current time zone
+    pub fn now() -> Self {
+        Zone::current().now()
+    }
+
+    /// Returns the date of the `AppointmentDateTime`
+    pub fn date(&self) -> NaiveDate {
+        self.date_time.date()
+    }
+
+    /// Returns the time of the day of the `AppointmentDateTime`, without the seconds
+    pub fn time(&self) -> AppointmentTime {
+        AppointmentTime {
+            hour: self.date_time.hour() as i32,
+            minutes: self.date_time.minute() as i32,
+        }
+    }
+
+    /// Returns the seconds of the `AppointmentDateTime`
+    pub fn second(&self) -> u32 {
+        self.date_time.second()
+    }
+
+    /// Returns the `AppointmentDateTime` as a `NaiveDateTime`
+    pub fn naive(&self) -> NaiveDateTime {
+        self.date_time
+    }
+
+    /// Moves the `AppointmentDateTime` by a number of seconds, stopping at the first or last
+    /// moment that can be represented
+    fn shifted(self, seconds: i64) -> Self {
+        let date_time = self
+            .date_time
+            .checked_add_signed(TimeDelta::seconds(seconds))
+            .unwrap_or(match seconds < 0 {
+                true => NaiveDateTime::MIN,
+                false => NaiveDateTime::MAX,
+            });
+        Self { date_time }
+    }
+}
+
+impl Add<Duration> for AppointmentDateTime {
+    type Output = AppointmentDateTime;
+
+    /// Adds the duration, going on to the next days when it passes midnight
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentDateTime, AppointmentTime, Duration};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let date_time = AppointmentDateTime::new(date, &AppointmentTime::new(23, 50).unwrap());
+    /// assert_eq!("2024-06-11 00:20:00", (date_time + Duration::from_minutes(30)).to_string());
+    /// ```
+    fn add(self, rhs: Duration) -> Self::Output {
+        self.shifted(rhs.seconds() as i64)
+    }
+}
+
+impl Sub<Duration> for AppointmentDateTime {
+    type Output = AppointmentDateTime;
+
+    /// Subtracts the duration, going back to the previous days when it passes midnight
+    fn sub(self, rhs: Duration) -> Self::Output {
+        self.shifted(-(rhs.seconds() as i64))
+    }
+}
+
+impl Sub for AppointmentDateTime {
+    type Output = Duration;
+
+    /// Returns the duration between two moments, limited to what a `Duration` can hold
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentDateTime, AppointmentTime, Duration};
+    /// use chrono::NaiveDate;
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let start = AppointmentDateTime::new(date, &AppointmentTime::new(23, 0).unwrap());
+    /// let end = AppointmentDateTime::new(date.succ_opt().unwrap(), &AppointmentTime::new(1, 30).unwrap());
+    /// assert_eq!(Duration::from_minutes(150), end - start);
+    /// ```
+    fn sub(self, rhs: Self) -> Self::Output {
+        let seconds = (self.date_time - rhs.date_time).num_seconds();
+        Duration::from_seconds(seconds.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+}
+
+impl fmt::Display for AppointmentDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.date_time.format("%Y-%m-%d %H:%M:%S"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::AppointmentTime;
-    use chrono::Local;
+    use super::{AppointmentDateTime, AppointmentTime};
+    use crate::appointment::duration::Duration;
+    use chrono::{Local, NaiveDate};
 
     #[test]
     fn wellformed_appointment_time() {
@@ -269,63 +408,95 @@ mod tests {
     }
 
     #[test]
-    fn add_i32_to_appointment_time() {
-        let result = AppointmentTime::new(10, 30).unwrap() + 10;
+    fn add_duration_to_appointment_time() {
+        let result = AppointmentTime::new(10, 30).unwrap() + Duration::from_minutes(10);
         assert_eq!(result, AppointmentTime::new(10, 40).unwrap());
     }
 
     #[test]
-    fn add_i32_to_appointment_time_edge_case() {
-        let result = AppointmentTime::new(3, 50).unwrap() + 100;
+    fn add_duration_to_appointment_time_edge_case() {
+        let result = AppointmentTime::new(3, 50).unwrap() + Duration::from_minutes(100);
         assert_eq!(result, AppointmentTime::new(5, 30).unwrap());
     }
 
     #[test]
-    fn add_i32_to_appointment_time_upper_limit() {
-        let result = AppointmentTime::new(23, 55).unwrap() + 20;
-        assert_eq!(result, AppointmentTime::max_value());
+    fn add_duration_to_appointment_time_crossing_midnight() {
+        let result = AppointmentTime::new(23, 55).unwrap() + Duration::from_minutes(20);
+        assert_eq!(result, AppointmentTime::new(0, 15).unwrap());
     }
 
     #[test]
-    fn add_i32_to_appointment_time_upper_limit_edge_case() {
-        let result = AppointmentTime::new(23, 55).unwrap() + 4;
+    fn add_duration_to_appointment_time_upper_limit_edge_case() {
+        let result = AppointmentTime::new(23, 55).unwrap() + Duration::from_minutes(4);
         assert_eq!(result, AppointmentTime::max_value());
+        let result = AppointmentTime::new(23, 55).unwrap() + Duration::from_minutes(5);
+        assert_eq!(result, AppointmentTime::min_value());
     }
 
     #[test]
-    fn sub_i32_to_appointment_time() {
-        let result = AppointmentTime::new(10, 30).unwrap() - 20;
+    fn sub_duration_to_appointment_time() {
+        let result = AppointmentTime::new(10, 30).unwrap() - Duration::from_minutes(20);
         assert_eq!(result, AppointmentTime::new(10, 10).unwrap());
     }
 
     #[test]
-    fn sub_i32_to_appointment_time_edge_case() {
-        let result = AppointmentTime::new(3, 10).unwrap() - 100;
+    fn sub_duration_to_appointment_time_edge_case() {
+        let result = AppointmentTime::new(3, 10).unwrap() - Duration::from_minutes(100);
         assert_eq!(result, AppointmentTime::new(1, 30).unwrap());
     }
 
     #[test]
-    fn sub_i32_to_appointment_time_lower_limit() {
-        let result = AppointmentTime::new(0, 5).unwrap() - 20;
-        assert_eq!(result, AppointmentTime::min_value());
+    fn sub_duration_to_appointment_time_crossing_hour() {
+        let result = AppointmentTime::new(10, 0).unwrap() - Duration::from_minutes(5);
+        assert_eq!(result, AppointmentTime::new(9, 55).unwrap());
+        let result = AppointmentTime::new(10, 0).unwrap() - Duration::from_minutes(60);
+        assert_eq!(result, AppointmentTime::new(9, 0).unwrap());
+    }
+
+    #[test]
+    fn sub_duration_to_appointment_time_crossing_midnight() {
+        let result = AppointmentTime::new(0, 5).unwrap() - Duration::from_minutes(20);
+        assert_eq!(result, AppointmentTime::new(23, 45).unwrap());
     }
 
     #[test]
-    fn sub_i32_to_appointment_time_lower_limit_edge_case() {
-        let result = AppointmentTime::new(0, 5).unwrap() - 5;
+    fn sub_duration_to_appointment_time_lower_limit_edge_case() {
+        let result = AppointmentTime::new(0, 5).unwrap() - Duration::from_minutes(5);
         assert_eq!(result, AppointmentTime::min_value());
+        let result = AppointmentTime::new(0, 5).unwrap() - Duration::from_seconds(301);
+        assert_eq!(result, AppointmentTime::max_value());
+    }
+
+    #[test]
+    fn date_time_arithmetic_should_cross_days() {
+        let date = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+        let date_time = AppointmentDateTime::new(date, &AppointmentTime::new(23, 50).unwrap());
+        let later = date_time + Duration::from_seconds(630);
+        assert_eq!(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(), later.date());
+        assert_eq!(AppointmentTime::new(0, 0).unwrap(), later.time());
+        assert_eq!(30, later.second());
+        assert_eq!(date_time, later - Duration::from_seconds(630));
+        assert_eq!(Duration::from_seconds(630), later - date_time);
+        let earlier = date_time - Duration::from_minutes(24 * 60);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 12, 30).unwrap(),
+            earlier.date()
+        );
+        assert_eq!(date_time.time(), earlier.time());
     }
 
     #[test]
     fn appointment_time_should_be_passed() {
-        let future_appointment_time = AppointmentTime::now() + 5;
-        assert!(!future_appointment_time.is_equal_or_earlier_than(&AppointmentTime::now()))
+        let reference_time = AppointmentTime::new(14, 30).unwrap();
+        let future_appointment_time = reference_time.clone() + Duration::from_minutes(5);
+        assert!(!future_appointment_time.is_equal_or_earlier_than(&reference_time))
     }
 
     #[test]
     fn appointment_time_should_not_be_passed() {
-        let future_appointment_time = AppointmentTime::now() - 5;
-        assert!(future_appointment_time.is_equal_or_earlier_than(&AppointmentTime::now()))
+        let reference_time = AppointmentTime::new(14, 30).unwrap();
+        let future_appointment_time = reference_time.clone() - Duration::from_minutes(5);
+        assert!(future_appointment_time.is_equal_or_earlier_than(&reference_time))
     }
 
     #[test]
@@ -0,0 +1,661 @@
+use std::{cmp::Ordering, path::PathBuf, time};
+
+use chrono::{Days, NaiveDate};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+use super::{
+    helper::{self, Config},
+    journal::{self, Journal},
+    list::AppointmentList,
+    natural,
+    recurrence::RecurrenceList,
+    time::{AppointmentDateTime, AppointmentTime},
+    zone::Zone,
+    Appointment, AppointmentChanges, Status,
+};
+
+/// Keys shown at the bottom of the screen when nothing else is
+const HINTS: &str =
+    "j/k move  h/l day  t today  a add  e edit  x remove  d done  s skip  c cancel  u undo  q quit";
+
+/// Describe what the keys typed are for
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Keys move around and change the selected appointment
+    Normal,
+    /// Keys write a new appointment, like `14:30-15:00 Review #work`
+    Add(String),
+    /// Keys rewrite the selected appointment, starting from its current text
+    Edit(String),
+    /// `y` removes the selected appointment, any other key keeps it
+    ConfirmRemove,
+}
+
+/// Describe the state of the terminal UI: the day shown, its appointments and what is selected
+struct App<'a> {
+    config: &'a Config,
+    /// Time zone of the appointments without one, see `Config::appointments_shown_on`
+    default_zone: Option<Zone>,
+    date: NaiveDate,
+    now: AppointmentDateTime,
+    /// Appointments shown on the date, each with the date of the file it is stored in
+    appointments: Vec<(NaiveDate, Appointment)>,
+    selected: usize,
+    mode: Mode,
+    /// Result of the last action, shown instead of the key hints
+    message: Option<Result<String, String>>,
+    quit: bool,
+}
+
+/// Opens the terminal UI on a date until `q` is pressed. The screen is refreshed every minute, so
+/// appointments are marked as passed while it is open. Appointments are shown in the current time
+/// zone like `list` does, the ones without a time zone being in the default one
+pub fn run(config: &Config, date: NaiveDate, default_zone: Option<Zone>) -> Result<(), String> {
+    let mut terminal =
+        ratatui::try_init().map_err(|error| format!("Failed to open the terminal UI. {error}"))?;
+    let result = event_loop(&mut terminal, config, date, default_zone);
+    ratatui::restore();
+    result
+}
+
+/// Draws the UI and handles the keys until the user quits
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    config: &Config,
+    date: NaiveDate,
+    default_zone: Option<Zone>,
+) -> Result<(), String> {
+    let failed = |error| format!("The terminal UI stopped working. {error}");
+    let mut app = App::new(config, date, AppointmentDateTime::now(), default_zone);
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame)).map_err(failed)?;
+        let until_next_minute = 60 - u64::from(app.now.second());
+        if event::poll(time::Duration::from_secs(until_next_minute)).map_err(failed)? {
+            if let Event::Key(key) = event::read().map_err(failed)? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key);
+                }
+            }
+        }
+        app.refresh(AppointmentDateTime::now());
+    }
+    Ok(())
+}
+
+impl<'a> App<'a> {
+    fn new(
+        config: &'a Config,
+        date: NaiveDate,
+        now: AppointmentDateTime,
+        default_zone: Option<Zone>,
+    ) -> Self {
+        let mut app = Self {
+            config,
+            default_zone,
+            date,
+            now,
+            appointments: vec![],
+            selected: 0,
+            mode: Mode::Normal,
+            message: None,
+            quit: false,
+        };
+        app.reload();
+        app
+    }
+
+    /// Updates the current time and reloads the appointments, following the current day when it
+    /// changes while today is shown
+    fn refresh(&mut self, now: AppointmentDateTime) {
+        if self.date == self.now.date() {
+            self.date = now.date();
+        }
+        self.now = now;
+        self.reload();
+    }
+
+    /// Reads the appointments of the date again, keeping the selection inside the list. A file
+    /// of the date in the legacy format is migrated, like `list` does
+    fn reload(&mut self) {
+        let reference_time = self.reference_time();
+        let path = (self.config.appointment_file_path_builder)(self.date);
+        let recurrences = RecurrenceList::new(&self.config.recurrence_file_path);
+        let list = list_for_date(&reference_time, &path, &recurrences, self.date, self.now);
+        if list.is_legacy_format() {
+            self.message = Some(list.write().map(|_| {
+                format!(
+                    "{} was in the legacy format and has been migrated.",
+                    path.display()
+                )
+            }));
+        }
+        self.appointments =
+            self.config
+                .appointments_shown_on(&recurrences, self.date, self.now, self.default_zone);
+        self.appointments.sort_by(|(_, a), (_, b)| a.cmp(b));
+        self.selected = self.selected.min(self.appointments.len().saturating_sub(1));
+    }
+
+    /// Returns the time the appointments of the date are compared to. Past dates are compared
+    /// against the end of the day and future dates against its start
+    fn reference_time(&self) -> AppointmentTime {
+        match self.date.cmp(&self.now.date()) {
+            Ordering::Less => AppointmentTime::max_value(),
+            Ordering::Equal => self.now.time(),
+            Ordering::Greater => AppointmentTime::min_value(),
+        }
+    }
+
+    /// Shows another date, starting from its first appointment
+    fn show_date(&mut self, date: NaiveDate) {
+        self.date = date;
+        self.selected = 0;
+        self.reload();
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+        match self.mode.clone() {
+            Mode::Normal => self.handle_normal_key(key.code),
+            Mode::Add(text) | Mode::Edit(text) => self.handle_input_key(key.code, text),
+            Mode::ConfirmRemove => {
+                self.mode = Mode::Normal;
+                if key.code == KeyCode::Char('y') {
+                    let result = self.remove();
+                    self.finish(result);
+                }
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, code: KeyCode) {
+        self.message = None;
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('j') | KeyCode::Down if self.selected + 1 < self.appointments.len() => {
+                self.selected += 1
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Char('h') | KeyCode::Left => {
+                if let Some(date) = self.date.checked_sub_days(Days::new(1)) {
+                    self.show_date(date);
+                }
+            }
+            KeyCode::Char('l') | KeyCode::Right => {
+                if let Some(date) = self.date.checked_add_days(Days::new(1)) {
+                    self.show_date(date);
+                }
+            }
+            KeyCode::Char('t') | KeyCode::Home => self.show_date(self.now.date()),
+            KeyCode::Char('a') => self.mode = Mode::Add(String::new()),
+            KeyCode::Char('e') => {
+                if let Some((_, appointment)) = self.appointments.get(self.selected) {
+                    self.mode = Mode::Edit(appointment.to_string());
+                }
+            }
+            KeyCode::Char('x') | KeyCode::Delete if self.selected < self.appointments.len() => {
+                self.mode = Mode::ConfirmRemove
+            }
+            KeyCode::Char('d') => self.change_status(Status::Done),
+            KeyCode::Char('s') => self.change_status(Status::Skipped),
+            KeyCode::Char('c') => self.change_status(Status::Cancelled),
+            KeyCode::Char('u') => {
+                let result = Journal::new(&self.config.journal_file_path)
+                    .undo()
+                    .map(|operation| format!("Undone: {} {}", operation.name, operation.summary));
+                self.finish(result);
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_input_key(&mut self, code: KeyCode, mut text: String) {
+        match code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                return;
+            }
+            KeyCode::Enter => {
+                let result = match self.mode {
+                    Mode::Add(..) => self.add(&text),
+                    _ => self.edit(&text),
+                };
+                if result.is_ok() {
+                    self.mode = Mode::Normal;
+                }
+                self.message = Some(result);
+                return;
+            }
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(character) => text.push(character),
+            _ => (),
+        }
+        self.mode = match self.mode {
+            Mode::Add(..) => Mode::Add(text),
+            _ => Mode::Edit(text),
+        };
+    }
+
+    /// Reloads the appointments after an action and shows its result
+    fn finish(&mut self, result: Result<String, String>) {
+        self.reload();
+        self.message = Some(result);
+    }
+
+    /// Adds an appointment written like in `add --stdin`, showing the day it was added to
+    fn add(&mut self, text: &str) -> Result<String, String> {
+        let (date, appointment) = natural::parse_appointment_in_zone(
+            text,
+            self.date,
+            self.now.naive(),
+            None,
+            self.default_zone,
+        )?;
+        let (shown_on, shown) = appointment.in_zone(date, self.default_zone, &Zone::current());
+        if shown.starts_at(shown_on) <= self.now {
+            return Err(String::from("Given time already passed."));
+        }
+        let reference_time = AppointmentTime::min_value();
+        let path = (self.config.appointment_file_path_builder)(date);
+        let recurrences = RecurrenceList::new(&self.config.recurrence_file_path);
+        let summary = format!("[{}] {}", shown.time(), shown.description());
+        let mut list = list_for_date(&reference_time, &path, &recurrences, date, self.now);
+        list.add(appointment)?;
+        self.date = shown_on;
+        self.reload();
+        self.selected = self
+            .appointments
+            .iter()
+            .position(|(_, a)| a.time() == shown.time() && a.description() == shown.description())
+            .unwrap_or(0);
+        Ok(format!("Added {summary}."))
+    }
+
+    /// Rewrites the selected appointment. Occurrences are stored as regular appointments and
+    /// skipped on their recurrence
+    fn edit(&mut self, text: &str) -> Result<String, String> {
+        let (day, selected) = self.selected_appointment()?;
+        let (_, edited) = natural::parse_appointment(text, self.date, self.now.naive())?;
+        let reference_time = self.reference_time();
+        let path = (self.config.appointment_file_path_builder)(day);
+        let mut recurrences = RecurrenceList::new(&self.config.recurrence_file_path);
+        let mut list = list_for_date(&reference_time, &path, &recurrences, day, self.now);
+        // The time is written as shown, in the current time zone
+        let stored = list
+            .appointments()
+            .iter()
+            .find(|a| match selected.recurrence_id() {
+                Some(recurrence_id) => a.recurrence_id() == Some(recurrence_id),
+                None => a.recurrence_id().is_none() && a.id() == selected.id(),
+            });
+        let zone = stored
+            .and_then(Appointment::zone)
+            .or(self.default_zone)
+            .unwrap_or(Zone::current());
+        let time = Zone::current()
+            .convert(AppointmentDateTime::new(self.date, edited.time()), &zone)
+            .time();
+        let changes = AppointmentChanges {
+            time: Some(time),
+            description: Some(edited.description().to_string()),
+            duration: edited.duration(),
+            tags: Some(edited.tags().to_vec()),
+            ..Default::default()
+        };
+        match selected.recurrence_id() {
+            Some(recurrence_id) => {
+                list.edit_occurrence(recurrence_id, changes)?;
+                recurrences.skip(recurrence_id, day)?;
+            }
+            None => list.edit_by_id(selected.id(), changes)?,
+        }
+        self.reload();
+        Ok(String::from("Appointment changed."))
+    }
+
+    /// Removes the selected appointment. Occurrences are skipped on their recurrence instead
+    fn remove(&mut self) -> Result<String, String> {
+        let (day, selected) = self.selected_appointment()?;
+        let reference_time = self.reference_time();
+        let path = (self.config.appointment_file_path_builder)(day);
+        let mut recurrences = RecurrenceList::new(&self.config.recurrence_file_path);
+        if let Some(recurrence_id) = selected.recurrence_id() {
+            recurrences.skip(recurrence_id, day)?;
+            return Ok(String::from("Occurrence skipped."));
+        }
+        let mut list = list_for_date(&reference_time, &path, &recurrences, day, self.now);
+        list.remove_by_id(selected.id())?;
+        Ok(String::from("Appointment removed."))
+    }
+
+    /// Records what happened to the selected appointment, see `set_status`
+    fn change_status(&mut self, status: Status) {
+        let result = self.set_status(status);
+        self.finish(result);
+    }
+
+    /// Records what happened to the selected appointment. Occurrences are stored as regular
+    /// appointments and skipped on their recurrence
+    fn set_status(&mut self, status: Status) -> Result<String, String> {
+        let (day, selected) = self.selected_appointment()?;
+        let reference_time = self.reference_time();
+        let path = (self.config.appointment_file_path_builder)(day);
+        let mut recurrences = RecurrenceList::new(&self.config.recurrence_file_path);
+        let mut list = list_for_date(&reference_time, &path, &recurrences, day, self.now);
+        match selected.recurrence_id() {
+            Some(recurrence_id) => {
+                let changes = AppointmentChanges {
+                    status: Some(status),
+                    ..Default::default()
+                };
+                list.edit_occurrence(recurrence_id, changes)?;
+                recurrences.skip(recurrence_id, day)?;
+            }
+            None => list.set_status_by_id(selected.id(), status)?,
+        }
+        Ok(format!("Appointment marked as {status}."))
+    }
+
+    /// Returns the selected appointment, with the date of the file it is stored in
+    fn selected_appointment(&self) -> Result<(NaiveDate, Appointment), String> {
+        self.appointments
+            .get(self.selected)
+            .cloned()
+            .ok_or(String::from("There is no appointment selected."))
+    }
+
+    /// Returns the lines of the list with the index of the appointment they show. A line without
+    /// appointment marks the current time when today is shown
+    fn rows(&self) -> Vec<(Option<usize>, Line<'static>)> {
+        let reference_time = self.reference_time();
+        let mut rows = vec![];
+        let mut marked = self.date != self.now.date();
+        for (index, (_, appointment)) in self.appointments.iter().enumerate() {
+            if !marked && appointment.time() > &reference_time {
+                rows.push((None, now_marker(&reference_time)));
+                marked = true;
+            }
+            rows.push((Some(index), appointment_line(appointment, &self.now)));
+        }
+        if !marked {
+            rows.push((None, now_marker(&reference_time)));
+        }
+        rows
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let mut title = vec![Span::from(format!(
+            " {} {} ",
+            self.date.format("%A"),
+            helper::format_date(self.date)
+        ))
+        .bold()];
+        if self.date == self.now.date() {
+            title.push(Span::from("(today) "));
+        }
+        let block = Block::bordered()
+            .title_top(Line::from(title))
+            .title_top(Line::from(format!(" {} ", self.now.time())).right_aligned());
+
+        if self.appointments.is_empty() {
+            let empty = Paragraph::new("No appointments for this day. Press a to add one.")
+                .dim()
+                .block(block);
+            frame.render_widget(empty, body);
+        } else {
+            let rows = self.rows();
+            let position = rows
+                .iter()
+                .position(|(index, _)| *index == Some(self.selected));
+            let items: Vec<ListItem> = rows
+                .into_iter()
+                .map(|(_, line)| ListItem::new(line))
+                .collect();
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            let mut state = ListState::default().with_selected(position);
+            frame.render_stateful_widget(list, body, &mut state);
+        }
+
+        let footer_line = match &self.mode {
+            Mode::Add(text) | Mode::Edit(text) => {
+                let label = match self.mode {
+                    Mode::Add(..) => "Add",
+                    _ => "Edit",
+                };
+                let prompt = Line::from(format!("{label}: {text}"));
+                let column = footer.x.saturating_add(prompt.width() as u16);
+                frame.set_cursor_position((column.min(footer.right().saturating_sub(1)), footer.y));
+                match &self.message {
+                    Some(Err(error)) => prompt
+                        .spans
+                        .into_iter()
+                        .chain([Span::from(format!("  {error}")).red()])
+                        .collect(),
+                    _ => prompt,
+                }
+            }
+            Mode::ConfirmRemove => Line::from("Remove the selected appointment? (y/n)"),
+            Mode::Normal => match &self.message {
+                Some(Ok(message)) => Line::from(message.clone()),
+                Some(Err(error)) => Line::from(error.clone()).red(),
+                None => Line::from(HINTS).dim(),
+            },
+        };
+        frame.render_widget(Paragraph::new(footer_line), footer);
+    }
+}
+
+/// Returns the list of appointments of a date compared to the current moment, recording its
+/// changes in the journal
+fn list_for_date<'a>(
+    reference_time: &'a AppointmentTime,
+    path: &'a PathBuf,
+    recurrences: &RecurrenceList,
+    date: NaiveDate,
+    now: AppointmentDateTime,
+) -> AppointmentList<'a> {
+    AppointmentList::new(reference_time, path)
+        .with_occurrences(recurrences.occurrences(date))
+        .with_journal(path.with_file_name(journal::JOURNAL_FILE))
+        .for_date(date, now)
+}
+
+/// Returns an appointment as shown by `Appointment::to_string_display`, with styles instead of
+/// colors
+fn appointment_line(appointment: &Appointment, now: &AppointmentDateTime) -> Line<'static> {
+    let display = format!(
+        "[{}] {}{}",
+        appointment.time_range(),
+        appointment.description(),
+        appointment.tags_suffix()
+    );
+    if !appointment.status().is_pending() {
+        Line::from(vec![
+            Span::from(display).crossed_out(),
+            Span::from(format!(" ({})", appointment.status())),
+        ])
+    } else if appointment.is_in_progress_at(now) {
+        Line::from(vec![Span::from(display), Span::from(" (now)").bold()])
+    } else if appointment.is_passed_at(now) {
+        Line::from(display).dim()
+    } else {
+        Line::from(display)
+    }
+}
+
+/// Returns the line marking the current time between the appointments
+fn now_marker(reference_time: &AppointmentTime) -> Line<'static> {
+    Line::from(format!("── {reference_time} ──")).fg(Color::Yellow)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use ratatui::{
+        backend::TestBackend,
+        crossterm::event::{KeyCode, KeyEvent},
+        Terminal,
+    };
+    use std::fs;
+
+    use super::{App, Mode};
+    use crate::appointment::{
+        helper::Config,
+        list::AppointmentList,
+        time::{AppointmentDateTime, AppointmentTime},
+        Appointment, Status,
+    };
+
+    fn at(hour: i32, minutes: i32) -> AppointmentDateTime {
+        AppointmentDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            &AppointmentTime::new(hour, minutes).unwrap(),
+        )
+    }
+
+    fn config_with(test_name: &str, appointments: &[&str]) -> Config {
+        let dir = std::env::temp_dir().join(format!("todayiwill-tui-{test_name}"));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).expect("Failed to clean test dir");
+        }
+        fs::create_dir_all(&dir).expect("Failed to create test dir");
+        let config = Config::new(dir);
+        let path = (config.appointment_file_path_builder)(at(0, 0).date());
+        let reference_time = AppointmentTime::min_value();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        for appointment in appointments {
+            list.add(Appointment::from(appointment).unwrap()).unwrap();
+        }
+        config
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for character in keys.chars() {
+            let code = match character {
+                '\n' => KeyCode::Enter,
+                character => KeyCode::Char(character),
+            };
+            app.handle_key(KeyEvent::from(code));
+        }
+    }
+
+    #[test]
+    fn rows_should_mark_the_current_time() {
+        let config = config_with("rows", &["08:00 Gym", "09:30 Standup", "11:00 Review"]);
+        let app = App::new(&config, at(0, 0).date(), at(10, 0), None);
+        let rows: Vec<String> = app
+            .rows()
+            .iter()
+            .map(|(_, line)| line.to_string())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "[08:00] Gym",
+                "[09:30] Standup",
+                "── 10:00 ──",
+                "[11:00] Review"
+            ]
+        );
+        let app = App::new(
+            &config,
+            at(0, 0).date().succ_opt().unwrap(),
+            at(10, 0),
+            None,
+        );
+        assert!(app.rows().is_empty());
+    }
+
+    #[test]
+    fn rows_should_show_what_goes_on_from_the_day_before() {
+        let config = config_with("overnight", &[]);
+        let yesterday = at(0, 0).date().pred_opt().unwrap();
+        let path = (config.appointment_file_path_builder)(yesterday);
+        let reference_time = AppointmentTime::min_value();
+        let mut list = AppointmentList::new(&reference_time, &path);
+        list.add(Appointment::from("22:00-06:00 Night shift").unwrap())
+            .unwrap();
+        let mut app = App::new(&config, at(0, 0).date(), at(1, 0), None);
+        assert_eq!(
+            vec![(yesterday, 22)],
+            app.appointments
+                .iter()
+                .map(|(day, a)| (*day, a.time().hour()))
+                .collect::<Vec<_>>()
+        );
+        press(&mut app, "d");
+        let list = AppointmentList::new(&reference_time, &path);
+        assert_eq!(Status::Done, list.appointments()[0].status());
+    }
+
+    #[test]
+    fn keys_should_change_the_appointments() {
+        let config = config_with("keys", &["09:30 Standup", "11:00 Review"]);
+        let mut app = App::new(&config, at(0, 0).date(), at(10, 0), None);
+        press(&mut app, "a14:00-15:00 Workshop #rust\n");
+        assert_eq!(Mode::Normal, app.mode);
+        assert_eq!(
+            "14:00-15:00 Workshop #rust",
+            app.appointments[app.selected].1.to_string()
+        );
+
+        press(&mut app, "kd");
+        assert_eq!(Status::Done, app.appointments[1].1.status());
+        press(&mut app, "jxy");
+        assert_eq!(2, app.appointments.len());
+        press(&mut app, "e");
+        assert_eq!(Mode::Edit(String::from("11:00 Review")), app.mode);
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        press(&mut app, "ws\n");
+        assert_eq!("[11:00] Reviews (done)", app.rows()[2].1.to_string());
+
+        press(&mut app, "k");
+        press(&mut app, "a08:00 Too late\n");
+        assert_eq!(
+            Some(Err(String::from("Given time already passed."))),
+            app.message
+        );
+        app.handle_key(KeyEvent::from(KeyCode::Esc));
+
+        press(&mut app, "l");
+        assert!(app.appointments.is_empty());
+        press(&mut app, "t");
+        assert_eq!(2, app.appointments.len());
+    }
+
+    #[test]
+    fn draw_should_show_the_day() {
+        let config = config_with("draw", &["09:30 Standup"]);
+        let app = App::new(&config, at(0, 0).date(), at(10, 0), None);
+        let mut terminal = Terminal::new(TestBackend::new(40, 5)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let lines: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect();
+        assert!(lines[0].contains("Monday 10/06/2024 (today)"));
+        assert!(lines[0].contains("10:00"));
+        assert!(lines[1].contains("[09:30] Standup"));
+        assert!(lines[2].contains("── 10:00 ──"));
+    }
+}
@@ -0,0 +1,271 @@
+use std::{cmp::Ordering, fmt, str, sync::OnceLock};
+
+use chrono::{
+    DateTime, Local, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::{Tz, TZ_VARIANTS};
+use serde::{de, Deserialize, Deserializer};
+
+use super::{duration::Duration, time::AppointmentDateTime};
+
+/// Time zone configured as the current one for the process, see `Zone::set_current`
+static CURRENT_ZONE: OnceLock<Zone> = OnceLock::new();
+
+/// Describe the time zone the appointments are shown in, or the one their times are written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Zone {
+    /// The time zone of the system
+    #[default]
+    Local,
+    /// A time zone of the IANA database, like `Europe/Berlin`
+    Named(Tz),
+}
+
+impl Zone {
+    /// Parses a time zone from its IANA name, or `local` for the time zone of the system
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::Zone;
+    ///
+    /// assert_eq!(Ok(Zone::Local), Zone::from("local"));
+    /// assert_eq!("America/Sao_Paulo", Zone::from("america/sao_paulo").unwrap().to_string());
+    /// assert!(Zone::from("Mars/Olympus").is_err());
+    /// ```
+    pub fn from(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("local") {
+            return Ok(Zone::Local);
+        }
+        TZ_VARIANTS
+            .iter()
+            .find(|tz| tz.name().eq_ignore_ascii_case(name))
+            .map(|tz| Zone::Named(*tz))
+            .ok_or_else(|| {
+                format!("Unknown time zone \"{name}\", use an IANA name like \"Europe/Berlin\"")
+            })
+    }
+
+    /// Sets the time zone considered the current one for the rest of the process, used for the
+    /// current date and time. Only the first call has effect
+    pub fn set_current(zone: Zone) {
+        let _ = CURRENT_ZONE.set(zone);
+    }
+
+    /// Returns the time zone considered the current one, the time zone of the system unless
+    /// another was set with `Zone::set_current`
+    pub fn current() -> Zone {
+        CURRENT_ZONE.get().copied().unwrap_or_default()
+    }
+
+    /// Returns the current date and time in the zone
+    pub fn now(&self) -> AppointmentDateTime {
+        AppointmentDateTime::from_naive(self.date_time_at(Utc::now()))
+    }
+
+    /// Returns the current date in the zone
+    pub fn today(&self) -> NaiveDate {
+        self.now().date()
+    }
+
+    /// Converts a date and time written in the zone to another zone. Times skipped by a daylight
+    /// saving transition are moved forward by the transition, times repeated by one are taken
+    /// the first time they happen
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentDateTime, AppointmentTime, Zone};
+    /// use chrono::NaiveDate;
+    ///
+    /// let berlin = Zone::from("Europe/Berlin").unwrap();
+    /// let new_york = Zone::from("America/New_York").unwrap();
+    /// let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    /// let at = AppointmentDateTime::new(date, &AppointmentTime::new(3, 0).unwrap());
+    /// assert_eq!("2024-06-09 21:00:00", berlin.convert(at, &new_york).to_string());
+    /// ```
+    pub fn convert(&self, date_time: AppointmentDateTime, to: &Zone) -> AppointmentDateTime {
+        if self == to {
+            return date_time;
+        }
+        AppointmentDateTime::from_naive(to.date_time_at(self.instant_of(date_time.naive())))
+    }
+
+    /// Returns the date and time of the zone when the duration has elapsed since the given one.
+    /// Unlike adding the duration to it, daylight saving transitions are considered
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use todayiwill::{AppointmentDateTime, AppointmentTime, Duration, Zone};
+    /// use chrono::NaiveDate;
+    ///
+    /// let new_york = Zone::from("America/New_York").unwrap();
+    /// let date = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+    /// let at = AppointmentDateTime::new(date, &AppointmentTime::new(1, 30).unwrap());
+    /// assert_eq!("2024-03-10 03:30:00", new_york.advance(at, Duration::from_minutes(60)).to_string());
+    /// ```
+    pub fn advance(
+        &self,
+        date_time: AppointmentDateTime,
+        duration: Duration,
+    ) -> AppointmentDateTime {
+        let instant =
+            self.instant_of(date_time.naive()) + TimeDelta::seconds(duration.seconds().into());
+        AppointmentDateTime::from_naive(self.date_time_at(instant))
+    }
+
+    /// Returns the instant a date and time of the zone happens
+    fn instant_of(&self, date_time: NaiveDateTime) -> DateTime<Utc> {
+        match self {
+            Zone::Local => resolve(&Local, date_time).with_timezone(&Utc),
+            Zone::Named(tz) => resolve(tz, date_time).with_timezone(&Utc),
+        }
+    }
+
+    /// Returns the date and time of the zone at an instant
+    fn date_time_at(&self, instant: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Local => instant.with_timezone(&Local).naive_local(),
+            Zone::Named(tz) => instant.with_timezone(tz).naive_local(),
+        }
+    }
+}
+
+/// Returns the first instant a date and time happens in a time zone. Times skipped by a transition
+/// are read with the offset from before it, which moves them forward by the transition
+fn resolve<Z: TimeZone>(zone: &Z, date_time: NaiveDateTime) -> DateTime<Z> {
+    match zone.from_local_datetime(&date_time) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => time,
+        LocalResult::None => {
+            let offset = zone
+                .offset_from_utc_datetime(&(date_time - TimeDelta::days(1)))
+                .fix();
+            let utc = date_time - TimeDelta::seconds(offset.local_minus_utc() as i64);
+            zone.from_utc_datetime(&utc)
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Zone::Local => write!(f, "local"),
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl PartialOrd for Zone {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Zone {
+    /// Orders the time zones by name, with the time zone of the system first
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Zone::Local, Zone::Local) => Ordering::Equal,
+            (Zone::Local, Zone::Named(..)) => Ordering::Less,
+            (Zone::Named(..), Zone::Local) => Ordering::Greater,
+            (Zone::Named(tz), Zone::Named(other)) => tz.name().cmp(other.name()),
+        }
+    }
+}
+
+impl str::FromStr for Zone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Zone::from(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Zone {
+    /// Reads a time zone from its name
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Zone::from(&name).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::Zone;
+    use crate::appointment::duration::Duration;
+    use crate::appointment::time::{AppointmentDateTime, AppointmentTime};
+
+    fn at(year: i32, month: u32, day: u32, hour: i32, minutes: i32) -> AppointmentDateTime {
+        AppointmentDateTime::new(
+            NaiveDate::from_ymd_opt(year, month, day).unwrap(),
+            &AppointmentTime::new(hour, minutes).unwrap(),
+        )
+    }
+
+    #[test]
+    fn convert_between_zones() {
+        let sao_paulo = Zone::from("America/Sao_Paulo").unwrap();
+        let tokyo = Zone::from("Asia/Tokyo").unwrap();
+        assert_eq!(
+            sao_paulo.convert(at(2024, 6, 10, 22, 0), &tokyo),
+            at(2024, 6, 11, 10, 0)
+        );
+        assert_eq!(
+            tokyo.convert(at(2024, 6, 11, 10, 0), &sao_paulo),
+            at(2024, 6, 10, 22, 0)
+        );
+        assert_eq!(
+            tokyo.convert(at(2024, 6, 11, 10, 0), &tokyo),
+            at(2024, 6, 11, 10, 0)
+        );
+    }
+
+    #[test]
+    fn convert_across_daylight_saving_transitions() {
+        let berlin = Zone::from("Europe/Berlin").unwrap();
+        let utc = Zone::from("UTC").unwrap();
+        // 02:30 does not exist on the last sunday of march and is moved to 03:30
+        assert_eq!(
+            berlin.convert(at(2024, 3, 31, 2, 30), &utc),
+            at(2024, 3, 31, 1, 30)
+        );
+        // 02:30 happens twice on the last sunday of october, the first time is taken
+        assert_eq!(
+            berlin.convert(at(2024, 10, 27, 2, 30), &utc),
+            at(2024, 10, 27, 0, 30)
+        );
+        assert_eq!(
+            utc.convert(at(2024, 10, 27, 1, 30), &berlin),
+            at(2024, 10, 27, 2, 30)
+        );
+    }
+
+    #[test]
+    fn advance_across_daylight_saving_transitions() {
+        let berlin = Zone::from("Europe/Berlin").unwrap();
+        assert_eq!(
+            berlin.advance(at(2024, 10, 27, 1, 30), Duration::from_minutes(120)),
+            at(2024, 10, 27, 2, 30)
+        );
+        assert_eq!(
+            berlin.advance(at(2024, 6, 10, 23, 30), Duration::from_minutes(60)),
+            at(2024, 6, 11, 0, 30)
+        );
+    }
+
+    #[test]
+    fn parse_zones() {
+        assert_eq!(Zone::from(" Local "), Ok(Zone::Local));
+        assert_eq!("Asia/Tokyo", Zone::from("asia/tokyo").unwrap().to_string());
+        assert_eq!(
+            Zone::from("Nowhere"),
+            Err(String::from(
+                "Unknown time zone \"Nowhere\", use an IANA name like \"Europe/Berlin\""
+            ))
+        );
+    }
+}
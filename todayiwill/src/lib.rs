@@ -2,6 +2,12 @@ pub mod appointment;
 
 // Re-exports
 pub use appointment::{
-    helper, helper::Config, list::AppointmentList, list::FilterOption, time::AppointmentTime,
-    Appointment,
+    agenda, duration::Duration, helper, helper::Config, ics, journal, journal::Journal,
+    list::AppointmentList, list::FilterOption, natural, natural::TimeExpression, next,
+    next::Upcoming, output, output::OutputFormat, recurrence::Recurrence,
+    recurrence::RecurrenceList, recurrence::RecurrenceRule, reminder, reminder::CommandSink,
+    reminder::FifoSink, reminder::NotificationSink, reminder::TerminalSink, search::Query,
+    settings::CalendarSettings, settings::ColorChoice, settings::Settings, status::Status, storage,
+    time::AppointmentDateTime, time::AppointmentTime, tui, zone::Zone, Appointment,
+    AppointmentChanges, Metadata,
 };
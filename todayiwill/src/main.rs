@@ -1,13 +1,36 @@
-use std::{io, process};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env, fs,
+    io::{self, IsTerminal, Read},
+    path::{Path, PathBuf},
+    process, thread,
+    time::{self, SystemTime},
+};
 
-use chrono::NaiveDate;
-use clap::{Parser, Subcommand};
+use chrono::{Days, NaiveDate, NaiveDateTime};
+use clap::{Args, Parser, Subcommand};
 
 extern crate chrono;
 extern crate dirs;
 
-use colored::Colorize;
-use todayiwill::{helper, Appointment, AppointmentList, AppointmentTime, Config, FilterOption};
+use colored::{Color, Colorize};
+use todayiwill::{
+    agenda::{self, Period},
+    helper, ics,
+    journal::{self, Operation},
+    natural, next, output, reminder,
+    storage::{self, InvalidLine},
+    tui, Appointment, AppointmentChanges, AppointmentDateTime, AppointmentList, AppointmentTime,
+    CommandSink, Config, Duration, FifoSink, FilterOption, Journal, NotificationSink, OutputFormat,
+    Query, RecurrenceList, RecurrenceRule, Settings, Status, TerminalSink, TimeExpression,
+    Upcoming, Zone,
+};
+
+/// Interval used by `watch` for checking changes on the appointment files
+const WATCH_POLL_INTERVAL: time::Duration = time::Duration::from_secs(2);
+
+/// How long before the appointments `watch` sends the reminders when nothing is configured
+const DEFAULT_LEAD_TIME: Duration = Duration::from_minutes(5);
 
 /// A CLI for remembering what you need to do today
 #[derive(Debug, Parser)]
@@ -18,55 +41,423 @@ struct Cli {
     command: Commands,
 
     /// Current time, defaults to system time
-    #[arg(short, long, global=true, default_value_t=AppointmentTime::now(), value_name = "HH:MM")]
-    current_time: AppointmentTime,
+    #[arg(short, long, global = true, value_name = "HH:MM")]
+    current_time: Option<AppointmentTime>,
+
+    /// Time zone the appointments are shown in (Europe/Berlin, local), overrides the config file
+    #[arg(long, global = true, value_name = "ZONE")]
+    tz: Option<Zone>,
+
+    /// Directory where the appointments are stored, overrides $TODAYIWILL_DATA_DIR and the config file
+    #[arg(long, global = true, value_name = "PATH")]
+    data_dir: Option<PathBuf>,
+
+    /// Calendar the appointments belong to, each calendar is stored in its own directory
+    #[arg(long, global = true, default_value = helper::DEFAULT_CALENDAR, value_name = "NAME")]
+    calendar: String,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
-    /// Add appointment for today
+    /// Add appointment for today or a future date
     Add {
         /// Appointment description
         #[arg(short, long, required_unless_present("stdin"), value_name = "STRING")]
         description: Option<String>,
 
-        /// Appointment time
+        /// Appointment time (14:30, 9am, 2:30pm, noon, in 45m, in 2h)
         #[arg(short, long, required_unless_present("stdin"), value_name = "HH:MM")]
-        time: Option<AppointmentTime>,
+        time: Option<TimeExpression>,
 
-        /// Parses an appointment as a string from STDIN ("hh:mm appointment content")
+        /// Parses the appointments from STDIN, one per line ("hh:mm appointment content", "hh:mm-hh:mm appointment content" or "tomorrow 3pm appointment content")
         #[arg(long, conflicts_with_all(["description", "time"]))]
         stdin: bool,
+
+        /// Adds the valid lines from STDIN even if others are invalid, instead of adding none
+        #[arg(long, requires("stdin"))]
+        partial: bool,
+
+        /// Appointment date (DD/MM/YYYY, today, tomorrow, +3d, next monday), defaults to today
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        date: Option<NaiveDate>,
+
+        /// Replaces the appointments at the same time instead of keeping them
+        #[arg(long)]
+        replace: bool,
+
+        /// Time the appointment ends
+        #[arg(long, conflicts_with_all(["stdin", "duration"]), value_name = "HH:MM")]
+        until: Option<AppointmentTime>,
+
+        /// Duration of the appointment (90m, 2h, 1h30m)
+        #[arg(long = "for", conflicts_with("stdin"), value_parser=helper::parse_appointment_duration, value_name = "DURATION")]
+        duration: Option<Duration>,
+
+        /// Tag of the appointment, can be repeated. Tags can also be written at the end of the description (#work)
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        tag: Vec<String>,
+
+        /// Time zone the time is written in (America/New_York), defaults to the one of the calendar
+        #[arg(long, value_name = "ZONE")]
+        zone: Option<Zone>,
     },
     /// Copies the appointments from a specific date to today
     Copy {
         /// Date wich the appointments will be copied from
-        #[arg(short, long, value_parser=helper::str_dmy_to_naive_date, value_name = "DD/MM/YYYY")]
+        #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
         from: NaiveDate,
     },
     /// Clear all the appointments added for today
     Clear,
     /// List the appointments to come for today
     List {
-        /// Show appointments which will expire in X seconds
-        #[arg(short, long, value_name = "SECONDS")]
-        expire_in: Option<i32>,
+        /// Show appointments which will start within the duration (90s, 15m, 1h30m), a number
+        /// without unit is in minutes
+        #[arg(short, long, value_name = "DURATION")]
+        expire_in: Option<Duration>,
 
         /// If informed, all appointments are retrieved
         #[arg(short, long)]
         all: bool,
+
+        /// Prints the appointments for other programs (json, csv, tsv, plain)
+        #[arg(short, long, value_name = "FORMAT")]
+        format: Option<OutputFormat>,
+
+        /// Lists the appointments of every calendar, prefixed with the calendar name
+        #[arg(long, conflicts_with("format"))]
+        all_calendars: bool,
+
+        /// Shows only the appointments with the tag, can be repeated
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        tag: Vec<String>,
+
+        /// Hides the appointments with the tag, can be repeated
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        exclude_tag: Vec<String>,
+    },
+    /// Prints only the next appointment, for shell prompts and status bars
+    ///
+    /// Exits with 3 when there is none, 4 when it starts soon and 5 when it is happening now
+    Next {
+        /// Template of the line printed, with {time}, {end}, {desc}, {mins} and {tags}. Defaults
+        /// to the config file or "{time} {desc} (in {mins}m)"
+        #[arg(short, long, value_name = "TEMPLATE")]
+        template: Option<String>,
+
+        /// How long before the start the appointment is soon (90s, 15m), a number without unit is
+        /// in minutes. Defaults to the lead time of the config file or 5 minutes
+        #[arg(short, long, value_name = "DURATION")]
+        soon: Option<Duration>,
+
+        /// Considers only the appointments with the tag, can be repeated
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        tag: Vec<String>,
+
+        /// Ignores the appointments with the tag, can be repeated
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        exclude_tag: Vec<String>,
     },
     /// List the appointments for other days
     History {
-        /// Show appointments which will expire in X seconds
-        #[arg(short, long, value_parser=helper::str_dmy_to_naive_date, value_name = "DD/MM/YYYY")]
+        /// Date of the appointments (DD/MM/YYYY, yesterday, -2d, last monday)
+        #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
         date: NaiveDate,
+
+        /// Prints the appointments for other programs (json, csv, tsv, plain)
+        #[arg(short, long, value_name = "FORMAT")]
+        format: Option<OutputFormat>,
+
+        /// Shows only the appointments with the tag, can be repeated
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        tag: Vec<String>,
+
+        /// Hides the appointments with the tag, can be repeated
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        exclude_tag: Vec<String>,
+    },
+    /// Shows the appointments of a week, a month or a number of days
+    Agenda {
+        /// Shows the week of the first date, from monday to sunday (default)
+        #[arg(long, conflicts_with_all(["month", "days"]))]
+        week: bool,
+
+        /// Shows the month of the first date
+        #[arg(long, conflicts_with("days"))]
+        month: bool,
+
+        /// Shows a number of days starting at the first date
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=366), value_name = "N")]
+        days: Option<u32>,
+
+        /// First date (DD/MM/YYYY, tomorrow, +1w, next monday), defaults to today
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        from: Option<NaiveDate>,
+
+        /// Shows a calendar grid marking the days with appointments with "*"
+        #[arg(long)]
+        grid: bool,
+
+        /// Shows the days without appointments instead of leaving them out
+        #[arg(long, conflicts_with("grid"))]
+        show_empty: bool,
+    },
+    /// Searches the descriptions of the appointments of every day
+    Search {
+        /// Text contained in the descriptions, or a regular expression with --regex
+        query: String,
+
+        /// Matches the text in any case
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Takes the query as a regular expression
+        #[arg(short = 'e', long)]
+        regex: bool,
+
+        /// First date searched (DD/MM/YYYY, yesterday, -2w, last monday)
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        since: Option<NaiveDate>,
+
+        /// Last date searched (DD/MM/YYYY, today, +2w, next monday)
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        until: Option<NaiveDate>,
     },
     /// Removes a future appointment
     Remove {
+        /// Appointment time
+        #[arg(
+            short,
+            long,
+            required_unless_present("id"),
+            conflicts_with("id"),
+            value_name = "HH:MM"
+        )]
+        time: Option<AppointmentTime>,
+
+        /// Appointment id, needed when there are several appointments at the same time
+        #[arg(long)]
+        id: Option<u32>,
+
+        /// Appointment date (DD/MM/YYYY, today, tomorrow, +3d, next monday), defaults to today
+        #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        date: Option<NaiveDate>,
+    },
+    /// Changes an appointment, or all the appointments of a day with $EDITOR
+    Edit {
+        /// Appointment time
+        #[arg(
+            short,
+            long,
+            required_unless_present_any(["id", "editor"]),
+            conflicts_with("id"),
+            value_name = "HH:MM"
+        )]
+        time: Option<AppointmentTime>,
+
+        /// Appointment id, needed when there are several appointments at the same time
+        #[arg(long)]
+        id: Option<u32>,
+
+        /// Appointment date (DD/MM/YYYY, yesterday, tomorrow, -2d), defaults to today
+        #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        date: Option<NaiveDate>,
+
+        /// New appointment time
+        #[arg(long, value_name = "HH:MM")]
+        new_time: Option<AppointmentTime>,
+
+        /// New appointment description
+        #[arg(long, value_name = "STRING")]
+        description: Option<String>,
+
+        /// New time the appointment ends
+        #[arg(long, conflicts_with("duration"), value_name = "HH:MM")]
+        until: Option<AppointmentTime>,
+
+        /// New duration of the appointment (90m, 2h, 1h30m)
+        #[arg(long = "for", value_parser=helper::parse_appointment_duration, value_name = "DURATION")]
+        duration: Option<Duration>,
+
+        /// New status of the appointment (pending, done, skipped, cancelled)
+        #[arg(long, value_name = "STATUS")]
+        status: Option<Status>,
+
+        /// New tag of the appointment, replacing the current ones, can be repeated
+        #[arg(long, value_parser=helper::parse_tag, value_name = "TAG")]
+        tag: Vec<String>,
+
+        /// Removes the tags of the appointment
+        #[arg(long, conflicts_with("tag"))]
+        clear_tags: bool,
+
+        /// Opens the appointments file of the day in $EDITOR
+        #[arg(long, conflicts_with_all(["time", "id", "new_time", "description", "until", "duration", "status", "tag", "clear_tags"]))]
+        editor: bool,
+    },
+    /// Marks an appointment as done
+    Done(AppointmentSelection),
+    /// Marks an appointment as skipped
+    Skip(AppointmentSelection),
+    /// Marks an appointment as cancelled
+    Cancel(AppointmentSelection),
+    /// Exports the appointments between two dates
+    Export {
+        /// First date exported, defaults to today
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        from: Option<NaiveDate>,
+
+        /// Last date exported, defaults to the first one
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        to: Option<NaiveDate>,
+
+        /// Format of the export
+        #[arg(long, default_value = "ics", value_parser = ["ics"])]
+        format: String,
+    },
+    /// Imports the events of an iCalendar (.ics) file
+    Import {
+        /// Path of the iCalendar file
+        file: PathBuf,
+
+        /// First date imported, defaults to today
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        from: Option<NaiveDate>,
+
+        /// Last date imported, defaults to 30 days after the first one
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        to: Option<NaiveDate>,
+    },
+    /// Manages appointments that repeat over the days
+    Recur {
+        #[command(subcommand)]
+        command: RecurCommands,
+    },
+    /// Keeps running and sends reminders before the appointments of the day
+    Watch {
+        /// How long before the appointment the reminder is sent (90s, 15m), a number without unit
+        /// is in minutes. Defaults to the config file or 5 minutes
+        #[arg(short, long, value_name = "DURATION")]
+        lead_time: Option<Duration>,
+
+        /// Rings the terminal bell on each reminder
+        #[arg(long)]
+        bell: bool,
+
+        /// Runs a shell command on each reminder ($TODAYIWILL_TIME and $TODAYIWILL_DESCRIPTION are set)
+        #[arg(long, value_name = "COMMAND")]
+        exec: Option<String>,
+
+        /// Writes each reminder as a line to a file or FIFO
+        #[arg(long, value_name = "PATH")]
+        fifo: Option<PathBuf>,
+
+        /// Sends the reminders due at the current time and exits
+        #[arg(long)]
+        once: bool,
+    },
+    /// Opens the appointments of the day in an interactive view, used with the keyboard
+    Tui {
+        /// Date shown first (DD/MM/YYYY, tomorrow, +3d), defaults to today
+        #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        date: Option<NaiveDate>,
+    },
+    /// Reverts the last change made to the appointments
+    Undo,
+    /// Applies again the last change reverted by undo
+    Redo,
+    /// Shows the last changes made to the appointments
+    Log {
+        /// Number of changes shown
+        #[arg(short = 'n', long, default_value_t = 10, value_name = "COUNT")]
+        limit: usize,
+    },
+    /// Checks every day file and the recurrences for lines that cannot be read
+    #[command(alias = "check")]
+    Doctor {
+        /// Moves the invalid lines to a file with the .invalid extension next to the original one
+        #[arg(long)]
+        quarantine: bool,
+    },
+}
+
+/// Describe how an appointment is chosen by the commands that change its status
+#[derive(Debug, Args)]
+struct AppointmentSelection {
+    /// Appointment time
+    #[arg(
+        short,
+        long,
+        required_unless_present("id"),
+        conflicts_with("id"),
+        value_name = "HH:MM"
+    )]
+    time: Option<AppointmentTime>,
+
+    /// Appointment id, needed when there are several appointments at the same time
+    #[arg(long)]
+    id: Option<u32>,
+
+    /// Appointment date (DD/MM/YYYY, yesterday, -2d, last monday), defaults to today
+    #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+    date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Subcommand)]
+enum RecurCommands {
+    /// Add a recurring appointment
+    Add {
+        /// Appointment description
+        #[arg(short, long, value_name = "STRING")]
+        description: String,
+
         /// Appointment time
         #[arg(short, long, value_name = "HH:MM")]
         time: AppointmentTime,
+
+        /// Recurrence rule ("daily", "every weekday", "every mon,fri", "every 2nd tuesday", "first day of month")
+        #[arg(short, long, value_name = "RULE")]
+        rule: RecurrenceRule,
+
+        /// Date of the first occurrence, defaults to today
+        #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        start: Option<NaiveDate>,
+    },
+    /// List the recurring appointments
+    List,
+    /// Removes a recurring appointment and all its occurrences
+    Remove {
+        /// Recurrence id
+        #[arg(short, long)]
+        id: u32,
+    },
+    /// Skips a single occurrence of a recurring appointment
+    Skip {
+        /// Recurrence id
+        #[arg(short, long)]
+        id: u32,
+
+        /// Date of the occurrence, defaults to today
+        #[arg(short, long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        date: Option<NaiveDate>,
+    },
+    /// Replaces a single occurrence of a recurring appointment by a regular appointment
+    Override {
+        /// Recurrence id
+        #[arg(short, long)]
+        id: u32,
+
+        /// Date of the occurrence, defaults to today
+        #[arg(long, allow_hyphen_values = true, value_parser=helper::str_to_naive_date, value_name = "DD/MM/YYYY")]
+        date: Option<NaiveDate>,
+
+        /// New appointment description, defaults to the recurrence one
+        #[arg(short, long, value_name = "STRING")]
+        description: Option<String>,
+
+        /// New appointment time, defaults to the recurrence one
+        #[arg(short, long, value_name = "HH:MM")]
+        time: Option<AppointmentTime>,
     },
 }
 
@@ -82,49 +473,173 @@ fn main() {
 }
 
 fn parse_input() -> Result<(), String> {
-    let config = Config::standard();
+    let settings = Settings::path()
+        .map(|path| Settings::load(&path))
+        .transpose()
+        .map(Option::unwrap_or_default);
+    // The date format is needed for parsing the dates of the arguments
+    if let Ok(settings) = &settings {
+        if let Some(date_format) = &settings.date_format {
+            helper::set_date_format(date_format);
+        }
+        settings.apply_color();
+    }
     let args = Cli::parse();
+    // The config file is only needed when the data dir is not given some other way
+    let settings = match settings {
+        Ok(settings) => settings,
+        Err(error) if Settings::data_dir_override(args.data_dir.clone()).is_some() => {
+            eprintln!("Warning: {error} The default settings are used.");
+            Settings::default()
+        }
+        Err(error) => return Err(error),
+    };
+    Zone::set_current(args.tz.or(settings.time_zone).unwrap_or_default());
+    let data_dir = settings.data_dir(args.data_dir)?;
+    let config = Config::for_calendar(data_dir.clone(), &args.calendar)?;
+    let default_zone = settings.calendar_zone(&args.calendar);
 
-    let current_time = args.current_time;
+    let current_time = args.current_time.unwrap_or_else(AppointmentTime::now);
+    let today = Zone::current().today();
+    let mut recurrences = RecurrenceList::new(&config.recurrence_file_path);
+    if !matches!(args.command, Commands::Doctor { .. }) {
+        warn_invalid_lines(&config.recurrence_file_path, recurrences.invalid_lines());
+    }
 
     match args.command {
         Commands::Add {
             description,
             time,
             stdin,
+            partial,
+            date,
+            replace,
+            until,
+            duration,
+            tag,
+            zone,
         } => {
-            let mut list = create_list_for_current_day(&current_time, &config);
-
-            let appointment = match stdin {
-                true => read_appointment_from_stdin()?,
-                false => Appointment::new(
-                    description.expect("Description should be available here"),
-                    time.expect("Time should be available here"),
-                ),
-            };
+            let now = current_date_time(&current_time);
+            if stdin {
+                let batch = Batch {
+                    default_date: date.unwrap_or(today),
+                    tags: tag,
+                    replace,
+                    partial,
+                    zone,
+                };
+                return add_from_stdin(&config, &recurrences, &current_time, default_zone, batch);
+            }
+            let time = time.expect("Time should be available here");
+            if date.is_some() && time.is_relative() {
+                return Err(String::from(
+                    "Relative times cannot be combined with a date",
+                ));
+            }
+            let zone = time.zone(zone, default_zone)?;
+            let (date, time) = time.resolve(date.unwrap_or(today), now);
+            let description = description.expect("Description should be available here");
+            let (description, tags) = helper::split_tags(&description);
+            let mut appointment = Appointment::new(description, time);
+            if let Some(zone) = zone {
+                appointment = appointment.with_zone(zone);
+            }
+            for tag in tags {
+                appointment = appointment.with_tag(&tag)?;
+            }
+            if date < today {
+                return Err(String::from("Given date already passed."));
+            }
+            let reference_time = reference_time_for_date(date, today, &current_time);
+            let path_for_date = (config.appointment_file_path_builder)(date);
+            let mut list =
+                create_list_for_date(&reference_time, &path_for_date, &recurrences, date)?;
 
-            if appointment.is_equal_or_earlier_than(&current_time) {
+            for tag in tag {
+                appointment = appointment.with_tag(&tag)?;
+            }
+            if let Some(end_time) = until {
+                appointment = appointment.until(end_time)?;
+            }
+            if let Some(duration) = duration {
+                appointment = appointment.with_duration(duration);
+            }
+
+            let (shown_on, shown) = appointment.in_zone(date, default_zone, &Zone::current());
+            if shown.starts_at(shown_on).naive() <= now {
                 return Err(String::from("Given time already passed."));
             }
 
-            list.add(appointment)?;
+            let overlapping: Vec<String> = list
+                .overlapping(&appointment)
+                .into_iter()
+                .filter(|a| !(replace && a.time() == appointment.time()))
+                .map(|a| format!("[{}] {}", a.time(), a.description()))
+                .collect();
+
+            match replace {
+                true => list.replace(appointment)?,
+                false => list.add(appointment)?,
+            };
             println!("Appointment added successfully.");
+            if !overlapping.is_empty() {
+                eprintln!(
+                    "Warning: the appointment overlaps with {}.",
+                    overlapping.join(", ")
+                );
+            }
         }
         Commands::Copy { from } => {
-            let mut list = create_list_for_current_day(&current_time, &config);
+            let mut list = create_list_for_current_day(&current_time, &config, &recurrences)?;
             let path_for_date = (config.appointment_file_path_builder)(from);
             list.copy(&path_for_date)?;
             println!("Appointments copied to current day.");
         }
         Commands::Clear => {
-            let mut list = create_list_for_current_day(&current_time, &config);
+            let mut list = create_list_for_current_day(&current_time, &config, &recurrences)?;
             list.clear()?;
             println!("Appointments cleared successfully.");
         }
-        Commands::List { expire_in, all } => {
-            let mut list = create_list_for_current_day(&current_time, &config);
+        Commands::List {
+            expire_in,
+            all,
+            format,
+            all_calendars,
+            tag,
+            exclude_tag,
+        } => {
+            if all_calendars {
+                return list_all_calendars(
+                    &data_dir,
+                    &settings,
+                    &current_time,
+                    expire_in,
+                    all,
+                    (&tag, &exclude_tag),
+                );
+            }
+            let mut list = create_view_for_date(
+                &current_time,
+                &config.appointment_file_path_current_day,
+                &config,
+                &recurrences,
+                today,
+                default_zone,
+            )?;
+            let following_days = match (all, expire_in) {
+                (false, Some(expire_in)) => following_days_within(
+                    &config,
+                    &recurrences,
+                    &current_time,
+                    expire_in,
+                    (&tag, &exclude_tag),
+                    default_zone,
+                )?,
+                _ => vec![],
+            };
+            let no_following = following_days.iter().all(|(_, a)| a.is_empty());
 
-            if list.no_appointments() {
+            if list.no_appointments() && no_following && format.is_none() {
                 println!("There are no appointments added for today.");
                 return Ok(());
             }
@@ -132,30 +647,509 @@ fn parse_input() -> Result<(), String> {
             if !all {
                 match expire_in {
                     None => list.filter(FilterOption::ByReferenceTime),
-                    Some(value) => list.filter(FilterOption::ByReferenceAndExpireTime(value)),
+                    Some(value) => list.filter(FilterOption::ByReferenceAndExpireTime(
+                        on_the_clock(&current_time, value),
+                    )),
                 };
             }
+            filter_by_tags(&mut list, &tag, &exclude_tag);
 
-            if list.no_appointments() {
+            if let Some(format) = format {
+                let mut days = vec![(today, list.appointments().clone())];
+                days.extend(following_days);
+                print_formatted(&days, &current_time, format);
+            } else if list.no_appointments() && no_following {
                 println!("No appointments found.");
             } else {
-                println!("{list}");
+                let mut groups = vec![];
+                if !list.no_appointments() {
+                    groups.push(list.to_string());
+                }
+                let now = list.now();
+                for (date, appointments) in following_days.iter().filter(|(_, a)| !a.is_empty()) {
+                    let lines: Vec<String> = appointments
+                        .iter()
+                        .map(|a| a.to_string_display_at(&now))
+                        .collect();
+                    groups.push(format!(
+                        "{}\n{}",
+                        format!("{} {}", date.format("%A"), helper::format_date(*date)).bold(),
+                        lines.join("\n")
+                    ));
+                }
+                println!("{}", groups.join("\n"));
             }
         }
-        Commands::History { date } => {
+        Commands::Next {
+            template,
+            soon,
+            tag,
+            exclude_tag,
+        } => {
+            let template = template
+                .or(settings.next_template)
+                .unwrap_or(String::from(next::DEFAULT_TEMPLATE));
+            next::validate(&template)?;
+            let soon = soon.or(settings.lead_time).unwrap_or(DEFAULT_LEAD_TIME);
+            let now = AppointmentDateTime::new(today, &current_time);
+            // Past midnight the next appointment may be on the day after
+            let appointment = today.iter_days().take(2).find_map(|date| {
+                next_shown_on(
+                    &config,
+                    &recurrences,
+                    date,
+                    now,
+                    default_zone,
+                    (&tag, &exclude_tag),
+                )
+            });
+            if let Some(appointment) = &appointment {
+                println!("{}", next::render(&template, appointment, &now)?);
+            }
+            process::exit(Upcoming::of(appointment.as_ref(), &now, soon).exit_code());
+        }
+        Commands::History {
+            date,
+            format,
+            tag,
+            exclude_tag,
+        } => {
+            let reference_time = reference_time_for_date(date, today, &current_time);
             let path_for_date = (config.appointment_file_path_builder)(date);
-            let list = AppointmentList::new(&current_time, &path_for_date);
-            if list.no_appointments() {
+            let mut list = create_view_for_date(
+                &reference_time,
+                &path_for_date,
+                &config,
+                &recurrences,
+                date,
+                default_zone,
+            )?;
+            let no_appointments = list.no_appointments();
+            filter_by_tags(&mut list, &tag, &exclude_tag);
+            if let Some(format) = format {
+                print_formatted(
+                    &[(date, list.appointments().clone())],
+                    &current_time,
+                    format,
+                );
+            } else if no_appointments {
                 println!("There were no appointments added in this day.");
+            } else if list.no_appointments() {
+                println!("No appointments found.");
             } else {
                 println!("{list}");
             }
         }
-        Commands::Remove { time } => {
-            let mut list = create_list_for_current_day(&current_time, &config);
-            list.remove(time)?;
+        Commands::Agenda {
+            week: _,
+            month,
+            days,
+            from,
+            grid,
+            show_empty,
+        } => {
+            let period = match (month, days) {
+                (true, _) => Period::Month,
+                (false, Some(days)) => Period::Days(days),
+                (false, None) => Period::Week,
+            };
+            let dates = period.dates(from.unwrap_or(today));
+            let mut days = vec![];
+            for date in dates.iter().copied() {
+                let reference_time = reference_time_for_date(date, today, &current_time);
+                let path_for_date = (config.appointment_file_path_builder)(date);
+                let list = create_view_for_date(
+                    &reference_time,
+                    &path_for_date,
+                    &config,
+                    &recurrences,
+                    date,
+                    default_zone,
+                )?;
+                let now = list.now();
+                let lines: Vec<String> = list
+                    .appointments()
+                    .iter()
+                    .map(|appointment| appointment.to_string_display_at(&now))
+                    .collect();
+                days.push((date, lines));
+            }
+            if grid {
+                let busy: HashSet<NaiveDate> = days
+                    .iter()
+                    .filter(|(_, lines)| !lines.is_empty())
+                    .map(|(date, _)| *date)
+                    .collect();
+                println!("{}", agenda::render_grid(&dates, &busy, today));
+                return Ok(());
+            }
+            match agenda::render_days(&days, show_empty, today) {
+                output if output.is_empty() => println!("No appointments found."),
+                output => println!("{output}"),
+            }
+        }
+        Commands::Search {
+            query,
+            ignore_case,
+            regex,
+            since,
+            until,
+        } => {
+            let query = Query::new(&query, ignore_case, regex)?;
+            if let (Some(since), Some(until)) = (since, until) {
+                date_range(since, until)?;
+            }
+            let mut groups = vec![];
+            for date in config.appointment_dates() {
+                if since.is_some_and(|since| date < since)
+                    || until.is_some_and(|until| date > until)
+                {
+                    continue;
+                }
+                let reference_time = reference_time_for_date(date, today, &current_time);
+                let path_for_date = (config.appointment_file_path_builder)(date);
+                let list = AppointmentList::new(&reference_time, &path_for_date);
+                let lines: Vec<String> = list
+                    .appointments()
+                    .iter()
+                    .filter(|appointment| query.matches(appointment.description()))
+                    .map(|appointment| {
+                        format!("  {}", appointment.to_string_display(&reference_time))
+                    })
+                    .collect();
+                if !lines.is_empty() {
+                    groups.push(format!(
+                        "{}\n{}",
+                        helper::format_date(date).bold(),
+                        lines.join("\n")
+                    ));
+                }
+            }
+            if groups.is_empty() {
+                println!("No appointments found.");
+            } else {
+                println!("{}", groups.join("\n\n"));
+            }
+        }
+        Commands::Remove { time, id, date } => {
+            let date = date.unwrap_or(today);
+            let reference_time = reference_time_for_date(date, today, &current_time);
+            let path_for_date = (config.appointment_file_path_builder)(date);
+            let mut list =
+                create_list_for_date(&reference_time, &path_for_date, &recurrences, date)?;
+            match id {
+                Some(id) => list.remove_by_id(id)?,
+                None => {
+                    let time = time.expect("Time should be available here");
+                    let appointments_at_time = list.appointments_at(&time);
+                    if appointments_at_time.len() > 1 && io::stdin().is_terminal() {
+                        let id = choose_appointment(&appointments_at_time)?;
+                        list.remove_by_id(id)?;
+                    } else {
+                        list.remove(time)?;
+                    }
+                }
+            }
             println!("Appointment removed successfully.");
         }
+        Commands::Edit {
+            time,
+            id,
+            date,
+            new_time,
+            description,
+            until,
+            duration,
+            status,
+            tag,
+            clear_tags,
+            editor,
+        } => {
+            let date = date.unwrap_or(today);
+            let reference_time = reference_time_for_date(date, today, &current_time);
+            let path_for_date = (config.appointment_file_path_builder)(date);
+            let mut list =
+                create_list_for_date(&reference_time, &path_for_date, &recurrences, date)?;
+            if editor {
+                edit_with_editor(&mut list, &path_for_date)?;
+                println!("Appointments updated successfully.");
+                return Ok(());
+            }
+
+            let (description, mut tags) = match description {
+                Some(description) => {
+                    let (description, tags) = helper::split_tags(&description);
+                    (Some(description), tags)
+                }
+                None => (None, vec![]),
+            };
+            tags.extend(tag);
+            let changes = AppointmentChanges {
+                time: new_time,
+                description,
+                end_time: until,
+                duration,
+                status,
+                tags: (clear_tags || !tags.is_empty()).then_some(tags),
+            };
+            if changes.time.is_none()
+                && changes.description.is_none()
+                && changes.end_time.is_none()
+                && changes.duration.is_none()
+                && changes.status.is_none()
+                && changes.tags.is_none()
+            {
+                return Err(String::from("There is nothing to change."));
+            }
+            match (id, time) {
+                (Some(id), _) => list.edit_by_id(id, changes)?,
+                (None, Some(time)) => {
+                    let appointments_at_time = list.appointments_at(&time);
+                    let recurrence_id = match appointments_at_time[..] {
+                        [appointment] => appointment.recurrence_id(),
+                        _ => None,
+                    };
+                    if appointments_at_time.len() > 1 && io::stdin().is_terminal() {
+                        let id = choose_appointment(&appointments_at_time)?;
+                        list.edit_by_id(id, changes)?;
+                    } else {
+                        list.edit(time, changes)?;
+                    }
+                    if let Some(recurrence_id) = recurrence_id {
+                        recurrences.skip(recurrence_id, date)?;
+                    }
+                }
+                (None, None) => unreachable!("Time or id should be available here"),
+            }
+            println!("Appointment edited successfully.");
+        }
+        Commands::Done(selection) => set_status(
+            selection,
+            Status::Done,
+            &config,
+            &mut recurrences,
+            &current_time,
+        )?,
+        Commands::Skip(selection) => set_status(
+            selection,
+            Status::Skipped,
+            &config,
+            &mut recurrences,
+            &current_time,
+        )?,
+        Commands::Cancel(selection) => set_status(
+            selection,
+            Status::Cancelled,
+            &config,
+            &mut recurrences,
+            &current_time,
+        )?,
+        Commands::Export { from, to, .. } => {
+            let from = from.unwrap_or(today);
+            let to = to.unwrap_or(from);
+            let mut days = vec![];
+            for date in date_range(from, to)? {
+                let reference_time = reference_time_for_date(date, today, &current_time);
+                let path_for_date = (config.appointment_file_path_builder)(date);
+                let list = create_view_for_date(
+                    &reference_time,
+                    &path_for_date,
+                    &config,
+                    &recurrences,
+                    date,
+                    default_zone,
+                )?;
+                // Appointments of the day before still in progress are exported on their own day
+                let appointments: Vec<Appointment> = list
+                    .appointments()
+                    .iter()
+                    .filter(|appointment| appointment.starts_at(date).date() == date)
+                    .cloned()
+                    .collect();
+                if !appointments.is_empty() {
+                    days.push((date, appointments));
+                }
+            }
+            print!("{}", ics::export(&days));
+        }
+        Commands::Import { file, from, to } => {
+            let from = from.unwrap_or(today);
+            let to = to.unwrap_or(from + Days::new(30));
+            let content = match fs::read_to_string(&file) {
+                Ok(content) => content,
+                Err(error) => return Err(format!("Failed to read {}. {error}", file.display())),
+            };
+            let mut days: BTreeMap<NaiveDate, Vec<Appointment>> = BTreeMap::new();
+            // Only checks the range, occurrences are computed for each event
+            date_range(from, to)?;
+            for event in ics::parse(&content)? {
+                for (date, mut appointment) in event.occurrences(from, to) {
+                    // Events are converted to the current time zone
+                    if default_zone.is_some_and(|zone| zone != Zone::current()) {
+                        appointment = appointment.with_zone(Zone::current());
+                    }
+                    days.entry(date).or_default().push(appointment);
+                }
+            }
+
+            let (mut imported, mut existing) = (0, 0);
+            for (date, appointments) in days {
+                let reference_time = reference_time_for_date(date, today, &current_time);
+                let path_for_date = (config.appointment_file_path_builder)(date);
+                let mut list =
+                    create_list_for_date(&reference_time, &path_for_date, &recurrences, date)?;
+                for appointment in appointments {
+                    let uid = appointment.metadata().extra.get(ics::UID_FIELD);
+                    if list
+                        .appointments()
+                        .iter()
+                        .any(|a| a.metadata().extra.get(ics::UID_FIELD) == uid)
+                    {
+                        existing += 1;
+                        continue;
+                    }
+                    list.add(appointment)?;
+                    imported += 1;
+                }
+            }
+            match existing {
+                0 => println!("{imported} appointments imported."),
+                _ => {
+                    println!("{imported} appointments imported, {existing} were already imported.")
+                }
+            }
+        }
+        Commands::Recur { command } => match command {
+            RecurCommands::Add {
+                description,
+                time,
+                rule,
+                start,
+            } => {
+                let id = recurrences.add(rule, time, description, start.unwrap_or(today))?;
+                println!("Recurrence #{id} added successfully.");
+            }
+            RecurCommands::List => {
+                if recurrences.recurrences().is_empty() {
+                    println!("There are no recurrences added.");
+                } else {
+                    println!("{recurrences}");
+                }
+            }
+            RecurCommands::Remove { id } => {
+                recurrences.remove(id)?;
+                println!("Recurrence removed successfully.");
+            }
+            RecurCommands::Skip { id, date } => {
+                recurrences.skip(id, date.unwrap_or(today))?;
+                println!("Occurrence skipped successfully.");
+            }
+            RecurCommands::Override {
+                id,
+                date,
+                description,
+                time,
+            } => {
+                let date = date.unwrap_or(today);
+                if date < today {
+                    return Err(String::from("Given date already passed."));
+                }
+                if recurrences.get(id).is_none() {
+                    return Err(String::from("There is no recurrence with this id."));
+                }
+                let (description, tags) = match description {
+                    Some(description) => {
+                        let (description, tags) = helper::split_tags(&description);
+                        (Some(description), tags)
+                    }
+                    None => (None, vec![]),
+                };
+                let changes = AppointmentChanges {
+                    time,
+                    description,
+                    tags: (!tags.is_empty()).then_some(tags),
+                    ..Default::default()
+                };
+
+                let reference_time = reference_time_for_date(date, today, &current_time);
+                let path_for_date = (config.appointment_file_path_builder)(date);
+                let mut list =
+                    create_list_for_date(&reference_time, &path_for_date, &recurrences, date)?;
+                // The occurrence is stored before being skipped, so a failure keeps it in place
+                list.edit_occurrence(id, changes)?;
+                recurrences.skip(id, date)?;
+                println!("Occurrence overridden successfully.");
+            }
+        },
+        Commands::Watch {
+            lead_time,
+            bell,
+            exec,
+            fifo,
+            once,
+        } => {
+            let lead_time = lead_time
+                .or(settings.lead_time)
+                .unwrap_or(DEFAULT_LEAD_TIME);
+            let mut sinks: Vec<Box<dyn NotificationSink>> = vec![];
+            if bell || (exec.is_none() && fifo.is_none()) {
+                sinks.push(Box::new(TerminalSink::new(bell)));
+            }
+            if let Some(command) = exec {
+                sinks.push(Box::new(CommandSink::new(command)));
+            }
+            if let Some(path) = fifo {
+                sinks.push(Box::new(FifoSink::new(path)));
+            }
+
+            if once {
+                let appointments =
+                    upcoming_appointments(&config, &recurrences, &current_time, default_zone)?;
+                let now = AppointmentDateTime::new(today, &current_time);
+                for appointment in reminders_due(&appointments, &now, lead_time) {
+                    for sink in sinks.iter_mut() {
+                        sink.notify(appointment)?;
+                    }
+                }
+            } else {
+                watch(&config, default_zone, lead_time, &mut sinks);
+            }
+        }
+        Commands::Tui { date } => {
+            if !io::stdout().is_terminal() {
+                return Err(String::from("The interactive view needs a terminal."));
+            }
+            tui::run(&config, date.unwrap_or(today), default_zone)?;
+        }
+        Commands::Undo => {
+            let operation = Journal::new(&config.journal_file_path).undo()?;
+            println!("Undone: {}", describe_operation(&config, &operation));
+        }
+        Commands::Redo => {
+            let operation = Journal::new(&config.journal_file_path).redo()?;
+            println!("Redone: {}", describe_operation(&config, &operation));
+        }
+        Commands::Log { limit } => {
+            let journal = Journal::new(&config.journal_file_path);
+            if journal.operations().is_empty() {
+                println!("No changes recorded.");
+                return Ok(());
+            }
+            for operation in journal.operations().iter().rev().take(limit) {
+                let line = format!(
+                    "#{} {} {} {}",
+                    operation.id,
+                    helper::format_date(operation.at.date_naive()),
+                    operation.at.format("%H:%M"),
+                    describe_operation(&config, operation)
+                );
+                match operation.undone {
+                    true => println!("{}", format!("{line} (undone)").dimmed()),
+                    false => println!("{line}"),
+                }
+            }
+        }
+        Commands::Doctor { quarantine } => doctor(&config, &mut recurrences, quarantine)?,
     }
 
     Ok(())
@@ -164,15 +1158,755 @@ fn parse_input() -> Result<(), String> {
 fn create_list_for_current_day<'a>(
     current_time: &'a AppointmentTime,
     config: &'a Config,
-) -> AppointmentList<'a> {
-    AppointmentList::new(current_time, &config.appointment_file_path_current_day)
+    recurrences: &RecurrenceList,
+) -> Result<AppointmentList<'a>, String> {
+    create_list_for_date(
+        current_time,
+        &config.appointment_file_path_current_day,
+        recurrences,
+        Zone::current().today(),
+    )
+}
+
+/// Loads the list of appointments of a date, migrating its file when it is still in the legacy
+/// format
+fn create_list_for_date<'a>(
+    reference_time: &'a AppointmentTime,
+    path: &'a PathBuf,
+    recurrences: &RecurrenceList,
+    date: NaiveDate,
+) -> Result<AppointmentList<'a>, String> {
+    let list = AppointmentList::new(reference_time, path)
+        .with_occurrences(recurrences.occurrences(date))
+        .with_journal(path.with_file_name(journal::JOURNAL_FILE))
+        .for_date(date, reference_moment(date, reference_time));
+    if list.is_legacy_format() {
+        eprintln!(
+            "Warning: {} was in the legacy format and has been migrated. Support for reading the legacy format will be removed in the next release.",
+            path.display()
+        );
+        list.write()?;
+    }
+    warn_invalid_lines(path, list.invalid_lines());
+    Ok(list)
+}
+
+/// Loads the list of appointments of a date as shown in the current time zone, with the ones of
+/// the days around it that fall on that date there and the ones of the day before still in
+/// progress. Appointments without a time zone are taken in the default one. The list is only for
+/// showing the appointments, it cannot be changed
+fn create_view_for_date<'a>(
+    reference_time: &'a AppointmentTime,
+    path: &'a PathBuf,
+    config: &Config,
+    recurrences: &RecurrenceList,
+    date: NaiveDate,
+    default_zone: Option<Zone>,
+) -> Result<AppointmentList<'a>, String> {
+    let mut list = create_list_for_date(reference_time, path, recurrences, date)?;
+    let appointments = config
+        .appointments_shown_on(recurrences, date, list.now(), default_zone)
+        .into_iter()
+        .map(|(_, appointment)| appointment)
+        .collect();
+    list.show_in_zone(appointments);
+    Ok(list)
+}
+
+/// Returns what an operation of the journal did and the dates of the appointments it changed
+fn describe_operation(config: &Config, operation: &Operation) -> String {
+    let dates = operation
+        .files
+        .iter()
+        .map(|change| {
+            let file = config.journal_file_path.with_file_name(&change.file);
+            match config.date_of(&file) {
+                Some(date) => helper::format_date(date),
+                None => change.file.clone(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("{} {} on {dates}", operation.name, operation.summary)
+}
+
+/// Prints a warning for each line of a file that could not be read
+fn warn_invalid_lines(path: &Path, lines: &[InvalidLine]) {
+    for line in lines {
+        eprintln!(
+            "Warning: line {} of {} could not be read and was kept as is. {}",
+            line.number,
+            path.display(),
+            line.error
+        );
+    }
+    if !lines.is_empty() {
+        eprintln!("Run \"todayiwill doctor\" to check every file.");
+    }
+}
+
+/// Reports the lines of the day files and of the recurrences that cannot be read, moving them to
+/// the quarantine files if asked to
+fn doctor(
+    config: &Config,
+    recurrences: &mut RecurrenceList,
+    quarantine: bool,
+) -> Result<(), String> {
+    let report = |path: &Path, lines: &[InvalidLine]| {
+        println!("{}", path.display().to_string().bold());
+        for line in lines {
+            println!("  Line {}: {}", line.number, line.error);
+        }
+    };
+    let mut total = 0;
+    let mut files = 0;
+    for date in config.appointment_dates() {
+        let reference_time = AppointmentTime::max_value();
+        let path_for_date = (config.appointment_file_path_builder)(date);
+        let mut list = AppointmentList::new(&reference_time, &path_for_date);
+        if list.invalid_lines().is_empty() {
+            continue;
+        }
+        report(&path_for_date, list.invalid_lines());
+        total += list.invalid_lines().len();
+        files += 1;
+        if quarantine {
+            list.quarantine()?;
+        }
+    }
+    if !recurrences.invalid_lines().is_empty() {
+        report(&config.recurrence_file_path, recurrences.invalid_lines());
+        total += recurrences.invalid_lines().len();
+        files += 1;
+        if quarantine {
+            recurrences.quarantine()?;
+        }
+    }
+
+    match (total, quarantine) {
+        (0, _) => println!("No problems found."),
+        (_, true) => println!("{total} invalid lines moved to .invalid files."),
+        (_, false) => {
+            return Err(format!(
+                "{total} invalid lines found in {files} files. Run with --quarantine to move them aside."
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Prints the appointments of the day of every calendar sorted by time, each one prefixed with
+/// the name of its calendar
+fn list_all_calendars(
+    data_dir: &Path,
+    settings: &Settings,
+    current_time: &AppointmentTime,
+    expire_in: Option<Duration>,
+    all: bool,
+    (tags, excluded_tags): (&[String], &[String]),
+) -> Result<(), String> {
+    let colors = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::Yellow,
+        Color::Green,
+        Color::Blue,
+        Color::Red,
+    ];
+    let today = Zone::current().today();
+    let mut any_appointment = false;
+    let mut lines: Vec<(AppointmentDateTime, String)> = vec![];
+    for (index, calendar) in helper::calendars(data_dir).iter().enumerate() {
+        let config = Config::for_calendar(data_dir.to_path_buf(), calendar)?;
+        let recurrences = RecurrenceList::new(&config.recurrence_file_path);
+        warn_invalid_lines(&config.recurrence_file_path, recurrences.invalid_lines());
+        let default_zone = settings.calendar_zone(calendar);
+        let mut list = create_view_for_date(
+            current_time,
+            &config.appointment_file_path_current_day,
+            &config,
+            &recurrences,
+            today,
+            default_zone,
+        )?;
+        let following_days = match (all, expire_in) {
+            (false, Some(expire_in)) => following_days_within(
+                &config,
+                &recurrences,
+                current_time,
+                expire_in,
+                (tags, excluded_tags),
+                default_zone,
+            )?,
+            _ => vec![],
+        };
+        any_appointment |= !list.no_appointments();
+        any_appointment |= following_days.iter().any(|(_, a)| !a.is_empty());
+        if !all {
+            match expire_in {
+                None => list.filter(FilterOption::ByReferenceTime),
+                Some(value) => list.filter(FilterOption::ByReferenceAndExpireTime(on_the_clock(
+                    current_time,
+                    value,
+                ))),
+            };
+        }
+        filter_by_tags(&mut list, tags, excluded_tags);
+        let label = format!("{calendar}:").color(colors[index % colors.len()]);
+        let now = list.now();
+        for appointment in list.appointments() {
+            lines.push((
+                appointment.starts_at(today),
+                format!("{label} {}", appointment.to_string_display_at(&now)),
+            ));
+        }
+        for (date, appointments) in &following_days {
+            for appointment in appointments {
+                lines.push((
+                    appointment.starts_at(*date),
+                    format!(
+                        "{label} {} {}",
+                        helper::format_date(*date),
+                        appointment.to_string_display_at(&now)
+                    ),
+                ));
+            }
+        }
+    }
+
+    if !any_appointment {
+        println!("There are no appointments added for today.");
+    } else if lines.is_empty() {
+        println!("No appointments found.");
+    } else {
+        lines.sort_by_key(|(time, _)| *time);
+        let lines: Vec<String> = lines.into_iter().map(|(_, line)| line).collect();
+        println!("{}", lines.join("\n"));
+    }
+    Ok(())
+}
+
+/// Keeps only the appointments with all the tags and none of the excluded ones
+fn filter_by_tags(list: &mut AppointmentList, tags: &[String], excluded_tags: &[String]) {
+    for tag in tags {
+        list.filter(FilterOption::ByTag(tag.clone()));
+    }
+    for tag in excluded_tags {
+        list.filter(FilterOption::ExcludingTag(tag.clone()));
+    }
+}
+
+/// Returns today at the current time
+fn current_date_time(current_time: &AppointmentTime) -> NaiveDateTime {
+    AppointmentDateTime::new(Zone::current().today(), current_time).naive()
+}
+
+/// Returns how far the clock of the current time zone moves while the duration elapses from the
+/// current time, which differs from the duration across daylight saving transitions
+fn on_the_clock(current_time: &AppointmentTime, duration: Duration) -> Duration {
+    let now = AppointmentDateTime::new(Zone::current().today(), current_time);
+    Zone::current().advance(now, duration) - now
+}
+
+/// Returns the appointments of the days after today starting within the duration from the
+/// current time, for when it goes past midnight. Each day comes with its appointments having all
+/// the tags and none of the excluded ones
+fn following_days_within(
+    config: &Config,
+    recurrences: &RecurrenceList,
+    current_time: &AppointmentTime,
+    duration: Duration,
+    (tags, excluded_tags): (&[String], &[String]),
+    default_zone: Option<Zone>,
+) -> Result<Vec<(NaiveDate, Vec<Appointment>)>, String> {
+    let today = Zone::current().today();
+    let now = AppointmentDateTime::new(today, current_time);
+    let until = Zone::current().advance(now, duration);
+    let reference_time = AppointmentTime::min_value();
+    let mut days = vec![];
+    for date in today
+        .iter_days()
+        .skip(1)
+        .take_while(|date| *date <= until.date())
+    {
+        let path_for_date = (config.appointment_file_path_builder)(date);
+        let mut list = create_view_for_date(
+            &reference_time,
+            &path_for_date,
+            config,
+            recurrences,
+            date,
+            default_zone,
+        )?;
+        filter_by_tags(&mut list, tags, excluded_tags);
+        // Appointments started before are already in the list of their day
+        let appointments = list
+            .appointments()
+            .iter()
+            .filter(|appointment| (now..=until).contains(&appointment.starts_at(date)))
+            .cloned()
+            .collect();
+        days.push((date, appointments));
+    }
+    Ok(days)
+}
+
+/// Returns the next pending appointment shown on the date, having all the tags and none of the
+/// excluded ones. The files are only read, as it runs on every shell prompt: files in the legacy
+/// format are not migrated and invalid lines are not reported
+fn next_shown_on(
+    config: &Config,
+    recurrences: &RecurrenceList,
+    date: NaiveDate,
+    now: AppointmentDateTime,
+    default_zone: Option<Zone>,
+    (tags, excluded_tags): (&[String], &[String]),
+) -> Option<Appointment> {
+    let reference_time = now.time();
+    let path_for_date = (config.appointment_file_path_builder)(date);
+    let mut list = AppointmentList::new(&reference_time, &path_for_date).for_date(date, now);
+    let appointments = config
+        .appointments_shown_on(recurrences, date, now, default_zone)
+        .into_iter()
+        .map(|(_, appointment)| appointment)
+        .collect();
+    list.show_in_zone(appointments);
+    filter_by_tags(&mut list, tags, excluded_tags);
+    list.next().cloned()
+}
+
+/// Returns the dates from the first to the last one, inclusive
+fn date_range(from: NaiveDate, to: NaiveDate) -> Result<Vec<NaiveDate>, String> {
+    if to < from {
+        return Err(String::from(
+            "The last date should not be earlier than the first one.",
+        ));
+    }
+    Ok(from.iter_days().take_while(|date| *date <= to).collect())
+}
+
+/// Prints the appointments of a date in a format for other programs
+fn print_formatted(
+    days: &[(NaiveDate, Vec<Appointment>)],
+    current_time: &AppointmentTime,
+    format: OutputFormat,
+) {
+    let output = output::render_days(days, current_date_time(current_time), format);
+    if !output.is_empty() {
+        println!("{output}");
+    }
+}
+
+/// Records what happened to the selected appointment. Occurrences of recurrences are stored as
+/// regular appointments and skipped on the recurrence
+fn set_status(
+    selection: AppointmentSelection,
+    status: Status,
+    config: &Config,
+    recurrences: &mut RecurrenceList,
+    current_time: &AppointmentTime,
+) -> Result<(), String> {
+    let today = Zone::current().today();
+    let date = selection.date.unwrap_or(today);
+    let reference_time = reference_time_for_date(date, today, current_time);
+    let path_for_date = (config.appointment_file_path_builder)(date);
+    let mut list = create_list_for_date(&reference_time, &path_for_date, recurrences, date)?;
+    match (selection.id, selection.time) {
+        (Some(id), _) => list.set_status_by_id(id, status)?,
+        (None, Some(time)) => {
+            let appointments_at_time = list.appointments_at(&time);
+            let recurrence_id = match appointments_at_time[..] {
+                [appointment] => appointment.recurrence_id(),
+                _ => None,
+            };
+            if appointments_at_time.len() > 1 && io::stdin().is_terminal() {
+                let id = choose_appointment(&appointments_at_time)?;
+                list.set_status_by_id(id, status)?;
+            } else {
+                list.set_status(time, status)?;
+            }
+            if let Some(recurrence_id) = recurrence_id {
+                recurrences.skip(recurrence_id, date)?;
+            }
+        }
+        (None, None) => unreachable!("Time or id should be available here"),
+    }
+    println!("Appointment marked as {status}.");
+    Ok(())
+}
+
+/// Opens the appointments file of a day in the editor set by $VISUAL or $EDITOR. The list is only
+/// changed when every edited line is valid
+fn edit_with_editor(list: &mut AppointmentList, path: &Path) -> Result<(), String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or(String::from("vi"));
+    let stored: Vec<&Appointment> = list
+        .appointments()
+        .iter()
+        .filter(|a| a.recurrence_id().is_none())
+        .collect();
+    let edit_path = env::temp_dir().join(format!(
+        "todayiwill_edit_{}_{}",
+        process::id(),
+        path.file_name()
+            .expect("Appointments file should have a name")
+            .to_string_lossy()
+    ));
+    let content = storage::serialize(&stored, list.format(), 0)?;
+    if let Err(error) = fs::write(&edit_path, content) {
+        return Err(format!("Failed to create the file to edit. {error}"));
+    }
+
+    let status = process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(&edit_path)
+        .status();
+    let content = fs::read_to_string(&edit_path);
+    let _ = fs::remove_file(&edit_path);
+    match status {
+        Ok(status) if status.success() => (),
+        Ok(status) => {
+            return Err(format!(
+                "The editor exited with {status}. Nothing was changed."
+            ))
+        }
+        Err(error) => return Err(format!("Failed to run the editor. {error}")),
+    }
+    let content = match content {
+        Ok(content) => content,
+        Err(error) => return Err(format!("Failed to read the edited file. {error}")),
+    };
+
+    let (_, parsed) = storage::parse_lines(&content);
+    let mut appointments = vec![];
+    let mut errors = vec![];
+    for (line, appointment) in parsed {
+        match appointment {
+            Ok(appointment) => appointments.push(appointment),
+            Err(error) => errors.push(format!("Line {line}: {error}")),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!("{}\nNothing was changed.", errors.join("\n")));
+    }
+    list.replace_all(appointments)
+}
+
+/// Sends reminders for the appointments of the current day until interrupted, and before
+/// midnight for the ones right after it. The day files are reloaded whenever they change and the
+/// next day file is used after midnight
+fn watch(
+    config: &Config,
+    default_zone: Option<Zone>,
+    lead_time: Duration,
+    sinks: &mut [Box<dyn NotificationSink>],
+) {
+    let mut notified: HashSet<(NaiveDate, String)> = HashSet::new();
+    loop {
+        let now = AppointmentDateTime::now();
+        let today = now.date();
+        let current_time = now.time();
+        let recurrences = RecurrenceList::new(&config.recurrence_file_path);
+        let appointments =
+            match upcoming_appointments(config, &recurrences, &current_time, default_zone) {
+                Ok(appointments) => appointments,
+                Err(error) => {
+                    eprintln!("{error}");
+                    thread::sleep(WATCH_POLL_INTERVAL);
+                    continue;
+                }
+            };
+
+        let midnight =
+            AppointmentDateTime::new(today + Days::new(1), &AppointmentTime::min_value());
+        let wake_at = match reminder::next_reminder_time(&appointments, &now, lead_time) {
+            Some(at) => at.min(midnight),
+            None => midnight,
+        };
+
+        notified.retain(|(date, _)| *date >= today);
+        for appointment in reminders_due(&appointments, &now, lead_time) {
+            let key = (appointment.starts_at(today).date(), appointment.to_string());
+            if !notified.insert(key) {
+                continue;
+            }
+            for sink in sinks.iter_mut() {
+                if let Err(error) = sink.notify(appointment) {
+                    eprintln!("{error}");
+                }
+            }
+        }
+
+        let watched_files = [
+            (config.appointment_file_path_builder)(today - Days::new(1)),
+            (config.appointment_file_path_builder)(today),
+            (config.appointment_file_path_builder)(today + Days::new(1)),
+            (config.appointment_file_path_builder)(today + Days::new(2)),
+            *config.recurrence_file_path.clone(),
+        ];
+        let last_modified = files_last_modified(&watched_files);
+        while AppointmentDateTime::now() < wake_at
+            && files_last_modified(&watched_files) == last_modified
+        {
+            thread::sleep(WATCH_POLL_INTERVAL);
+        }
+    }
+}
+
+/// Returns the appointments of today and of tomorrow as shown in the current time zone, each one
+/// with its date, for sending reminders before midnight for the ones right after it
+fn upcoming_appointments(
+    config: &Config,
+    recurrences: &RecurrenceList,
+    current_time: &AppointmentTime,
+    default_zone: Option<Zone>,
+) -> Result<Vec<Appointment>, String> {
+    let today = Zone::current().today();
+    let mut appointments = vec![];
+    for date in [today, today + Days::new(1)] {
+        let reference_time = reference_time_for_date(date, today, current_time);
+        let path_for_date = (config.appointment_file_path_builder)(date);
+        let list = create_view_for_date(
+            &reference_time,
+            &path_for_date,
+            config,
+            recurrences,
+            date,
+            default_zone,
+        )?;
+        appointments.extend(
+            list.appointments()
+                .iter()
+                .filter(|appointment| date == today || appointment.starts_at(date).date() == date)
+                .cloned(),
+        );
+    }
+    Ok(appointments)
+}
+
+/// Returns the pending appointments starting within the lead time. Appointments already in
+/// progress are left out, so starting `watch` during a meeting does not remind it. The ones
+/// starting right now are kept, as `watch` wakes up at their start when the lead time is zero
+fn reminders_due<'a>(
+    appointments: &'a [Appointment],
+    now: &AppointmentDateTime,
+    lead_time: Duration,
+) -> Vec<&'a Appointment> {
+    appointments
+        .iter()
+        .filter(|appointment| appointment.status().is_pending())
+        .filter(|appointment| {
+            let starts_at = appointment.starts_at(now.date());
+            *now <= starts_at && starts_at <= *now + lead_time
+        })
+        .collect()
+}
+
+/// Returns the last modification time of each file, `None` when it does not exist
+fn files_last_modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Returns the time which the appointments of a given date should be compared to. Past dates are
+/// compared against the end of the day and future dates against its start
+fn reference_time_for_date(
+    date: NaiveDate,
+    today: NaiveDate,
+    current_time: &AppointmentTime,
+) -> AppointmentTime {
+    match date.cmp(&today) {
+        std::cmp::Ordering::Less => AppointmentTime::max_value(),
+        std::cmp::Ordering::Equal => current_time.clone(),
+        std::cmp::Ordering::Greater => AppointmentTime::min_value(),
+    }
+}
+
+/// Returns the moment the appointments of a given date are compared to, see
+/// `reference_time_for_date`. Other dates are compared against the current moment, which is
+/// strictly before the future ones, so their appointments at midnight are not passed yet
+fn reference_moment(date: NaiveDate, reference_time: &AppointmentTime) -> AppointmentDateTime {
+    match date == Zone::current().today() {
+        true => AppointmentDateTime::new(date, reference_time),
+        false => AppointmentDateTime::now(),
+    }
+}
+
+/// Asks the user to choose one of the appointments, returning its id
+fn choose_appointment(appointments: &[&Appointment]) -> Result<u32, String> {
+    eprintln!(
+        "There are {} appointments at this time:",
+        appointments.len()
+    );
+    for (index, appointment) in appointments.iter().enumerate() {
+        eprintln!("  {}) {}", index + 1, appointment.description());
+    }
+    eprint!("Which one? ");
+    let mut buffer = String::new();
+    if let Err(error) = io::stdin().read_line(&mut buffer) {
+        return Err(format!("{error}"));
+    }
+    match buffer.trim().parse::<usize>() {
+        Ok(choice) if (1..=appointments.len()).contains(&choice) => {
+            Ok(appointments[choice - 1].id())
+        }
+        _ => Err(String::from("Invalid choice.")),
+    }
+}
+
+/// Options applied to every appointment read by `add --stdin`
+struct Batch {
+    /// Date of the lines without one
+    default_date: NaiveDate,
+    tags: Vec<String>,
+    replace: bool,
+    /// Adds the valid lines even if others are invalid
+    partial: bool,
+    /// Time zone the times are written in, defaults to the one of the calendar
+    zone: Option<Zone>,
+}
+
+/// Adds the appointments read from STDIN, one per line. Nothing is added if any line is invalid,
+/// unless the batch is partial, and the files of every date are written together. A single line
+/// is reported as a regular `add`
+fn add_from_stdin(
+    config: &Config,
+    recurrences: &RecurrenceList,
+    current_time: &AppointmentTime,
+    default_zone: Option<Zone>,
+    batch: Batch,
+) -> Result<(), String> {
+    let today = Zone::current().today();
+    let now = current_date_time(current_time);
+    let input = read_stdin()?;
+    let lines: Vec<(usize, &str)> = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index + 1, line))
+        .collect();
+    if lines.is_empty() {
+        return Err(String::from("No appointments were given."));
+    }
+
+    let mut valid: BTreeMap<NaiveDate, Vec<(usize, Appointment)>> = BTreeMap::new();
+    let mut invalid: Vec<(usize, String)> = vec![];
+    for (number, line) in &lines {
+        let parsed = natural::parse_appointment_in_zone(
+            line,
+            batch.default_date,
+            now,
+            batch.zone,
+            default_zone,
+        )
+        .and_then(|(date, mut appointment)| {
+            for tag in &batch.tags {
+                appointment = appointment.with_tag(tag)?;
+            }
+            if date < today {
+                return Err(String::from("Given date already passed."));
+            }
+            let (shown_on, shown) = appointment.in_zone(date, default_zone, &Zone::current());
+            if shown.starts_at(shown_on).naive() <= now {
+                return Err(String::from("Given time already passed."));
+            }
+            Ok((date, appointment))
+        });
+        match parsed {
+            Ok((date, appointment)) => valid.entry(date).or_default().push((*number, appointment)),
+            Err(error) => invalid.push((*number, error)),
+        }
+    }
+
+    let single = lines.len() == 1;
+    if single {
+        if let Some((_, error)) = invalid.pop() {
+            return Err(error);
+        }
+    }
+    for (number, error) in &invalid {
+        eprintln!("Line {number}: {error}");
+    }
+    if !invalid.is_empty() && !batch.partial {
+        return Err(format!(
+            "No appointments added, {} of {} lines are invalid. Use --partial to add the valid ones.",
+            invalid.len(),
+            lines.len()
+        ));
+    }
+
+    let dates: Vec<(NaiveDate, AppointmentTime, PathBuf)> = valid
+        .keys()
+        .map(|date| {
+            (
+                *date,
+                reference_time_for_date(*date, today, current_time),
+                (config.appointment_file_path_builder)(*date),
+            )
+        })
+        .collect();
+    let mut added: Vec<(usize, String, Vec<String>)> = vec![];
+    let mut lists = vec![];
+    for ((date, reference_time, path_for_date), appointments) in dates.iter().zip(valid.values()) {
+        let list = create_list_for_date(reference_time, path_for_date, recurrences, *date)?;
+        let mut kept: Vec<Appointment> = vec![];
+        for (number, appointment) in appointments {
+            let overlapping: Vec<String> = list
+                .overlapping(appointment)
+                .into_iter()
+                .chain(kept.iter())
+                .filter(|a| a.overlaps(appointment))
+                .filter(|a| !(batch.replace && a.time() == appointment.time()))
+                .map(|a| format!("[{}] {}", a.time(), a.description()))
+                .collect();
+            added.push((*number, appointment.to_string(), overlapping));
+            if batch.replace {
+                kept.retain(|a| a.time() != appointment.time());
+            }
+            kept.push(appointment.clone());
+        }
+        lists.push((list, kept));
+    }
+    AppointmentList::add_to_lists(lists, batch.replace)?;
+    added.sort();
+
+    for (number, appointment, overlapping) in &added {
+        if !single {
+            println!("Line {number}: {appointment} added.");
+        }
+        if !overlapping.is_empty() {
+            let warning = format!(
+                "Warning: the appointment overlaps with {}.",
+                overlapping.join(", ")
+            );
+            match single {
+                true => eprintln!("{warning}"),
+                false => eprintln!("Line {number}: {warning}"),
+            }
+        }
+    }
+    match (single, invalid.len()) {
+        (true, _) => println!("Appointment added successfully."),
+        (false, 0) => println!("{} appointments added successfully.", added.len()),
+        (false, skipped) => println!(
+            "{} appointments added successfully, {skipped} invalid lines skipped.",
+            added.len()
+        ),
+    }
+    Ok(())
 }
 
-fn read_appointment_from_stdin() -> Result<Appointment, String> {
+/// Reads everything from STDIN
+fn read_stdin() -> Result<String, String> {
     let mut buffer = String::new();
-    match io::stdin().read_line(&mut buffer) {
+    match io::stdin().read_to_string(&mut buffer) {
         Ok(..) => (),
         Err(error) => return Err(format!("{error}")),
     };
-    Appointment::from(&buffer)
+    Ok(buffer)
 }
@@ -1,6 +1,8 @@
-use assert_cmd::Command;
-use chrono::{Local, NaiveDate};
+use std::fs;
+
+use chrono::{Days, Local, NaiveDate};
 use serial_test::serial;
+use todayiwill::Zone;
 
 mod common;
 
@@ -9,8 +11,7 @@ mod common;
 fn empty_list() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list"])
         .assert()
         .success()
@@ -27,8 +28,7 @@ fn list_appointments() {
         b"08:12 Call mom\n14:45 Listen to music\n",
     );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
@@ -42,8 +42,7 @@ fn list_appointments() {
 fn add_appointment() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -57,8 +56,7 @@ fn add_appointment() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
@@ -72,8 +70,7 @@ fn add_appointment() {
 fn clear_appointments() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -87,22 +84,19 @@ fn clear_appointments() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
         .stdout("[20:10] An urgent event\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["clear"])
         .assert()
         .success()
         .stdout("Appointments cleared successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list"])
         .assert()
         .success()
@@ -116,8 +110,7 @@ fn clear_appointments() {
 fn list_current_time() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -131,8 +124,7 @@ fn list_current_time() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -146,29 +138,25 @@ fn list_current_time() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "10:00"])
         .assert()
         .success()
         .stdout("[19:00] Clean bedroom\n[22:30] Brush teeth\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "22:29"])
         .assert()
         .success()
         .stdout("[22:30] Brush teeth\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "22:30"])
         .assert()
         .success()
         .stdout("No appointments found.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "22:30", "--all"])
         .assert()
         .success()
@@ -182,8 +170,7 @@ fn list_current_time() {
 fn list_expire_in_x_mins() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -197,8 +184,7 @@ fn list_expire_in_x_mins() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -212,49 +198,155 @@ fn list_expire_in_x_mins() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "09:30", "--expire-in", "20"])
         .assert()
         .success()
         .stdout("[09:45] Reply to an important e-mail\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "09:30", "--expire-in", "10"])
         .assert()
         .success()
         .stdout("No appointments found.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "09:30", "--expire-in", "60"])
         .assert()
         .success()
         .stdout("[09:45] Reply to an important e-mail\n[10:23] Schedule doctor appointment\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "09:30", "--expire-in", "15"])
         .assert()
         .success()
         .stdout("[09:45] Reply to an important e-mail\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "09:30", "--expire-in", "14"])
         .assert()
         .success()
         .stdout("No appointments found.\n");
 
+    common::command()
+        .args(["list", "--current-time", "09:30", "--expire-in", "900s"])
+        .assert()
+        .success()
+        .stdout("[09:45] Reply to an important e-mail\n");
+
+    common::command()
+        .args(["list", "--current-time", "09:30", "--expire-in", "1h"])
+        .assert()
+        .success()
+        .stdout("[09:45] Reply to an important e-mail\n[10:23] Schedule doctor appointment\n");
+
+    common::command()
+        .args(["list", "--current-time", "09:30", "--expire-in", "a while"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(
+            r#"error: invalid value 'a while' for '--expire-in <DURATION>': Invalid duration "a while", use a number with a unit like "90s", "15m" or "1h30m"
+
+For more information, try '--help'.
+"#,
+        );
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn list_expire_in_should_cross_midnight() {
+    common::setup();
+    let tomorrow = Local::now().date_naive() + Days::new(1);
+
+    common::command()
+        .args(["add", "--description", "Night shift", "--time", "22:00"])
+        .args(["--for", "8h", "--current-time", "21:00"])
+        .assert()
+        .success();
+    common::command()
+        .args(["add", "--description", "Deploy", "--time", "23:55"])
+        .args(["--current-time", "23:50"])
+        .assert()
+        .success();
+    common::command()
+        .args(["add", "--description", "Night check", "--time", "00:10"])
+        .args(["--date", "tomorrow", "--current-time", "23:50"])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["list", "--current-time", "23:50", "--expire-in", "30m"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "[22:00-06:00] Night shift (now)\n[23:55] Deploy\n{}\n[00:10] Night check\n",
+            tomorrow.format("%A %d/%m/%Y")
+        ));
+
+    common::command()
+        .args(["list", "--current-time", "23:50", "--expire-in", "10m"])
+        .assert()
+        .success()
+        .stdout("[22:00-06:00] Night shift (now)\n[23:55] Deploy\n");
+
+    common::command()
+        .args(["list", "--current-time", "23:50", "--expire-in", "20m"])
+        .args(["--format", "csv", "--tag", "none"])
+        .assert()
+        .success()
+        .stdout("id,date,time,end_time,description,status,passed,minutes_until,tags\n");
+
+    common::command()
+        .args(["list", "--current-time", "23:50", "--expire-in", "20m"])
+        .args(["--format", "plain"])
+        .assert()
+        .success()
+        .stdout("[22:00-06:00] Night shift\n[23:55] Deploy\n[00:10] Night check\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn appointments_crossing_midnight_should_be_in_progress_on_the_next_day() {
+    common::setup();
+    let yesterday = Local::now().date_naive() - Days::new(1);
+    common::helper_write_to_appointment_data_file(b"22:00-02:00 Night shift\n", yesterday);
+
+    common::command()
+        .args(["list", "--current-time", "01:00"])
+        .assert()
+        .success()
+        .stdout("[22:00-02:00] Night shift (now)\n");
+
+    common::command()
+        .args(["list", "--current-time", "02:00"])
+        .assert()
+        .success()
+        .stdout("There are no appointments added for today.\n");
+
+    common::command()
+        .args(["add", "--description", "Night shift", "--time", "22:00"])
+        .args(["--until", "02:00", "--current-time", "10:00"])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["list", "--current-time", "23:00"])
+        .assert()
+        .success()
+        .stdout("[22:00-02:00] Night shift (now)\n");
+
     common::remove_all_appointment_files();
 }
 
 #[test]
 #[serial]
 fn add_invalid_entries_for_time() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--description", "A certain event", "--time", "9:y3"])
         .assert()
         .failure()
@@ -266,8 +358,7 @@ For more information, try '--help'.
 "#,
         );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--description", "An urgent event", "--time", "24:10"])
         .assert()
         .failure()
@@ -279,8 +370,7 @@ For more information, try '--help'.
 "#,
         );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--description", "An urgent event", "--time", "15:60"])
         .assert()
         .failure()
@@ -296,8 +386,7 @@ For more information, try '--help'.
 #[test]
 #[serial]
 fn add_invalid_entries_for_current_time() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -312,8 +401,7 @@ fn add_invalid_entries_for_current_time() {
         .code(1)
         .stderr("Given time already passed.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -328,8 +416,7 @@ fn add_invalid_entries_for_current_time() {
         .code(1)
         .stderr("Given time already passed.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -347,8 +434,7 @@ fn add_invalid_entries_for_current_time() {
 For more information, try '--help'.
 "#);
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -370,8 +456,7 @@ For more information, try '--help'.
 #[test]
 #[serial]
 fn add_invalid_entries_missing_parameters() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--time", "22:03"])
         .assert()
         .failure()
@@ -386,8 +471,7 @@ For more information, try '--help'.
 "#,
         );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--description", "Wash the kitchen floor"])
         .assert()
         .failure()
@@ -402,8 +486,7 @@ For more information, try '--help'.
 "#,
         );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add"])
         .assert()
         .failure()
@@ -423,8 +506,7 @@ For more information, try '--help'.
 #[test]
 #[serial]
 fn list_invalid_entries_current_time() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "23:60"])
         .assert()
         .failure()
@@ -434,8 +516,7 @@ fn list_invalid_entries_current_time() {
 For more information, try '--help'.
 "#);
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "as:"])
         .assert()
         .failure()
@@ -452,18 +533,14 @@ fn appointments_stored_using_determined_file_name() {
     common::setup();
 
     let current_date = Local::now().format("%d%m%Y").to_string();
-    let appointments_file = dirs::data_dir()
-        .unwrap()
-        .join("todayiwill")
-        .join(format!("appointments_{current_date}.txt"));
+    let appointments_file = common::app_data_dir().join(format!("appointments_{current_date}.txt"));
     assert!(
         !appointments_file.exists(),
         "File \"{}\" exists when it should not",
         appointments_file.to_str().unwrap()
     );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -487,8 +564,7 @@ fn appointments_stored_using_determined_file_name() {
 fn appointment_history() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -502,8 +578,7 @@ fn appointment_history() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "history",
             "--date",
@@ -513,8 +588,7 @@ fn appointment_history() {
         .success()
         .stdout("[18:40] Work on my art portfolio\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["history", "--date", "01/01/2024"])
         .assert()
         .success()
@@ -525,8 +599,7 @@ fn appointment_history() {
         NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
     );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["history", "--date", "01/01/2024"])
         .assert()
         .success()
@@ -538,8 +611,7 @@ fn appointment_history() {
 #[test]
 #[serial]
 fn history_invalid_entries() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["history"])
         .assert()
         .failure()
@@ -554,8 +626,7 @@ For more information, try '--help'.
 "#,
         );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["history", "--date", "01-2023-22"])
         .assert()
         .failure()
@@ -571,24 +642,21 @@ For more information, try '--help'.
 fn add_from_stdin_should_be_possible() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--current-time", "19:49", "--stdin"])
         .write_stdin("20:46 Finish final assingment")
         .assert()
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--current-time", "03:12", "--stdin"])
         .write_stdin("16:23 Read another chapter of moby dick")
         .assert()
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--current-time", "09:30"])
         .assert()
         .success()
@@ -600,8 +668,7 @@ fn add_from_stdin_should_be_possible() {
 #[test]
 #[serial]
 fn add_from_stdin_should_validate_current_time() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--current-time", "15:26", "--stdin"])
         .write_stdin("12:06 A past non-urgent event")
         .assert()
@@ -610,11 +677,66 @@ fn add_from_stdin_should_validate_current_time() {
         .stderr("Given time already passed.\n");
 }
 
+#[test]
+#[serial]
+fn add_from_stdin_should_write_every_date_or_none() {
+    common::setup();
+    let today = Local::now().date_naive();
+    fs::create_dir_all(common::appointments_file(today + Days::new(2)))
+        .expect("Failed to block the appointment file");
+
+    common::command()
+        .args(["add", "--current-time", "08:00", "--stdin"])
+        .write_stdin("12:00 Lunch\ntomorrow 10am Dentist\n+2d 9am Blocked\n")
+        .assert()
+        .failure()
+        .code(1);
+
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("There are no appointments added for today.\n");
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("There were no appointments added in this day.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn add_from_stdin_should_use_the_time_zone() {
+    common::setup();
+    common::helper_write_config_file("time_zone = \"Asia/Tokyo\"\n");
+
+    common::command()
+        .args(["add", "--stdin", "--zone", "UTC", "--date", "+7d"])
+        .write_stdin("09:00 Sync\n10:00 Review\n")
+        .assert()
+        .success();
+    common::command()
+        .args(["history", "--date", "+7d", "--tz", "UTC"])
+        .assert()
+        .success()
+        .stdout("[09:00] Sync\n[10:00] Review\n");
+
+    common::command()
+        .args(["add", "--stdin", "--zone", "UTC"])
+        .write_stdin("in 1h Call\n")
+        .assert()
+        .failure()
+        .stderr("Relative times cannot be combined with a time zone\n");
+
+    common::setup();
+}
+
 #[test]
 #[serial]
 fn add_from_stdin_should_not_be_run_with_other_add_args() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--current-time",
@@ -636,8 +758,7 @@ For more information, try '--help'.
 "#,
         );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--current-time",
@@ -663,8 +784,7 @@ For more information, try '--help'.
 #[test]
 #[serial]
 fn add_from_stdin_should_error_on_invalid_entries() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--stdin"])
         .write_stdin("1204 A malformed appointment")
         .assert()
@@ -672,8 +792,7 @@ fn add_from_stdin_should_error_on_invalid_entries() {
         .code(1)
         .stderr("Invalid string for appointment time\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["add", "--stdin"])
         .write_stdin("Unformatted 10:34 appointment")
         .assert()
@@ -682,6 +801,70 @@ fn add_from_stdin_should_error_on_invalid_entries() {
         .stderr("Invalid string for appointment time\n");
 }
 
+#[test]
+#[serial]
+fn add_from_stdin_should_add_every_line_or_none() {
+    common::setup();
+
+    common::command()
+        .args(["add", "--current-time", "08:00", "--stdin"])
+        .write_stdin("09:00 Standup\n1204 A malformed appointment\n\n07:00 Too early\n")
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(
+            "Line 2: Invalid string for appointment time\n\
+            Line 4: Given time already passed.\n\
+            No appointments added, 2 of 3 lines are invalid. Use --partial to add the valid ones.\n",
+        );
+
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("There are no appointments added for today.\n");
+
+    common::command()
+        .args(["add", "--current-time", "08:00", "--stdin", "--partial"])
+        .write_stdin("09:00 Standup\n1204 A malformed appointment\n09:00-10:00 Review #work\n")
+        .assert()
+        .success()
+        .stdout(
+            "Line 1: 09:00 Standup added.\n\
+            Line 3: 09:00-10:00 Review #work added.\n\
+            2 appointments added successfully, 1 invalid lines skipped.\n",
+        )
+        .stderr(
+            "Line 2: Invalid string for appointment time\n\
+            Line 3: Warning: the appointment overlaps with [09:00] Standup.\n",
+        );
+
+    common::command()
+        .args(["add", "--current-time", "08:00", "--stdin"])
+        .write_stdin("12:00 Lunch\ntomorrow 10am Dentist\n")
+        .assert()
+        .success()
+        .stdout(
+            "Line 1: 12:00 Lunch added.\n\
+            Line 2: 10:00 Dentist added.\n\
+            2 appointments added successfully.\n",
+        );
+
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("[09:00-10:00] Review #work\n[09:00] Standup\n[12:00] Lunch\n");
+
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("[10:00] Dentist\n");
+
+    common::remove_all_appointment_files();
+}
+
 #[test]
 #[serial]
 fn copy_appointments_from_past_days_should_be_ok() {
@@ -692,22 +875,19 @@ fn copy_appointments_from_past_days_should_be_ok() {
         NaiveDate::from_ymd_opt(2024, 2, 10).unwrap(),
     );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
         .stdout("There are no appointments added for today.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["copy", "--from", "10/02/2024"])
         .assert()
         .success()
         .stdout("Appointments copied to current day.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all", "--current-time", "01:56"])
         .assert()
         .success()
@@ -721,15 +901,13 @@ fn copy_appointments_from_past_days_should_be_ok() {
 fn copy_appointments_from_empty_days_should_error() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
         .stdout("There are no appointments added for today.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["copy", "--from", "23/09/2007"])
         .assert()
         .failure()
@@ -742,8 +920,7 @@ fn copy_appointments_from_empty_days_should_error() {
 #[test]
 #[serial]
 fn copy_command_with_no_arguments_should_error() {
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["copy"])
         .write_stdin("11:39 Appointment from stdin")
         .assert()
@@ -770,8 +947,7 @@ fn copy_command_should_error_if_there_are_appointments_for_today() {
         NaiveDate::from_ymd_opt(2023, 8, 28).unwrap(),
     );
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -785,8 +961,7 @@ fn copy_command_should_error_if_there_are_appointments_for_today() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["copy", "--from", "28/08/2023"])
         .assert()
         .failure()
@@ -798,11 +973,10 @@ fn copy_command_should_error_if_there_are_appointments_for_today() {
 
 #[test]
 #[serial]
-fn add_with_an_existing_time_should_override() {
+fn add_with_an_existing_time_and_replace_should_override() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -816,8 +990,7 @@ fn add_with_an_existing_time_should_override() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -831,15 +1004,13 @@ fn add_with_an_existing_time_should_override() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
         .stdout("[18:25] Watch the soccer game\n[20:03] Search for a new car\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -848,20 +1019,19 @@ fn add_with_an_existing_time_should_override() {
             "20:03",
             "--current-time",
             "10:01",
+            "--replace",
         ])
         .assert()
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
         .stdout("[18:25] Watch the soccer game\n[20:03] Learn Rust\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -875,8 +1045,7 @@ fn add_with_an_existing_time_should_override() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["list", "--all"])
         .assert()
         .success()
@@ -890,8 +1059,7 @@ fn add_with_an_existing_time_should_override() {
 fn delete_an_appointment_should_be_ok() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -905,8 +1073,7 @@ fn delete_an_appointment_should_be_ok() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -920,8 +1087,7 @@ fn delete_an_appointment_should_be_ok() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["remove", "--time", "17:02", "--current-time", "14:56"])
         .assert()
         .success()
@@ -935,8 +1101,7 @@ fn delete_an_appointment_should_be_ok() {
 fn delete_a_non_existent_appointment_should_error() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -950,8 +1115,7 @@ fn delete_a_non_existent_appointment_should_error() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["remove", "--time", "22:41", "--current-time", "19:34"])
         .assert()
         .failure()
@@ -966,8 +1130,7 @@ fn delete_a_non_existent_appointment_should_error() {
 fn delete_a_past_appointment_should_error() {
     common::setup();
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args([
             "add",
             "--description",
@@ -981,8 +1144,7 @@ fn delete_a_past_appointment_should_error() {
         .success()
         .stdout("Appointment added successfully.\n");
 
-    Command::cargo_bin("todayiwill")
-        .unwrap()
+    common::command()
         .args(["remove", "--time", "05:00", "--current-time", "12:57"])
         .assert()
         .failure()
@@ -991,3 +1153,1812 @@ fn delete_a_past_appointment_should_error() {
 
     common::remove_all_appointment_files();
 }
+
+#[test]
+#[serial]
+fn add_appointment_for_a_future_date() {
+    common::setup();
+
+    let tomorrow = Local::now().date_naive() + Days::new(1);
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Plan the week",
+            "--time",
+            "08:00",
+            "--date",
+            "tomorrow",
+            "--current-time",
+            "21:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Appointment added successfully.\n");
+
+    common::command()
+        .args(["list", "--all"])
+        .assert()
+        .success()
+        .stdout("There are no appointments added for today.\n");
+
+    common::command()
+        .args([
+            "history",
+            "--date",
+            tomorrow.format("%d/%m/%Y").to_string().as_str(),
+            "--current-time",
+            "21:00",
+        ])
+        .assert()
+        .success()
+        .stdout("[08:00] Plan the week\n");
+
+    common::command()
+        .args(["history", "--date", "+1d"])
+        .assert()
+        .success()
+        .stdout("[08:00] Plan the week\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn add_appointment_for_a_past_date_should_error() {
+    common::setup();
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Too late",
+            "--time",
+            "08:00",
+            "--date",
+            "yesterday",
+        ])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("Given date already passed.\n");
+
+    common::command()
+        .args(["add", "--stdin", "--date", "-3d"])
+        .write_stdin("10:00 Also too late")
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("Given date already passed.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn remove_appointment_from_another_date() {
+    common::setup();
+
+    let next_week = Local::now().date_naive() + Days::new(7);
+    common::helper_write_to_appointment_data_file(
+        b"00:00 Midnight release\n09:15 Weekly planning\n",
+        next_week,
+    );
+
+    common::command()
+        .args([
+            "remove",
+            "--time",
+            "09:15",
+            "--date",
+            "+1w",
+            "--current-time",
+            "23:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Appointment removed successfully.\n");
+
+    common::command()
+        .args(["history", "--date", "+7d"])
+        .assert()
+        .success()
+        .stdout("[00:00] Midnight release\n");
+
+    common::command()
+        .args([
+            "remove",
+            "--time",
+            "00:00",
+            "--date",
+            "+1w",
+            "--current-time",
+            "23:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Appointment removed successfully.\n");
+
+    common::helper_write_to_appointment_data_file(
+        b"18:30 Dinner with friends\n",
+        NaiveDate::from_ymd_opt(2024, 3, 2).unwrap(),
+    );
+
+    common::command()
+        .args([
+            "remove",
+            "--time",
+            "18:30",
+            "--date",
+            "02/03/2024",
+            "--current-time",
+            "08:00",
+        ])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("This appointment is already past and cannot be removed.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn recurring_appointments_should_be_listed() {
+    common::setup();
+
+    common::command()
+        .args(["recur", "list"])
+        .assert()
+        .success()
+        .stdout("There are no recurrences added.\n");
+
+    common::command()
+        .args([
+            "recur",
+            "add",
+            "--description",
+            "Lunch",
+            "--time",
+            "12:30",
+            "--rule",
+            "every day",
+            "--start",
+            "01/06/2024",
+        ])
+        .assert()
+        .success()
+        .stdout("Recurrence #1 added successfully.\n");
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Pick up the kids",
+            "--time",
+            "17:00",
+            "--current-time",
+            "08:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Appointment added successfully.\n");
+
+    common::command()
+        .args(["recur", "list"])
+        .assert()
+        .success()
+        .stdout("#1 [12:30] Lunch (daily since 01/06/2024)\n");
+
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("[12:30] Lunch\n[17:00] Pick up the kids\n");
+
+    common::command()
+        .args(["history", "--date", "01/06/2024"])
+        .assert()
+        .success()
+        .stdout("[12:30] Lunch\n");
+
+    common::command()
+        .args(["history", "--date", "31/05/2024"])
+        .assert()
+        .success()
+        .stdout("There were no appointments added in this day.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn recurring_appointments_occurrences_can_be_skipped_and_overridden() {
+    common::setup();
+
+    common::command()
+        .args([
+            "recur",
+            "add",
+            "--description",
+            "Standup",
+            "--time",
+            "09:30",
+            "--rule",
+            "daily",
+        ])
+        .assert()
+        .success()
+        .stdout("Recurrence #1 added successfully.\n");
+
+    common::command()
+        .args(["remove", "--time", "09:30", "--current-time", "08:00"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(
+            "This appointment is an occurrence of a recurrence and should be skipped instead.\n",
+        );
+
+    common::command()
+        .args(["recur", "skip", "--id", "1"])
+        .assert()
+        .success()
+        .stdout("Occurrence skipped successfully.\n");
+
+    common::command()
+        .args(["list", "--all"])
+        .assert()
+        .success()
+        .stdout("There are no appointments added for today.\n");
+
+    common::command()
+        .args([
+            "recur", "override", "--id", "1", "--date", "tomorrow", "--time", "11:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Occurrence overridden successfully.\n");
+
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("[11:00] Standup\n");
+
+    common::command()
+        .args(["history", "--date", "+2d"])
+        .assert()
+        .success()
+        .stdout("[09:30] Standup\n");
+
+    common::command()
+        .args([
+            "recur", "override", "--id", "1", "--date", "tomorrow", "--time", "12:00",
+        ])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("The recurrence has no occurrence on the given date.\n");
+
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("[11:00] Standup\n");
+
+    common::command()
+        .args(["recur", "skip", "--id", "1", "--date", "tomorrow"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("The recurrence has no occurrence on the given date.\n");
+
+    common::command()
+        .args(["recur", "remove", "--id", "1"])
+        .assert()
+        .success()
+        .stdout("Recurrence removed successfully.\n");
+
+    common::command()
+        .args(["recur", "remove", "--id", "1"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("There is no recurrence with this id.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn recurring_appointments_invalid_rule_should_error() {
+    common::command()
+        .args([
+            "recur",
+            "add",
+            "--description",
+            "Sometimes",
+            "--time",
+            "10:00",
+            "--rule",
+            "every blue moon",
+        ])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(
+            r#"error: invalid value 'every blue moon' for '--rule <RULE>': Invalid recurrence rule "blue moon"
+
+For more information, try '--help'.
+"#,
+        );
+}
+
+#[test]
+#[serial]
+fn watch_once_should_remind_due_appointments() {
+    common::setup();
+    common::helper_write_to_appointment_current_day_data_file(
+        b"09:00-10:00 Workshop\n09:30 Daily standup\n09:45 Review pull requests\n11:00 Lunch\n",
+    );
+
+    common::command()
+        .args(["watch", "--once", "--current-time", "09:26"])
+        .assert()
+        .success()
+        .stdout("Reminder: [09:30] Daily standup\n");
+
+    common::command()
+        .args([
+            "watch",
+            "--once",
+            "--lead-time",
+            "20",
+            "--current-time",
+            "09:26",
+        ])
+        .assert()
+        .success()
+        .stdout("Reminder: [09:30] Daily standup\nReminder: [09:45] Review pull requests\n");
+
+    common::command()
+        .args(["watch", "--once", "--current-time", "10:00"])
+        .assert()
+        .success()
+        .stdout("");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn watch_once_should_notify_sinks() {
+    common::setup();
+    common::helper_write_to_appointment_current_day_data_file(b"14:00 Dentist\n");
+
+    let fifo = std::env::temp_dir().join("todayiwill_watch_once_should_notify_sinks.txt");
+    if fifo.exists() {
+        std::fs::remove_file(&fifo).unwrap();
+    }
+
+    common::command()
+        .args([
+            "watch",
+            "--once",
+            "--current-time",
+            "13:58",
+            "--fifo",
+            fifo.to_str().unwrap(),
+            "--exec",
+            "echo \"$TODAYIWILL_TIME $TODAYIWILL_DESCRIPTION\"",
+        ])
+        .assert()
+        .success()
+        .stdout("14:00 Dentist\n");
+
+    assert_eq!(
+        "Reminder: [14:00] Dentist\n",
+        std::fs::read_to_string(&fifo).unwrap()
+    );
+
+    common::command()
+        .args([
+            "watch",
+            "--once",
+            "--current-time",
+            "13:58",
+            "--exec",
+            "exit 1",
+        ])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("Reminder command failed with exit status: 1\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn legacy_appointment_files_should_be_migrated() {
+    common::setup();
+
+    let date = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+    common::helper_write_raw_to_appointment_data_file(
+        b"07:30 Morning run\n18:00 Piano lesson\n",
+        date,
+    );
+    let appointments_file = common::appointments_file(date);
+
+    common::command()
+        .args(["history", "--date", "15/04/2024"])
+        .assert()
+        .success()
+        .stdout("[07:30] Morning run\n[18:00] Piano lesson\n")
+        .stderr(format!(
+            "Warning: {} was in the legacy format and has been migrated. Support for reading the legacy format will be removed in the next release.\n",
+            appointments_file.display()
+        ));
+
+    let content = std::fs::read_to_string(&appointments_file).unwrap();
+    assert!(content.starts_with(
+        "{\"version\":1,\"next_id\":3}\n{\"id\":1,\"date\":\"2024-04-15\",\"time\":\"07:30\",\"description\":\"Morning run\"}\n"
+    ));
+
+    common::command()
+        .args(["history", "--date", "15/04/2024"])
+        .assert()
+        .success()
+        .stdout("[07:30] Morning run\n[18:00] Piano lesson\n")
+        .stderr("");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn removed_ids_should_not_be_given_again() {
+    common::setup();
+    common::helper_write_to_appointment_current_day_data_file(b"16:00 Dentist\n18:00 Gym\n");
+
+    common::command()
+        .args(["remove", "--id", "2", "--current-time", "09:00"])
+        .assert()
+        .success();
+    common::command()
+        .args(["add", "-d", "Dinner", "-t", "20:00", "-c", "09:00"])
+        .assert()
+        .success();
+    common::command()
+        .args(["remove", "--id", "2", "--current-time", "09:00"])
+        .assert()
+        .failure()
+        .stderr("There is no appointment with this id.\n");
+    common::command()
+        .args(["remove", "--id", "3", "--current-time", "09:00"])
+        .assert()
+        .success();
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn newer_format_versions_should_not_be_written() {
+    common::setup();
+    let content = "{\"version\":2}\n{\"id\":1,\"time\":\"16:00\",\"description\":\"Dentist\",\"place\":\"Downtown\"}\n";
+    common::helper_write_raw_to_appointment_data_file(
+        content.as_bytes(),
+        Local::now().date_naive(),
+    );
+
+    common::command()
+        .args(["list", "--current-time", "09:00"])
+        .assert()
+        .success()
+        .stdout("[16:00] Dentist\n");
+    common::command()
+        .args(["add", "-d", "Dinner", "-t", "20:00", "-c", "09:00"])
+        .assert()
+        .failure()
+        .stderr("Error while saving the appointment. Error: The appointments are in version 2 of the file format, newer than the version 1 supported. Update todayiwill to change them.\n");
+    assert_eq!(
+        content,
+        fs::read_to_string(common::appointments_file(Local::now().date_naive())).unwrap()
+    );
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn add_with_an_existing_time_should_keep_both() {
+    common::setup();
+
+    for description in ["Team meeting", "Order lunch"] {
+        common::command()
+            .args([
+                "add",
+                "--description",
+                description,
+                "--time",
+                "12:00",
+                "--current-time",
+                "10:00",
+            ])
+            .assert()
+            .success()
+            .stdout("Appointment added successfully.\n");
+    }
+
+    common::command()
+        .args(["list", "--all"])
+        .assert()
+        .success()
+        .stdout("[12:00] Order lunch\n[12:00] Team meeting\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn remove_ambiguous_appointment_should_require_an_id() {
+    common::setup();
+    common::helper_write_to_appointment_current_day_data_file(
+        b"16:00 Dentist\n16:00 Pick up the car\n18:00 Gym\n",
+    );
+
+    common::command()
+        .args(["remove", "--time", "16:00", "--current-time", "09:00"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("There are 2 appointments at 16:00 (#1 Dentist, #2 Pick up the car). Choose one by its id.\n");
+
+    common::command()
+        .args(["remove", "--id", "2", "--current-time", "09:00"])
+        .assert()
+        .success()
+        .stdout("Appointment removed successfully.\n");
+
+    common::command()
+        .args(["remove", "--id", "2", "--current-time", "09:00"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr("There is no appointment with this id.\n");
+
+    common::command()
+        .args(["remove", "--time", "16:00", "--current-time", "09:00"])
+        .assert()
+        .success()
+        .stdout("Appointment removed successfully.\n");
+
+    common::command()
+        .args(["list", "--all"])
+        .assert()
+        .success()
+        .stdout("[18:00] Gym\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn add_with_end_time_and_duration_should_warn_overlaps() {
+    common::setup();
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Workshop",
+            "--time",
+            "14:00",
+            "--until",
+            "16:00",
+            "--current-time",
+            "10:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Appointment added successfully.\n")
+        .stderr("");
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Call Alice",
+            "--time",
+            "15:30",
+            "--for",
+            "1h",
+            "--current-time",
+            "10:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Appointment added successfully.\n")
+        .stderr("Warning: the appointment overlaps with [14:00] Workshop.\n");
+
+    common::command()
+        .args(["list", "--current-time", "16:10"])
+        .assert()
+        .success()
+        .stdout("[15:30-16:30] Call Alice (now)\n");
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Dinner",
+            "--time",
+            "19:00",
+            "--until",
+            "19:00",
+            "--current-time",
+            "10:00",
+        ])
+        .assert()
+        .failure()
+        .stderr("End time should differ from the start time\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn appointments_can_be_marked_as_done_skipped_and_cancelled() {
+    common::setup();
+
+    common::helper_write_to_appointment_current_day_data_file(
+        b"08:00 Go for a run\n10:00 Review the budget\n18:00 Dinner with Ana\n",
+    );
+
+    common::command()
+        .args([
+            "recur",
+            "add",
+            "--description",
+            "Lunch",
+            "--time",
+            "12:30",
+            "--rule",
+            "daily",
+            "--start",
+            "01/06/2024",
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["done", "--time", "08:00"])
+        .assert()
+        .success()
+        .stdout("Appointment marked as done.\n");
+
+    common::command()
+        .args(["skip", "--id", "2"])
+        .assert()
+        .success()
+        .stdout("Appointment marked as skipped.\n");
+
+    common::command()
+        .args(["cancel", "--time", "12:30"])
+        .assert()
+        .success()
+        .stdout("Appointment marked as cancelled.\n");
+
+    common::command()
+        .args(["list", "--all", "--current-time", "13:00"])
+        .assert()
+        .success()
+        .stdout("[08:00] Go for a run (done)\n[10:00] Review the budget (skipped)\n[12:30] Lunch (cancelled)\n[18:00] Dinner with Ana\n");
+
+    common::command()
+        .args(["done", "--time", "09:00"])
+        .assert()
+        .failure()
+        .stderr("There is no appointment at this specific time.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn edit_an_appointment_should_be_ok() {
+    common::setup();
+
+    common::helper_write_to_appointment_current_day_data_file(
+        b"08:00 Go for a run\n10:00 Review the budget\n",
+    );
+
+    common::command()
+        .args([
+            "edit",
+            "--time",
+            "08:00",
+            "--new-time",
+            "07:30",
+            "--description",
+            "Go for a long run",
+            "--for",
+            "1h",
+            "--current-time",
+            "12:00",
+        ])
+        .assert()
+        .success()
+        .stdout("Appointment edited successfully.\n");
+
+    common::command()
+        .args(["edit", "--id", "2", "--status", "done"])
+        .assert()
+        .success()
+        .stdout("Appointment edited successfully.\n");
+
+    common::command()
+        .args(["list", "--all", "--current-time", "12:00"])
+        .assert()
+        .success()
+        .stdout("[07:30-08:30] Go for a long run\n[10:00] Review the budget (done)\n");
+
+    common::command()
+        .args(["edit", "--time", "10:00", "--new-time", "25:00"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(
+            r#"error: invalid value '25:00' for '--new-time <HH:MM>': Hour should be between 0 and 23
+
+For more information, try '--help'.
+"#,
+        );
+
+    common::command()
+        .args(["edit", "--time", "10:00"])
+        .assert()
+        .failure()
+        .stderr("There is nothing to change.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn edit_appointments_with_editor_should_validate_the_lines() {
+    common::setup();
+
+    common::helper_write_to_appointment_current_day_data_file(
+        b"08:00 Go for a run\n10:00 Review the budget\n",
+    );
+
+    common::command()
+        .args(["edit", "--editor"])
+        .env(
+            "VISUAL",
+            "sed -i 's/\"time\":\"10:00\"/\"time\":\"99:00\"/'",
+        )
+        .assert()
+        .failure()
+        .stderr("Line 3: Hour should be between 0 and 23\nNothing was changed.\n");
+
+    common::command()
+        .args(["edit", "--editor"])
+        .env("VISUAL", "sed -i 's/budget/quarterly budget/'")
+        .assert()
+        .success()
+        .stdout("Appointments updated successfully.\n");
+
+    common::command()
+        .args(["list", "--all", "--current-time", "07:00"])
+        .assert()
+        .success()
+        .stdout("[08:00] Go for a run\n[10:00] Review the quarterly budget\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn list_and_history_in_machine_readable_formats() {
+    common::setup();
+
+    common::helper_write_to_appointment_current_day_data_file(
+        b"08:00 Go for a run\n10:00 Review the budget\n",
+    );
+    let yesterday = Local::now().date_naive() - Days::new(1);
+    common::helper_write_to_appointment_data_file(b"21:00 Call mom, then dad\n", yesterday);
+    let today_iso = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let yesterday_iso = yesterday.format("%Y-%m-%d").to_string();
+
+    common::command()
+        .args(["list", "--format", "json", "--current-time", "09:00"])
+        .assert()
+        .success()
+        .stdout(format!("[{{\"id\":2,\"date\":\"{today_iso}\",\"time\":\"10:00\",\"end_time\":null,\"description\":\"Review the budget\",\"status\":\"pending\",\"passed\":false,\"minutes_until\":60,\"tags\":[]}}]\n"));
+
+    common::command()
+        .args(["list", "--all", "--format", "tsv", "--current-time", "09:00"])
+        .assert()
+        .success()
+        .stdout(format!("id\tdate\ttime\tend_time\tdescription\tstatus\tpassed\tminutes_until\ttags\n1\t{today_iso}\t08:00\t\tGo for a run\tpending\ttrue\t-60\t\n2\t{today_iso}\t10:00\t\tReview the budget\tpending\tfalse\t60\t\n"));
+
+    common::command()
+        .args([
+            "history",
+            "--date",
+            "yesterday",
+            "--format",
+            "csv",
+            "--current-time",
+            "09:00",
+        ])
+        .assert()
+        .success()
+        .stdout(format!("id,date,time,end_time,description,status,passed,minutes_until,tags\n1,{yesterday_iso},21:00,,\"Call mom, then dad\",pending,true,-720,\n"));
+
+    common::command()
+        .args(["list", "--format", "plain", "--current-time", "11:00"])
+        .assert()
+        .success()
+        .stdout("");
+
+    common::command()
+        .args(["list", "--format", "xml"])
+        .assert()
+        .failure()
+        .code(2);
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn import_and_export_ics_files() {
+    common::setup();
+
+    let today = Local::now().date_naive();
+    let tomorrow = today + Days::new(1);
+    let ics_path = std::env::temp_dir().join("todayiwill_test_import.ics");
+    std::fs::write(
+        &ics_path,
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+            BEGIN:VEVENT\r\nUID:standup\r\nDTSTART:{}T093000\r\nDURATION:PT15M\r\nRRULE:FREQ=DAILY\r\nSUMMARY:Standup\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:dentist\r\nDTSTART:{}T140000\r\nSUMMARY:Dentist\\, downtown\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n",
+            today.format("%Y%m%d"),
+            tomorrow.format("%Y%m%d")
+        ),
+    )
+    .expect("Failed to write test file");
+
+    common::command()
+        .args(["import", ics_path.to_str().unwrap(), "--to", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("3 appointments imported.\n");
+
+    common::command()
+        .args(["import", ics_path.to_str().unwrap(), "--to", "+2d"])
+        .assert()
+        .success()
+        .stdout("1 appointments imported, 3 were already imported.\n");
+
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("[09:30-09:45] Standup\n[14:00] Dentist, downtown\n");
+
+    let output = common::command()
+        .args([
+            "export", "--from", "today", "--to", "tomorrow", "--format", "ics",
+        ])
+        .output()
+        .unwrap();
+    let exported = String::from_utf8(output.stdout).unwrap();
+    assert!(exported.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert_eq!(exported.matches("BEGIN:VEVENT").count(), 3);
+    assert!(exported.contains("UID:dentist\r\n"));
+    assert!(exported.contains("SUMMARY:Dentist\\, downtown\r\n"));
+
+    common::command()
+        .args(["export", "--from", "tomorrow", "--to", "today"])
+        .assert()
+        .failure()
+        .stderr("The last date should not be earlier than the first one.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn config_file_should_set_the_preferences() {
+    common::setup();
+    common::helper_write_config_file(
+        "lead_time = 20\ndate_format = \"%Y-%m-%d\"\ncolor = \"never\"\n",
+    );
+    common::helper_write_to_appointment_current_day_data_file(
+        b"09:30 Daily standup\n09:45 Review pull requests\n",
+    );
+    let tomorrow = Local::now().date_naive() + Days::new(1);
+
+    common::command()
+        .args(["watch", "--once", "--current-time", "09:26"])
+        .assert()
+        .success()
+        .stdout("Reminder: [09:30] Daily standup\nReminder: [09:45] Review pull requests\n");
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Dentist",
+            "--time",
+            "10:00",
+            "--date",
+            &tomorrow.format("%Y-%m-%d").to_string(),
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args([
+            "history",
+            "--date",
+            &tomorrow.format("%d/%m/%Y").to_string(),
+        ])
+        .assert()
+        .failure();
+
+    common::command()
+        .args([
+            "recur",
+            "add",
+            "-d",
+            "Gym",
+            "-t",
+            "18:00",
+            "-r",
+            "daily",
+            "-s",
+            "2024-06-01",
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["recur", "list"])
+        .assert()
+        .success()
+        .stdout("#1 [18:00] Gym (daily since 2024-06-01)\n");
+
+    common::helper_write_config_file("colour = \"never\"\n");
+    let error = format!(
+        "Invalid config file {}. unknown field `colour`, expected one of `data_dir`, `lead_time`, `date_format`, `color`, `time_zone`, `next_template`, `calendars`",
+        common::config_file().display()
+    );
+    common::command()
+        .args(["list"])
+        .env_remove("TODAYIWILL_DATA_DIR")
+        .assert()
+        .failure()
+        .stderr(format!("{error}\n"));
+
+    common::command()
+        .args(["list", "--current-time", "09:00"])
+        .assert()
+        .success()
+        .stdout("[09:30] Daily standup\n[09:45] Review pull requests\n[18:00] Gym\n")
+        .stderr(format!("Warning: {error} The default settings are used.\n"));
+
+    for flag in ["--help", "--version"] {
+        common::command()
+            .arg(flag)
+            .env_remove("TODAYIWILL_DATA_DIR")
+            .assert()
+            .success();
+    }
+
+    common::setup();
+}
+
+#[test]
+#[serial]
+fn data_dir_flag_should_override_the_environment() {
+    common::setup();
+    let other_data_dir = common::app_data_dir().with_file_name("other-data");
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Check sink problem",
+            "--time",
+            "09:56",
+            "--current-time",
+            "09:00",
+            "--data-dir",
+            other_data_dir.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["list", "--current-time", "09:00"])
+        .assert()
+        .success()
+        .stdout("There are no appointments added for today.\n");
+
+    common::command()
+        .args([
+            "list",
+            "--current-time",
+            "09:00",
+            "--data-dir",
+            other_data_dir.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout("[09:56] Check sink problem\n");
+
+    std::fs::remove_dir_all(other_data_dir).unwrap();
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn calendars_should_be_kept_apart_and_merged_on_list() {
+    common::setup();
+
+    for (calendar, time, description) in [
+        ("work", "09:00", "Standup"),
+        ("personal", "12:30", "Lunch with Ana"),
+        ("default", "10:00", "Water the plants"),
+        ("work", "14:00", "Sprint review"),
+    ] {
+        common::command()
+            .args([
+                "add",
+                "--description",
+                description,
+                "--time",
+                time,
+                "--current-time",
+                "08:00",
+                "--calendar",
+                calendar,
+            ])
+            .assert()
+            .success();
+    }
+
+    common::command()
+        .args(["list", "--current-time", "08:00", "--calendar", "work"])
+        .assert()
+        .success()
+        .stdout("[09:00] Standup\n[14:00] Sprint review\n");
+
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("[10:00] Water the plants\n");
+
+    common::command()
+        .args(["list", "--current-time", "09:30", "--all-calendars"])
+        .assert()
+        .success()
+        .stdout("default: [10:00] Water the plants\npersonal: [12:30] Lunch with Ana\nwork: [14:00] Sprint review\n");
+
+    common::command()
+        .args(["list", "--current-time", "09:30", "--all-calendars", "--all"])
+        .assert()
+        .success()
+        .stdout("work: [09:00] Standup\ndefault: [10:00] Water the plants\npersonal: [12:30] Lunch with Ana\nwork: [14:00] Sprint review\n");
+
+    common::command()
+        .args(["list", "--calendar", "../work"])
+        .assert()
+        .failure()
+        .stderr("Invalid calendar name \"../work\", use only letters, numbers, \"-\" and \"_\"\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn appointments_should_be_filtered_by_tag() {
+    common::setup();
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Check the alerts #oncall",
+            "--time",
+            "09:00",
+            "--current-time",
+            "08:00",
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Review #42",
+            "--time",
+            "10:00",
+            "--current-time",
+            "08:00",
+            "--tag",
+            "Work",
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Rotate the pager",
+            "--time",
+            "11:00",
+            "--current-time",
+            "08:00",
+            "--tag",
+            "#oncall",
+            "--tag",
+            "work",
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("[09:00] Check the alerts #oncall\n[10:00] Review #42 #work\n[11:00] Rotate the pager #oncall #work\n");
+
+    common::command()
+        .args(["list", "--current-time", "08:00", "--tag", "oncall"])
+        .assert()
+        .success()
+        .stdout("[09:00] Check the alerts #oncall\n[11:00] Rotate the pager #oncall #work\n");
+
+    common::command()
+        .args([
+            "list",
+            "--current-time",
+            "08:00",
+            "--exclude-tag",
+            "#oncall",
+        ])
+        .assert()
+        .success()
+        .stdout("[10:00] Review #42 #work\n");
+
+    common::command()
+        .args([
+            "history",
+            "--date",
+            "today",
+            "--tag",
+            "work",
+            "--exclude-tag",
+            "oncall",
+            "--format",
+            "json",
+            "--current-time",
+            "08:00",
+        ])
+        .assert()
+        .success()
+        .stdout(format!("[{{\"id\":2,\"date\":\"{}\",\"time\":\"10:00\",\"end_time\":null,\"description\":\"Review #42\",\"status\":\"pending\",\"passed\":false,\"minutes_until\":120,\"tags\":[\"work\"]}}]\n", Local::now().date_naive().format("%Y-%m-%d")));
+
+    common::command()
+        .args([
+            "edit",
+            "--time",
+            "10:00",
+            "--clear-tags",
+            "--current-time",
+            "08:00",
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["list", "--current-time", "08:00", "--tag", "nothing"])
+        .assert()
+        .success()
+        .stdout("No appointments found.\n");
+
+    common::command()
+        .args(["add", "-d", "Lunch", "-t", "12:00", "--tag", "on call"])
+        .assert()
+        .failure()
+        .code(2);
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn search_should_scan_every_day() {
+    common::setup();
+
+    let first = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+    let second = NaiveDate::from_ymd_opt(2024, 4, 20).unwrap();
+    common::helper_write_to_appointment_data_file(b"07:30 Morning run\n18:00 Call mom\n", first);
+    common::helper_write_to_appointment_data_file(
+        b"09:00 Dentist\n19:00 Call dad\n20:00 Recall the order\n",
+        second,
+    );
+
+    common::command()
+        .args(["search", "Call"])
+        .assert()
+        .success()
+        .stdout("15/04/2024\n  [18:00] Call mom\n\n20/04/2024\n  [19:00] Call dad\n");
+
+    common::command()
+        .args(["search", "call", "--ignore-case", "--since", "16/04/2024"])
+        .assert()
+        .success()
+        .stdout("20/04/2024\n  [19:00] Call dad\n  [20:00] Recall the order\n");
+
+    common::command()
+        .args([
+            "search",
+            "^(morning|dentist)",
+            "-i",
+            "--regex",
+            "--until",
+            "15/04/2024",
+        ])
+        .assert()
+        .success()
+        .stdout("15/04/2024\n  [07:30] Morning run\n");
+
+    common::command()
+        .args(["search", "gym"])
+        .assert()
+        .success()
+        .stdout("No appointments found.\n");
+
+    common::command()
+        .args(["search", "(", "--regex"])
+        .assert()
+        .failure()
+        .code(1);
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn agenda_should_group_the_days_of_the_period() {
+    common::setup();
+
+    common::helper_write_to_appointment_data_file(
+        b"09:00 Standup\n",
+        NaiveDate::from_ymd_opt(2024, 6, 11).unwrap(),
+    );
+    common::helper_write_to_appointment_data_file(
+        b"14:00 Dentist\n",
+        NaiveDate::from_ymd_opt(2024, 6, 13).unwrap(),
+    );
+
+    common::command()
+        .args(["agenda", "--from", "12/06/2024"])
+        .assert()
+        .success()
+        .stdout(
+            "Tuesday 11/06/2024\n  [09:00] Standup\n\nThursday 13/06/2024\n  [14:00] Dentist\n",
+        );
+
+    common::command()
+        .args([
+            "agenda",
+            "--days",
+            "2",
+            "--from",
+            "11/06/2024",
+            "--show-empty",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "Tuesday 11/06/2024\n  [09:00] Standup\n\nWednesday 12/06/2024\n  No appointments.\n",
+        );
+
+    common::command()
+        .args(["agenda", "--month", "--grid", "--from", "12/06/2024"])
+        .assert()
+        .success()
+        .stdout(
+            "June 2024\n\
+            Mo  Tu  We  Th  Fr  Sa  Su\n                     1   2\n 3   4   5   6   7   8   9\n\
+            10  11* 12  13* 14  15  16\n17  18  19  20  21  22  23\n24  25  26  27  28  29  30\n",
+        );
+
+    common::command()
+        .args(["agenda", "--week", "--from", "01/01/2024"])
+        .assert()
+        .success()
+        .stdout("No appointments found.\n");
+
+    common::command()
+        .args(["agenda", "--week", "--month"])
+        .assert()
+        .failure()
+        .code(2);
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn add_should_accept_natural_times() {
+    common::setup();
+
+    common::command()
+        .args([
+            "add",
+            "-d",
+            "Review the budget",
+            "-t",
+            "2:30pm",
+            "-c",
+            "09:00",
+        ])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["add", "-d", "Stretch", "-t", "in 45 minutes", "-c", "09:00"])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["add", "-d", "Lunch", "-t", "noon", "-c", "09:00"])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["list", "-c", "09:00"])
+        .assert()
+        .success()
+        .stdout("[09:45] Stretch\n[12:00] Lunch\n[14:30] Review the budget\n");
+
+    common::command()
+        .args(["add", "--stdin", "-c", "09:00"])
+        .write_stdin("tomorrow 3pm call Bob #work\n")
+        .assert()
+        .success();
+
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("[15:00] call Bob #work\n");
+
+    common::command()
+        .args(["add", "-d", "Call", "-t", "3", "-c", "09:00"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(
+            r#"error: invalid value '3' for '--time <HH:MM>': Ambiguous time "3", use "3am", "3pm" or "15:00"
+
+For more information, try '--help'.
+"#,
+        );
+
+    common::command()
+        .args(["add", "-d", "Call", "-t", "in 2h", "--date", "tomorrow"])
+        .assert()
+        .failure()
+        .stderr("Relative times cannot be combined with a date\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn invalid_lines_should_be_reported_and_quarantined() {
+    common::setup();
+
+    let date = Local::now().date_naive();
+    let path = common::appointments_file(date);
+    common::helper_write_raw_to_appointment_data_file(
+        b"{\"version\":1}\n{\"id\":1,\"time\":\"23:00\",\"description\":\"Read\"}\n{\"id\":2,\"time\":\"25:00\",\"description\":\"Late\"}\n",
+        date,
+    );
+
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("[23:00] Read\n")
+        .stderr(format!(
+            "Warning: line 3 of {} could not be read and was kept as is. Hour should be between 0 and 23\n\
+            Run \"todayiwill doctor\" to check every file.\n",
+            path.display()
+        ));
+
+    common::command()
+        .args(["doctor"])
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(format!(
+            "{}\n  Line 3: Hour should be between 0 and 23\n",
+            path.display()
+        ))
+        .stderr("1 invalid lines found in 1 files. Run with --quarantine to move them aside.\n");
+
+    common::command()
+        .args(["check", "--quarantine"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n  Line 3: Hour should be between 0 and 23\n1 invalid lines moved to .invalid files.\n",
+            path.display()
+        ));
+
+    common::command()
+        .args(["doctor"])
+        .assert()
+        .success()
+        .stdout("No problems found.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn concurrent_adds_should_all_be_kept() {
+    common::setup();
+
+    let children: Vec<std::process::Child> = (0..16)
+        .map(|hour| {
+            common::process_command()
+                .args(["add", "--date", "tomorrow", "--time"])
+                .arg(format!("{hour:02}:10"))
+                .args(["--description", &format!("Appointment {hour}")])
+                .stdout(std::process::Stdio::null())
+                .spawn()
+                .expect("Failed to run the app")
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().expect("Failed to wait for the app").success());
+    }
+
+    let output = common::command()
+        .args(["history", "--date", "tomorrow"])
+        .output()
+        .expect("Failed to run the app");
+    let lines: Vec<String> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    assert_eq!(16, lines.len());
+    assert_eq!("[00:10] Appointment 0", lines[0]);
+    assert_eq!("[15:10] Appointment 15", lines[15]);
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn changes_should_be_undone_and_redone() {
+    common::setup();
+    let today = Local::now().format("%d/%m/%Y").to_string();
+
+    common::command()
+        .args(["undo"])
+        .assert()
+        .failure()
+        .stderr("There is nothing to undo.\n");
+
+    for (time, description) in [("21:00", "Read"), ("22:00", "Sleep")] {
+        common::command()
+            .args(["add", "-c", "08:00", "-t", time, "-d", description])
+            .assert()
+            .success();
+    }
+    common::command()
+        .args(["clear", "-c", "08:00"])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["undo"])
+        .assert()
+        .success()
+        .stdout(format!("Undone: clear 2 appointments on {today}\n"));
+    common::command()
+        .args(["list", "-c", "08:00"])
+        .assert()
+        .success()
+        .stdout("[21:00] Read\n[22:00] Sleep\n");
+
+    common::command()
+        .args(["undo"])
+        .assert()
+        .success()
+        .stdout(format!("Undone: add [22:00] Sleep on {today}\n"));
+    common::command()
+        .args(["redo"])
+        .assert()
+        .success()
+        .stdout(format!("Redone: add [22:00] Sleep on {today}\n"));
+    common::command()
+        .args(["list", "-c", "08:00"])
+        .assert()
+        .success()
+        .stdout("[21:00] Read\n[22:00] Sleep\n");
+
+    let output = common::command()
+        .args(["log", "-n", "2"])
+        .output()
+        .expect("Failed to run the app");
+    let lines: Vec<String> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    assert_eq!(2, lines.len());
+    assert!(lines[0].starts_with(&format!("#3 {today} ")));
+    assert!(lines[0].ends_with(&format!(" clear 2 appointments on {today} (undone)")));
+    assert!(lines[1].ends_with(&format!(" add [22:00] Sleep on {today}")));
+
+    common::command()
+        .args(["remove", "-c", "08:00", "-t", "21:00"])
+        .assert()
+        .success();
+    common::command()
+        .args(["redo"])
+        .assert()
+        .failure()
+        .stderr("There is nothing to redo.\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn add_from_stdin_should_be_undone_at_once() {
+    common::setup();
+    let today = Local::now().date_naive();
+    let tomorrow = today + Days::new(1);
+    let dates = format!(
+        "{}, {}",
+        today.format("%d/%m/%Y"),
+        tomorrow.format("%d/%m/%Y")
+    );
+
+    common::command()
+        .args(["add", "--current-time", "08:00", "--stdin"])
+        .write_stdin("12:00 Lunch\ntomorrow 10am Dentist\n")
+        .assert()
+        .success();
+
+    common::command()
+        .args(["undo"])
+        .assert()
+        .success()
+        .stdout(format!("Undone: add 2 appointments on {dates}\n"));
+    common::command()
+        .args(["list", "--current-time", "08:00"])
+        .assert()
+        .success()
+        .stdout("There are no appointments added for today.\n");
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("There were no appointments added in this day.\n");
+
+    common::command()
+        .args(["redo"])
+        .assert()
+        .success()
+        .stdout(format!("Redone: add 2 appointments on {dates}\n"));
+    common::command()
+        .args(["history", "--date", "tomorrow"])
+        .assert()
+        .success()
+        .stdout("[10:00] Dentist\n");
+
+    common::remove_all_appointment_files();
+}
+
+#[test]
+#[serial]
+fn time_zones_should_convert_the_appointments() {
+    common::setup();
+    let date = Zone::from("Asia/Tokyo").unwrap().today() + Days::new(7);
+    let day_before = (date - Days::new(1)).format("%d/%m/%Y").to_string();
+    let date = date.format("%d/%m/%Y").to_string();
+
+    common::command()
+        .args(["add", "--description", "Sync", "--time", "09:00"])
+        .args(["--date", &date, "--zone", "Asia/Tokyo"])
+        .assert()
+        .success();
+    common::command()
+        .args(["add", "--description", "Dinner", "--time", "20:00"])
+        .args(["--date", &day_before])
+        .assert()
+        .success();
+
+    common::command()
+        .args(["history", "--date", &date, "--tz", "Asia/Tokyo"])
+        .assert()
+        .success()
+        .stdout("[09:00] Sync\n");
+    common::command()
+        .args([
+            "history",
+            "--date",
+            &day_before,
+            "--tz",
+            "America/Sao_Paulo",
+        ])
+        .assert()
+        .success()
+        .stdout("[20:00] Dinner\n[21:00] Sync\n");
+
+    common::helper_write_config_file(
+        "time_zone = \"America/Sao_Paulo\"\n[calendars.default]\ntime_zone = \"Asia/Tokyo\"\n",
+    );
+    common::command()
+        .args(["history", "--date", &day_before])
+        .assert()
+        .success()
+        .stdout("[08:00] Dinner\n[21:00] Sync\n");
+    common::command()
+        .args(["history", "--date", &day_before, "--tz", "Asia/Tokyo"])
+        .assert()
+        .success()
+        .stdout("[20:00] Dinner\n");
+
+    common::command()
+        .args([
+            "add",
+            "--description",
+            "Call",
+            "--time",
+            "in 1h",
+            "--zone",
+            "UTC",
+        ])
+        .assert()
+        .failure()
+        .stderr("Relative times cannot be combined with a time zone\n");
+    common::command()
+        .args(["list", "--tz", "Mars/Olympus"])
+        .assert()
+        .failure()
+        .stderr("error: invalid value 'Mars/Olympus' for '--tz <ZONE>': Unknown time zone \"Mars/Olympus\", use an IANA name like \"Europe/Berlin\"\n\nFor more information, try '--help'.\n");
+
+    common::setup();
+}
+
+#[test]
+#[serial]
+fn tui_should_need_a_terminal() {
+    common::setup();
+
+    common::command()
+        .arg("tui")
+        .assert()
+        .failure()
+        .stderr("The interactive view needs a terminal.\n");
+}
+
+#[test]
+#[serial]
+fn next_should_print_the_next_appointment() {
+    common::setup();
+    common::helper_write_to_appointment_current_day_data_file(
+        b"09:30 Daily standup\n11:00 Lunch #food\n",
+    );
+
+    common::command()
+        .args(["next", "--current-time", "09:00"])
+        .assert()
+        .code(0)
+        .stdout("09:30 Daily standup (in 30m)\n");
+
+    common::command()
+        .args(["next", "--current-time", "09:26"])
+        .assert()
+        .code(4)
+        .stdout("09:30 Daily standup (in 4m)\n");
+
+    common::command()
+        .args(["next", "--current-time", "09:30", "--template", "{desc}"])
+        .assert()
+        .code(5)
+        .stdout("Daily standup\n");
+
+    common::command()
+        .args([
+            "next",
+            "--current-time",
+            "09:31",
+            "--soon",
+            "2h",
+            "--template",
+            "{time} {tags} {mins}",
+        ])
+        .assert()
+        .code(4)
+        .stdout("11:00 #food 89\n");
+
+    common::command()
+        .args(["next", "--current-time", "11:01"])
+        .assert()
+        .code(3)
+        .stdout("");
+
+    common::command()
+        .args(["next", "--current-time", "09:00", "--template", "{when}"])
+        .assert()
+        .code(1)
+        .stderr("Unknown placeholder {when}, expected {time}, {end}, {desc}, {mins} or {tags}\n");
+}
+
+#[test]
+#[serial]
+fn next_should_look_at_the_next_day_without_writing() {
+    common::setup();
+    let tomorrow = Local::now().date_naive() + Days::new(1);
+    common::helper_write_to_appointment_data_file(b"00:10 Deploy\n", tomorrow);
+    let legacy = fs::read_to_string(common::appointments_file(tomorrow)).unwrap();
+
+    common::command()
+        .args(["next", "--current-time", "23:50", "--soon", "30m"])
+        .assert()
+        .code(4)
+        .stdout("00:10 Deploy (in 20m)\n")
+        .stderr("");
+    assert_eq!(
+        legacy,
+        fs::read_to_string(common::appointments_file(tomorrow)).unwrap()
+    );
+
+    common::command()
+        .args(["next", "--current-time", "09:00", "--template", "{when}"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("Unknown placeholder {when}, expected {time}, {end}, {desc}, {mins} or {tags}\n");
+}
+
+#[test]
+#[serial]
+fn next_should_use_the_template_of_the_config_file() {
+    common::setup();
+    common::helper_write_config_file("next_template = \"{time}: {desc}\"\nlead_time = 60\n");
+    common::helper_write_to_appointment_current_day_data_file(b"09:30 Daily standup\n");
+
+    common::command()
+        .args(["next", "--current-time", "09:00"])
+        .assert()
+        .code(4)
+        .stdout("09:30: Daily standup\n");
+}
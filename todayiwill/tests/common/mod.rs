@@ -4,31 +4,72 @@ use std::{
     path::PathBuf,
 };
 
+use assert_cmd::Command;
 use chrono::{Local, NaiveDate};
+use todayiwill::{
+    storage::{self, Format},
+    Appointment,
+};
 
 /// Provides the startup scripts for each test execution
 pub fn setup() {
-    remove_all_appointment_files()
+    remove_all_appointment_files();
+    remove_config_file()
+}
+
+/// Returns the app command using the testing data dir and config file
+pub fn command() -> Command {
+    let mut command = Command::cargo_bin("todayiwill").unwrap();
+    command
+        .env("TODAYIWILL_DATA_DIR", app_data_dir())
+        .env("TODAYIWILL_CONFIG", config_file());
+    command
+}
+
+/// Returns the app command like `command`, as a standard command that can be spawned
+pub fn process_command() -> std::process::Command {
+    let mut command = std::process::Command::new(assert_cmd::cargo::cargo_bin("todayiwill"));
+    command
+        .env("TODAYIWILL_DATA_DIR", app_data_dir())
+        .env("TODAYIWILL_CONFIG", config_file());
+    command
 }
 
-/// Clears the appointments file
+/// Clears the appointments files, including the ones of the named calendars
 pub fn remove_all_appointment_files() {
     let app_data_dir = app_data_dir();
     if !app_data_dir.exists() {
         return;
     }
-    for entry in app_data_dir.read_dir().expect("Failed to access data dir") {
-        if let Ok(entry) = entry {
-            match fs::remove_file(entry.path()) {
-                Err(error) => panic!("Failed to remove data file. {error}"),
-                _ => return,
-            }
+    for entry in app_data_dir
+        .read_dir()
+        .expect("Failed to access data dir")
+        .flatten()
+    {
+        let path = entry.path();
+        let result = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+        if let Err(error) = result {
+            panic!("Failed to remove data file. {error}")
         }
     }
 }
 
-/// Writes pre-formatted text to the appointment file
+/// Writes appointments in the `HH:MM description` format to the appointment file, converting
+/// them to the current storage format
 pub fn helper_write_to_appointment_data_file(content: &[u8], date: NaiveDate) {
+    let (_, appointments) = storage::parse(std::str::from_utf8(content).unwrap());
+    let appointments: Vec<Appointment> = appointments.into_iter().flatten().collect();
+    let appointments: Vec<&Appointment> = appointments.iter().collect();
+    let content = storage::serialize(&appointments, Format::Legacy, 0).unwrap();
+    helper_write_raw_to_appointment_data_file(content.as_bytes(), date)
+}
+
+/// Writes pre-formatted text to the appointment file
+pub fn helper_write_raw_to_appointment_data_file(content: &[u8], date: NaiveDate) {
     let data_file = appointments_file(date);
     fs::create_dir_all(data_file.parent().unwrap()).expect("Failed to create data dir");
     let mut file = File::create(data_file.to_str().unwrap()).expect("Failed to create test file");
@@ -40,16 +81,31 @@ pub fn helper_write_to_appointment_current_day_data_file(content: &[u8]) {
     helper_write_to_appointment_data_file(content, Local::now().date_naive())
 }
 
+/// Writes the config file used by the app command
+pub fn helper_write_config_file(content: &str) {
+    fs::write(config_file(), content).expect("Failed to write config file")
+}
+
+/// Removes the config file used by the app command
+pub fn remove_config_file() {
+    let config_file = config_file();
+    if config_file.exists() {
+        fs::remove_file(config_file).expect("Failed to remove config file")
+    }
+}
+
 /// Returns the appointment file corresponding to the date
-fn appointments_file(date: NaiveDate) -> PathBuf {
+pub fn appointments_file(date: NaiveDate) -> PathBuf {
     let current_date_code = date.format("%d%m%Y").to_string();
-    dirs::data_dir()
-        .unwrap()
-        .join("todayiwill")
-        .join(format!("appointments_{current_date_code}.txt"))
+    app_data_dir().join(format!("appointments_{current_date_code}.txt"))
+}
+
+/// Returns the app data dir for testing, kept apart from the user data
+pub fn app_data_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("data")
 }
 
-/// Returns the app data dir for testing
-fn app_data_dir() -> PathBuf {
-    dirs::data_dir().unwrap().join("todayiwill")
+/// Returns the config file for testing, kept apart from the user config
+pub fn config_file() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("config.toml")
 }